@@ -1,36 +1,314 @@
-use axum::{extract::State, http::StatusCode, routing::{get, post}, Json, Router};
-use chrono::{DateTime, Local};
+use axum::{
+    extract::{FromRequest, Multipart, Path, Request, State},
+    http::{header, HeaderMap, StatusCode},
+    middleware::Next,
+    response::{
+        sse::{Event, KeepAlive, Sse},
+        AppendHeaders, IntoResponse, Redirect, Response,
+    },
+    routing::{get, post, put},
+    Extension, Json, Router,
+};
+use chrono::{DateTime, Local, Utc};
+use serde::Serialize;
+use std::collections::BTreeMap;
 use std::sync::Arc;
 use tokio::sync::Mutex;
+use tokio_stream::wrappers::{errors::BroadcastStreamRecvError, BroadcastStream};
+use tokio_stream::{Stream, StreamExt};
 
-use fresh_post::{config::Config, service, storage};
+use fresh_post::config::{Config, FieldError, ValidationErrors};
+use fresh_post::notify::Notifier;
+use fresh_post::{archive, audit, auth, cookies, etag, diagnostics, metrics, overrides, proxy, ratelimit, readiness, reload, schema, secrets, service, session, storage};
 
 #[derive(Clone)]
 pub struct AppState {
     pub paths: storage::Paths,
     pub last_run: Arc<Mutex<Option<DateTime<Local>>>>,
+    pub started_at: DateTime<Local>,
+    pub auth: Arc<auth::Auth>,
+    /// URL prefix the app is mounted under (empty at the root)
+    pub base_path: String,
+    /// Mark cookies `Secure`; set when serving HTTPS
+    pub secure_cookies: bool,
+    /// Serializes config writes, so `If-Match` checks and saves don't interleave
+    pub config_lock: Arc<Mutex<()>>,
+    /// The config in effect, reloaded when `config.toml` is edited
+    pub config: Arc<reload::LiveConfig>,
+}
+
+impl AppState {
+    /// Attributes shared by the session and CSRF cookies
+    fn cookie_attributes(&self) -> String {
+        let path = if self.base_path.is_empty() { "/" } else { &self.base_path };
+        let secure = if self.secure_cookies { "; Secure" } else { "" };
+        format!("Path={}; SameSite=Strict{}", path, secure)
+    }
 }
 
 pub fn router(state: AppState) -> Router {
     Router::new()
-        .route("/api/config", get(get_config).post(update_config))
+        .route("/api/login", post(login))
+        .route("/api/logout", post(logout))
+        .route("/api/auth", get(get_auth))
+        .route("/api/config", get(get_config).post(update_config).patch(patch_config))
+        .route("/api/config/audit", get(get_config_audit))
+        .route("/api/config/status", get(get_config_status))
+        .route("/api/config/schema", get(get_config_schema))
+        .route("/api/events", get(get_events))
         .route("/api/jobs", get(get_jobs))
         .route("/api/run", post(run_scrape))
+        .route("/api/session", get(get_session))
+        .route("/api/proxies", get(get_proxies))
+        .route("/api/rate-limit", get(get_rate_limit))
+        .route("/metrics", get(get_metrics))
+        .route("/api/health", get(get_health))
+        .route("/healthz", get(get_healthz))
+        .route("/readyz", get(get_readyz))
+        .route("/api/runs", get(list_runs))
+        .route("/api/runs/:id/raw", get(get_run_raw))
+        .route("/api/cookies", put(upload_cookies))
+        .route("/api/cookies/status", get(get_cookie_status))
+        .route("/api/secrets", get(list_secrets))
+        .route("/api/secrets/:name", put(set_secret).delete(delete_secret))
         .with_state(state)
 }
 
 type ApiResult<T> = Result<Json<T>, (StatusCode, String)>;
 
-async fn get_config(State(state): State<AppState>) -> ApiResult<Config> {
-    let config = storage::load_config_or_default(&state.paths)
-        .map_err(internal_error)?;
-    Ok(Json(config))
+/// Let a request through only with a valid API token or login session. Pages
+/// redirect to the login form; API calls get 401 (403 for a missing CSRF token).
+pub async fn require_auth(State(state): State<AppState>, mut request: Request, next: Next) -> Response {
+    let path = request.uri().path();
+    match state.auth.authorize(request.method(), path, request.headers()) {
+        access if access.is_allowed() => {
+            // Handlers that record who did something read it from here
+            request.extensions_mut().insert(access);
+            next.run(request).await
+        }
+        auth::Access::CsrfRejected => (StatusCode::FORBIDDEN, "Missing or invalid CSRF token").into_response(),
+        _ if path.starts_with("/api/") || path == "/metrics" || path == "/readyz" => (
+            StatusCode::UNAUTHORIZED,
+            [(header::WWW_AUTHENTICATE, "Bearer")],
+            "Authentication required",
+        )
+            .into_response(),
+        _ => Redirect::to(&format!("{}/login", state.base_path)).into_response(),
+    }
+}
+
+#[derive(Debug, serde::Deserialize)]
+struct LoginRequest {
+    username: String,
+    password: String,
+}
+
+/// Start a browser session: sets the HttpOnly session cookie and the CSRF cookie
+/// the UI echoes back in `X-CSRF-Token`
+async fn login(State(state): State<AppState>, Json(request): Json<LoginRequest>) -> Result<Response, (StatusCode, String)> {
+    if !state.auth.login_enabled() {
+        return Err((StatusCode::NOT_FOUND, "Password login is not configured".to_string()));
+    }
+
+    let Some((session_id, session)) = state.auth.login(&request.username, &request.password) else {
+        tracing::warn!(username = %request.username, "failed login");
+        // Slow down password guessing
+        tokio::time::sleep(std::time::Duration::from_secs(1)).await;
+        return Err((StatusCode::UNAUTHORIZED, "Invalid username or password".to_string()));
+    };
+
+    tracing::info!(username = %session.username, "logged in");
+    let max_age = state.auth.session_ttl().num_seconds();
+    let attributes = state.cookie_attributes();
+    let cookies = AppendHeaders([
+        (
+            header::SET_COOKIE,
+            format!("{}={}; Max-Age={}; HttpOnly; {}", auth::SESSION_COOKIE, session_id, max_age, attributes),
+        ),
+        (
+            header::SET_COOKIE,
+            format!("{}={}; Max-Age={}; {}", auth::CSRF_COOKIE, session.csrf_token, max_age, attributes),
+        ),
+    ]);
+    let status = auth::AuthStatus {
+        enabled: true,
+        login_enabled: true,
+        username: Some(session.username),
+    };
+    Ok((cookies, Json(status)).into_response())
+}
+
+async fn logout(State(state): State<AppState>, headers: HeaderMap) -> impl IntoResponse {
+    if let Some(session_id) = auth::cookie(&headers, auth::SESSION_COOKIE) {
+        state.auth.logout(session_id);
+    }
+    let attributes = state.cookie_attributes();
+    let cookies = AppendHeaders([
+        (header::SET_COOKIE, format!("{}=; Max-Age=0; HttpOnly; {}", auth::SESSION_COOKIE, attributes)),
+        (header::SET_COOKIE, format!("{}=; Max-Age=0; {}", auth::CSRF_COOKIE, attributes)),
+    ]);
+    (cookies, StatusCode::NO_CONTENT)
+}
+
+/// Whether auth is on and who is logged in
+async fn get_auth(State(state): State<AppState>, headers: HeaderMap) -> Json<auth::AuthStatus> {
+    Json(state.auth.status(&headers))
+}
+
+/// The config and where each value came from (`default`, `file` or `env`)
+#[derive(Serialize)]
+struct ConfigView {
+    #[serde(flatten)]
+    config: Config,
+    sources: BTreeMap<String, overrides::Source>,
+}
+
+/// `config` with its version in `ETag` for use in `If-Match`. Proxy passwords
+/// are redacted; sending a redacted URL back keeps the stored one.
+fn config_response(state: &AppState, config: &Config) -> Response {
+    let mut redacted = config.clone();
+    redacted.proxy.urls = redacted.proxy.urls.iter().map(|url| proxy::redact_proxy_url(url)).collect();
+    let view = ConfigView {
+        config: redacted,
+        sources: state.config.sources(),
+    };
+    ([(header::ETAG, config.etag())], Json(view)).into_response()
+}
+
+async fn get_config(State(state): State<AppState>) -> Response {
+    config_response(&state, &state.config.current())
+}
+
+/// JSON Schema of the config, for validation and autocomplete
+async fn get_config_schema() -> Json<serde_json::Value> {
+    Json(schema::schema())
+}
+
+/// Whether the last edit to `config.toml` was applied, and the error if not
+async fn get_config_status(State(state): State<AppState>) -> Json<reload::ReloadStatus> {
+    Json(state.config.status())
 }
 
-async fn update_config(State(state): State<AppState>, Json(config): Json<Config>) -> ApiResult<Config> {
-    storage::save_config(&state.paths, &config)
+/// Server-sent events, one JSON object each: `config_changed`, `config_invalid`
+/// and `session_warning`. A session warning that still applies is sent on connect.
+async fn get_events(State(state): State<AppState>) -> Sse<impl Stream<Item = Result<Event, axum::Error>>> {
+    // A lagging client skips what it missed rather than ending the stream
+    let config_events = BroadcastStream::new(state.config.events())
+        .filter_map(|event| event.ok())
+        .map(|event| Event::default().json_data(event));
+    let notifier = Notifier::global();
+    // Subscribe first: a warning sent in between shows twice rather than not at all
+    let live = BroadcastStream::new(notifier.subscribe()).filter_map(|notification| match notification {
+        Ok(notification) => Some(notification),
+        Err(BroadcastStreamRecvError::Lagged(missed)) => {
+            metrics::Metrics::global().record_notifications(metrics::NotificationOutcome::Failed, missed);
+            None
+        }
+    });
+    let notifications = tokio_stream::iter(notifier.pending())
+        .chain(live)
+        .map(|notification| Event::default().json_data(notification));
+    Sse::new(config_events.merge(notifications)).keep_alive(KeepAlive::default())
+}
+
+/// Replace the whole config. `sources` is ignored, so a `GET` response can be
+/// edited and sent back as is.
+async fn update_config(
+    State(state): State<AppState>,
+    access: Option<Extension<auth::Access>>,
+    headers: HeaderMap,
+    Json(mut body): Json<serde_json::Value>,
+) -> Result<Response, Response> {
+    if let Some(body) = body.as_object_mut() {
+        body.remove("sources");
+    }
+    change_config(&state, access, &headers, "POST /api/config", |_| {
+        serde_json::from_value(body).map_err(|err| anyhow::anyhow!("Invalid config: {}", err))
+    })
+    .await
+}
+
+/// Change some values with a JSON Merge Patch (RFC 7396), e.g.
+/// `{"schedule": {"interval_hours": 2}}`; `null` resets a value to its default
+async fn patch_config(
+    State(state): State<AppState>,
+    access: Option<Extension<auth::Access>>,
+    headers: HeaderMap,
+    Json(patch): Json<serde_json::Value>,
+) -> Result<Response, Response> {
+    change_config(&state, access, &headers, "PATCH /api/config", |current| current.merge_patch(&patch)).await
+}
+
+/// Apply `update` to the stored config: 412 when `If-Match` names another
+/// version, 400 when `update` fails, 422 when the result doesn't validate or
+/// changes a field set by env. Saved changes go to the audit log.
+async fn change_config(
+    state: &AppState,
+    access: Option<Extension<auth::Access>>,
+    headers: &HeaderMap,
+    source: &str,
+    update: impl FnOnce(&Config) -> anyhow::Result<Config>,
+) -> Result<Response, Response> {
+    let _guard = state.config_lock.lock().await;
+    let current = state.config.current();
+    let current_etag = current.etag();
+
+    if let Some(if_match) = headers.get(header::IF_MATCH) {
+        let matches = if_match.to_str().is_ok_and(|value| etag::matches(value, &current_etag));
+        if !matches {
+            return Err((
+                StatusCode::PRECONDITION_FAILED,
+                [(header::ETAG, current_etag)],
+                "Config was changed by someone else; reload it and apply your change again",
+            )
+                .into_response());
+        }
+    }
+
+    let mut updated = update(&current).map_err(|err| bad_request(format!("{:#}", err)).into_response())?;
+    updated.proxy.urls = proxy::restore_redacted(&updated.proxy.urls, &current.proxy.urls);
+    let changes = audit::diff(&current, &updated);
+    let pinned: Vec<FieldError> = changes
+        .iter()
+        .filter_map(|change| state.config.overrides().iter().find(|item| item.field == change.field))
+        .map(|item| FieldError {
+            field: item.field.clone(),
+            message: format!("is set by {} and can't be changed here", item.var),
+        })
+        .collect();
+    if !pinned.is_empty() {
+        return Err((StatusCode::UNPROCESSABLE_ENTITY, Json(ValidationErrors { errors: pinned })).into_response());
+    }
+    if let Err(errors) = updated.validate() {
+        return Err((StatusCode::UNPROCESSABLE_ENTITY, Json(errors)).into_response());
+    }
+    let fields = changes.iter().map(|change| change.field.clone()).collect();
+    let (live, saved, save_source) = (state.config.clone(), updated.clone(), source.to_string());
+    tokio::task::spawn_blocking(move || live.save(saved, &save_source, fields))
+        .await
+        .map_err(|err| internal_error(err).into_response())?
+        .map_err(|err| internal_error(err).into_response())?;
+
+    if !changes.is_empty() {
+        let actor = access.map(|Extension(access)| access.actor()).unwrap_or_else(|| "anonymous".to_string());
+        let fields: Vec<&str> = changes.iter().map(|change| change.field.as_str()).collect();
+        tracing::info!(%actor, ?fields, "config changed");
+        if let Err(err) = storage::append_config_audit(&state.paths, &audit::AuditEntry::new(actor, source, changes)) {
+            tracing::error!(error = %format!("{:#}", err), "failed to write config audit log");
+        }
+    }
+
+    Ok(config_response(state, &updated))
+}
+
+/// Most recent entries shown by `/api/config/audit`
+const AUDIT_RECENT_ENTRIES: usize = 100;
+
+/// Recent config changes, newest first
+async fn get_config_audit(State(state): State<AppState>) -> ApiResult<Vec<audit::AuditEntry>> {
+    let entries = storage::load_config_audit(&state.paths)
         .map_err(internal_error)?;
-    Ok(Json(config))
+    Ok(Json(entries.into_iter().rev().take(AUDIT_RECENT_ENTRIES).collect()))
 }
 
 async fn get_jobs(State(state): State<AppState>) -> ApiResult<storage::JobsSnapshot> {
@@ -40,7 +318,7 @@ async fn get_jobs(State(state): State<AppState>) -> ApiResult<storage::JobsSnaps
 }
 
 async fn run_scrape(State(state): State<AppState>) -> ApiResult<service::ScrapeSummary> {
-    let summary = service::run_scrape_once(&state.paths).await
+    let summary = service::run_scrape_with_config(&state.paths, state.config.current()).await
         .map_err(internal_error)?;
 
     let mut guard = state.last_run.lock().await;
@@ -49,6 +327,243 @@ async fn run_scrape(State(state): State<AppState>) -> ApiResult<service::ScrapeS
     Ok(Json(summary))
 }
 
+async fn get_session(State(state): State<AppState>) -> ApiResult<session::SessionReport> {
+    let report = session::check_session(&state.paths)
+        .map_err(internal_error)?;
+    Ok(Json(report))
+}
+
+/// Health of the configured proxies (passwords redacted)
+async fn get_proxies() -> Json<Vec<proxy::ProxyHealth>> {
+    Json(proxy::ProxyPool::shared().health())
+}
+
+/// Prometheus scrape endpoint
+async fn get_metrics() -> impl IntoResponse {
+    ([(header::CONTENT_TYPE, metrics::CONTENT_TYPE)], metrics::Metrics::global().render())
+}
+
+/// Configured request rate and the remaining budget per host
+async fn get_rate_limit() -> Json<ratelimit::RateLimitStatus> {
+    Json(ratelimit::RateLimiter::shared().status())
+}
+
+async fn list_runs(State(state): State<AppState>) -> ApiResult<Vec<archive::ArchiveEntry>> {
+    let runs = archive::list_runs(&state.paths.archive)
+        .map_err(internal_error)?;
+    Ok(Json(runs))
+}
+
+/// Archived (redacted) HTML of a run. Served sandboxed so the page's scripts never run.
+async fn get_run_raw(
+    State(state): State<AppState>,
+    Path(run_id): Path<String>,
+) -> Result<impl IntoResponse, (StatusCode, String)> {
+    let html = archive::load_run_html(&state.paths.archive, &run_id)
+        .map_err(bad_request)?
+        .ok_or_else(|| (StatusCode::NOT_FOUND, format!("No archived HTML for run {}", run_id)))?;
+
+    Ok((
+        [
+            (header::CONTENT_TYPE, "text/html; charset=utf-8"),
+            (header::CONTENT_SECURITY_POLICY, "sandbox"),
+        ],
+        html,
+    ))
+}
+
+/// Recent runs shown by `/api/health`
+const HEALTH_RECENT_RUNS: usize = 10;
+
+#[derive(Debug, Serialize)]
+struct HealthReport {
+    healthy: bool,
+    latest: Option<diagnostics::ParseHealth>,
+    recent: Vec<diagnostics::ParseHealth>,
+}
+
+async fn get_health(State(state): State<AppState>) -> ApiResult<HealthReport> {
+    let history = storage::load_parse_history(&state.paths)
+        .map_err(internal_error)?;
+
+    let recent: Vec<_> = history.iter().rev().take(HEALTH_RECENT_RUNS).cloned().collect();
+    let latest = recent.first().cloned();
+
+    Ok(Json(HealthReport {
+        healthy: latest.as_ref().map(|health| health.is_healthy()).unwrap_or(true),
+        latest,
+        recent,
+    }))
+}
+
+/// Liveness: the process is up and serving requests
+async fn get_healthz() -> Json<serde_json::Value> {
+    Json(serde_json::json!({ "status": "ok" }))
+}
+
+/// Readiness: 503 with the failing checks when scrapes are stale, storage is
+/// unwritable or the session expired
+async fn get_readyz(State(state): State<AppState>) -> (StatusCode, Json<readiness::ReadinessReport>) {
+    let last_run = *state.last_run.lock().await;
+    let report = readiness::check(&state.paths, &state.config.current().schedule, last_run, state.started_at);
+    let status = if report.is_ready() {
+        StatusCode::OK
+    } else {
+        StatusCode::SERVICE_UNAVAILABLE
+    };
+    (status, Json(report))
+}
+
+#[derive(Debug, Serialize)]
+struct CookieUpload {
+    format: cookies::CookieFormat,
+    cookie_count: usize,
+    has_session_cookie: bool,
+    encrypted: bool,
+}
+
+#[derive(Debug, Serialize)]
+struct CookieStatus {
+    present: bool,
+    encrypted: bool,
+    format: Option<cookies::CookieFormat>,
+    has_session_cookie: bool,
+    cookies: Vec<cookies::CookieInfo>,
+}
+
+/// Accepts the cookie file as the raw request body (pasted text or `--data-binary @file`)
+/// or as the first field of a multipart upload
+async fn upload_cookies(State(state): State<AppState>, request: Request) -> ApiResult<CookieUpload> {
+    let is_multipart = request
+        .headers()
+        .get(header::CONTENT_TYPE)
+        .and_then(|value| value.to_str().ok())
+        .map(|value| value.starts_with("multipart/form-data"))
+        .unwrap_or(false);
+
+    let content = if is_multipart {
+        let mut multipart = Multipart::from_request(request, &())
+            .await
+            .map_err(|err| bad_request(err.body_text()))?;
+        let field = multipart
+            .next_field()
+            .await
+            .map_err(|err| bad_request(err.body_text()))?
+            .ok_or_else(|| bad_request("Multipart upload has no cookie file"))?;
+        field.text().await.map_err(|err| bad_request(err.body_text()))?
+    } else {
+        String::from_request(request, &())
+            .await
+            .map_err(|err| bad_request(err.body_text()))?
+    };
+
+    let format = cookies::detect_format(&content);
+    let parsed = cookies::parse_cookies(&content)
+        .map_err(|err| bad_request(format!("{:#}", err)))?;
+    if parsed.is_empty() {
+        return Err(bad_request("No cookies found in upload"));
+    }
+
+    let encrypted = storage::save_cookies(&state.paths, &content)
+        .map_err(internal_error)?;
+
+    Ok(Json(CookieUpload {
+        format,
+        cookie_count: parsed.len(),
+        has_session_cookie: parsed.iter().any(|cookie| cookie.name == session::SESSION_COOKIE),
+        encrypted,
+    }))
+}
+
+async fn get_cookie_status(State(state): State<AppState>) -> ApiResult<CookieStatus> {
+    let encrypted = storage::open_secret_store(&state.paths)
+        .map_err(internal_error)?
+        .is_some();
+
+    let Some(content) = storage::load_cookie_content(&state.paths).map_err(internal_error)? else {
+        return Ok(Json(CookieStatus {
+            present: false,
+            encrypted,
+            format: None,
+            has_session_cookie: false,
+            cookies: Vec::new(),
+        }));
+    };
+
+    let parsed = cookies::parse_cookies(&content)
+        .map_err(|err| internal_error(format!("{:#}", err)))?;
+    let now = Utc::now();
+
+    Ok(Json(CookieStatus {
+        present: true,
+        encrypted,
+        format: Some(cookies::detect_format(&content)),
+        has_session_cookie: parsed.iter().any(|cookie| cookie.name == session::SESSION_COOKIE),
+        cookies: parsed
+            .iter()
+            .map(|cookie| cookies::CookieInfo::from_cookie(cookie, now))
+            .collect(),
+    }))
+}
+
+#[derive(Debug, Serialize)]
+struct SecretNames {
+    names: Vec<String>,
+}
+
+fn require_secret_store(state: &AppState) -> Result<secrets::SecretStore, (StatusCode, String)> {
+    storage::open_secret_store(&state.paths)
+        .map_err(internal_error)?
+        .ok_or_else(|| {
+            (
+                StatusCode::CONFLICT,
+                format!(
+                    "Secret store is not configured; set {} or {}",
+                    secrets::KEY_ENV,
+                    secrets::KEY_FILE_ENV
+                ),
+            )
+        })
+}
+
+/// Names of stored secrets; values are never returned
+async fn list_secrets(State(state): State<AppState>) -> ApiResult<SecretNames> {
+    let store = require_secret_store(&state)?;
+    let names = store.names().map_err(internal_error)?;
+    Ok(Json(SecretNames { names }))
+}
+
+/// Store the raw request body as the secret `name`
+async fn set_secret(
+    State(state): State<AppState>,
+    Path(name): Path<String>,
+    value: String,
+) -> Result<StatusCode, (StatusCode, String)> {
+    let store = require_secret_store(&state)?;
+    let value = value.trim_end_matches(['\r', '\n']);
+    if value.is_empty() {
+        return Err(bad_request("Secret value is empty"));
+    }
+    store.set(&name, value).map_err(internal_error)?;
+    Ok(StatusCode::NO_CONTENT)
+}
+
+async fn delete_secret(
+    State(state): State<AppState>,
+    Path(name): Path<String>,
+) -> Result<StatusCode, (StatusCode, String)> {
+    let store = require_secret_store(&state)?;
+    if store.remove(&name).map_err(internal_error)? {
+        Ok(StatusCode::NO_CONTENT)
+    } else {
+        Err((StatusCode::NOT_FOUND, format!("Secret '{}' is not set", name)))
+    }
+}
+
+fn bad_request<E: std::fmt::Display>(err: E) -> (StatusCode, String) {
+    (StatusCode::BAD_REQUEST, err.to_string())
+}
+
 fn internal_error<E: std::fmt::Display>(err: E) -> (StatusCode, String) {
     (StatusCode::INTERNAL_SERVER_ERROR, err.to_string())
 }
@@ -0,0 +1,247 @@
+use crate::config::ArchiveConfig;
+use anyhow::{Context, Result};
+use chrono::{DateTime, Local};
+use flate2::read::GzDecoder;
+use flate2::write::GzEncoder;
+use flate2::Compression;
+use regex::Regex;
+use serde::Serialize;
+use std::fs;
+use std::io::{Read, Write};
+use std::path::{Path, PathBuf};
+use std::sync::OnceLock;
+
+const ARCHIVE_EXTENSION: &str = "html.gz";
+
+/// Replacement for scrubbed values
+pub const SCRUBBED: &str = "[scrubbed]";
+
+/// Patterns for session tokens, CSRF values and profile data in fetched pages.
+/// The logged-in view embeds JSON HTML-escaped, so quotes may be `"` or `&quot;`.
+fn scrub_patterns() -> &'static [Regex] {
+    static PATTERNS: OnceLock<Vec<Regex>> = OnceLock::new();
+    PATTERNS.get_or_init(|| {
+        let q = r#"(?:"|&quot;)"#;
+        let json_string_fields = [
+            "csrfToken", "csrf", "clientPageInstanceId", "trackingId", "trackingToken",
+            "firstName", "lastName", "publicIdentifier", "emailAddress", "headline",
+            "occupation", "picture", "profilePicture",
+        ];
+
+        let mut patterns: Vec<Regex> = json_string_fields
+            .iter()
+            .map(|field| {
+                Regex::new(&format!(r#"({q}{field}{q}\s*:\s*{q})(.*?)({q})"#))
+                    .expect("valid scrub pattern")
+            })
+            .collect();
+
+        patterns.extend(
+            [
+                // <meta name="csrf-token" content="..."> and similar
+                r#"(<meta[^>]+name="[^"]*(?:csrf|token|pageKey|member)[^"]*"[^>]+content=")([^"]*)(")"#,
+                r#"(<input[^>]+name="[^"]*(?:csrf|token)[^"]*"[^>]+value=")([^"]*)(")"#,
+                // Session cookies and CSRF tokens that appear inline
+                r#"(li_at=)([^;&"'\s]+)()"#,
+                r#"(JSESSIONID=)([^;&"'\s]+)()"#,
+                r#"(ajax:)(\d+)()"#,
+                // Member and profile identifiers
+                r#"(urn:li:(?:member|fsd_profile|fs_miniProfile|fs_profile):)([A-Za-z0-9_-]+)()"#,
+                r#"(linkedin\.com/in/)([^/"'?&\s]+)()"#,
+            ]
+            .iter()
+            .map(|pattern| Regex::new(pattern).expect("valid scrub pattern")),
+        );
+
+        patterns
+    })
+}
+
+/// Remove tokens, CSRF values and profile data from a fetched page
+pub fn redact_html(html: &str) -> String {
+    let replacement = format!("${{1}}{}${{3}}", SCRUBBED);
+    scrub_patterns()
+        .iter()
+        .fold(html.to_string(), |text, pattern| {
+            pattern.replace_all(&text, replacement.as_str()).into_owned()
+        })
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct ArchiveEntry {
+    pub run_id: String,
+    pub compressed_bytes: u64,
+    pub archived_at: DateTime<Local>,
+}
+
+/// Run ids are generated by us, but they also arrive in URLs; only allow safe file names
+fn validate_run_id(run_id: &str) -> Result<()> {
+    let valid = !run_id.is_empty()
+        && run_id
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_');
+    if !valid {
+        anyhow::bail!("Invalid run id: {:?}", run_id);
+    }
+    Ok(())
+}
+
+fn archive_path(dir: &Path, run_id: &str) -> PathBuf {
+    dir.join(format!("{}.{}", run_id, ARCHIVE_EXTENSION))
+}
+
+/// Redact and gzip the HTML of a run, then prune old archives
+pub fn save_run_html(dir: &Path, run_id: &str, html: &str, limits: &ArchiveConfig) -> Result<ArchiveEntry> {
+    validate_run_id(run_id)?;
+    fs::create_dir_all(dir)
+        .with_context(|| format!("Failed to create archive dir: {:?}", dir))?;
+
+    let mut encoder = GzEncoder::new(Vec::new(), Compression::default());
+    encoder
+        .write_all(redact_html(html).as_bytes())
+        .context("Failed to compress HTML")?;
+    let compressed = encoder.finish().context("Failed to compress HTML")?;
+
+    let path = archive_path(dir, run_id);
+    fs::write(&path, &compressed)
+        .with_context(|| format!("Failed to write archive: {:?}", path))?;
+
+    prune(dir, limits)?;
+
+    Ok(ArchiveEntry {
+        run_id: run_id.to_string(),
+        compressed_bytes: compressed.len() as u64,
+        archived_at: Local::now(),
+    })
+}
+
+/// Decompressed (already redacted) HTML of a run, if it is still archived
+pub fn load_run_html(dir: &Path, run_id: &str) -> Result<Option<String>> {
+    validate_run_id(run_id)?;
+    let path = archive_path(dir, run_id);
+    if !path.exists() {
+        return Ok(None);
+    }
+
+    let file = fs::File::open(&path)
+        .with_context(|| format!("Failed to open archive: {:?}", path))?;
+    let mut html = String::new();
+    GzDecoder::new(file)
+        .read_to_string(&mut html)
+        .with_context(|| format!("Failed to decompress archive: {:?}", path))?;
+
+    Ok(Some(html))
+}
+
+/// Archived runs, newest first
+pub fn list_runs(dir: &Path) -> Result<Vec<ArchiveEntry>> {
+    if !dir.exists() {
+        return Ok(Vec::new());
+    }
+
+    let suffix = format!(".{}", ARCHIVE_EXTENSION);
+    let mut entries = Vec::new();
+    for entry in fs::read_dir(dir).with_context(|| format!("Failed to list archive dir: {:?}", dir))? {
+        let entry = entry?;
+        let file_name = entry.file_name().to_string_lossy().to_string();
+        let Some(run_id) = file_name.strip_suffix(&suffix) else {
+            continue;
+        };
+        let metadata = entry.metadata()?;
+        entries.push(ArchiveEntry {
+            run_id: run_id.to_string(),
+            compressed_bytes: metadata.len(),
+            archived_at: metadata.modified().map(DateTime::from).unwrap_or_else(|_| Local::now()),
+        });
+    }
+
+    // Run ids start with a timestamp, so they sort chronologically
+    entries.sort_by(|a, b| b.run_id.cmp(&a.run_id));
+    Ok(entries)
+}
+
+/// Delete the oldest archives beyond the configured count or total size
+pub fn prune(dir: &Path, limits: &ArchiveConfig) -> Result<()> {
+    let mut total_bytes = 0u64;
+    for (index, entry) in list_runs(dir)?.iter().enumerate() {
+        total_bytes += entry.compressed_bytes;
+        let over_count = index >= limits.max_runs;
+        let over_size = limits.max_total_bytes > 0 && total_bytes > limits.max_total_bytes && index > 0;
+        if over_count || over_size {
+            let path = archive_path(dir, &entry.run_id);
+            fs::remove_file(&path)
+                .with_context(|| format!("Failed to remove old archive: {:?}", path))?;
+        }
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_redact_tokens_and_profile_data() {
+        let html = r#"<meta name="csrf-token" content="ajax:123456">
+            <code>{&quot;firstName&quot;:&quot;Ada&quot;,&quot;publicIdentifier&quot;:&quot;ada-l&quot;,
+            &quot;jobPostingTitle&quot;:&quot;Rust Engineer&quot;}</code>
+            <script>var c = "JSESSIONID=ajax:987; li_at=AQEDAS";</script>
+            <a href="https://www.linkedin.com/in/ada-lovelace/">me</a> urn:li:fsd_profile:ACoAAB12"#;
+
+        let redacted = redact_html(html);
+        for secret in ["123456", "Ada", "ada-l", "987", "AQEDAS", "ada-lovelace", "ACoAAB12"] {
+            assert!(!redacted.contains(secret), "{} leaked: {}", secret, redacted);
+        }
+        assert!(redacted.contains("Rust Engineer"));
+        assert!(redacted.contains("&quot;firstName&quot;:&quot;[scrubbed]&quot;"));
+    }
+
+    #[test]
+    fn test_save_and_load_roundtrip() {
+        let dir = TempDir::new().unwrap();
+        let limits = ArchiveConfig::default();
+
+        save_run_html(dir.path(), "20240101-120000-000", "<html>jobs</html>", &limits).unwrap();
+        let html = load_run_html(dir.path(), "20240101-120000-000").unwrap();
+        assert_eq!(html.as_deref(), Some("<html>jobs</html>"));
+        assert!(load_run_html(dir.path(), "missing").unwrap().is_none());
+    }
+
+    #[test]
+    fn test_rejects_path_traversal() {
+        let dir = TempDir::new().unwrap();
+        assert!(load_run_html(dir.path(), "../secrets").is_err());
+    }
+
+    #[test]
+    fn test_prune_by_count() {
+        let dir = TempDir::new().unwrap();
+        let limits = ArchiveConfig {
+            max_runs: 2,
+            max_total_bytes: 0,
+        };
+
+        for run_id in ["run-1", "run-2", "run-3"] {
+            save_run_html(dir.path(), run_id, "<html></html>", &limits).unwrap();
+        }
+
+        let runs: Vec<String> = list_runs(dir.path()).unwrap().into_iter().map(|e| e.run_id).collect();
+        assert_eq!(runs, vec!["run-3", "run-2"]);
+    }
+
+    #[test]
+    fn test_prune_by_size_keeps_newest() {
+        let dir = TempDir::new().unwrap();
+        let limits = ArchiveConfig {
+            max_runs: 100,
+            max_total_bytes: 1,
+        };
+
+        save_run_html(dir.path(), "run-1", "<html></html>", &limits).unwrap();
+        save_run_html(dir.path(), "run-2", "<html></html>", &limits).unwrap();
+
+        let runs: Vec<String> = list_runs(dir.path()).unwrap().into_iter().map(|e| e.run_id).collect();
+        assert_eq!(runs, vec!["run-2"]);
+    }
+}
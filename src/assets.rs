@@ -0,0 +1,206 @@
+use crate::etag;
+use axum::body::Body;
+use axum::http::{header, HeaderMap, HeaderValue, Method, StatusCode, Uri};
+use axum::response::{IntoResponse, Response};
+use axum::routing::get_service;
+use axum::Router;
+use flate2::write::GzEncoder;
+use flate2::Compression;
+use std::io::Write;
+use std::path::Path;
+use std::sync::OnceLock;
+use tower_http::services::{ServeDir, ServeFile};
+
+/// Page routes and the file each one serves
+const PAGES: &[(&str, &str)] = &[("/", "index.html"), ("/jobs", "jobs.html"), ("/login", "login.html")];
+
+/// Filenames aren't content-hashed, so browsers revalidate with the ETag each time
+const CACHE_CONTROL: &str = "no-cache";
+
+struct Asset {
+    name: &'static str,
+    content_type: &'static str,
+    body: &'static [u8],
+}
+
+/// The web UI, compiled into the binary
+const ASSETS: &[Asset] = &[
+    Asset { name: "index.html", content_type: "text/html; charset=utf-8", body: include_bytes!("../web/index.html") },
+    Asset { name: "jobs.html", content_type: "text/html; charset=utf-8", body: include_bytes!("../web/jobs.html") },
+    Asset { name: "login.html", content_type: "text/html; charset=utf-8", body: include_bytes!("../web/login.html") },
+    Asset { name: "app.js", content_type: "text/javascript; charset=utf-8", body: include_bytes!("../web/app.js") },
+    Asset { name: "styles.css", content_type: "text/css; charset=utf-8", body: include_bytes!("../web/styles.css") },
+];
+
+/// An asset with its ETag and gzipped body, computed on first use
+struct Prepared {
+    asset: &'static Asset,
+    etag: String,
+    gzip: Vec<u8>,
+}
+
+fn prepared() -> &'static [Prepared] {
+    static PREPARED: OnceLock<Vec<Prepared>> = OnceLock::new();
+    PREPARED.get_or_init(|| {
+        ASSETS
+            .iter()
+            .map(|asset| Prepared {
+                asset,
+                etag: etag::of(asset.body),
+                gzip: gzip(asset.body),
+            })
+            .collect()
+    })
+}
+
+fn gzip(bytes: &[u8]) -> Vec<u8> {
+    let mut encoder = GzEncoder::new(Vec::new(), Compression::best());
+    encoder.write_all(bytes).expect("writing to a Vec cannot fail");
+    encoder.finish().expect("writing to a Vec cannot fail")
+}
+
+fn find(path: &str) -> Option<&'static Prepared> {
+    let name = PAGES
+        .iter()
+        .find(|(route, _)| *route == path)
+        .map(|(_, name)| *name)
+        .unwrap_or_else(|| path.trim_start_matches('/'));
+    prepared().iter().find(|prepared| prepared.asset.name == name)
+}
+
+fn accepts_gzip(headers: &HeaderMap) -> bool {
+    headers
+        .get_all(header::ACCEPT_ENCODING)
+        .iter()
+        .filter_map(|value| value.to_str().ok())
+        .flat_map(|value| value.split(','))
+        .any(|encoding| {
+            let mut parts = encoding.trim().split(';');
+            let name = parts.next().unwrap_or("");
+            let refused = parts.any(|param| matches!(param.trim(), "q=0" | "q=0.0" | "q=0.00" | "q=0.000"));
+            (name == "gzip" || name == "*") && !refused
+        })
+}
+
+/// The gzip variant gets its own tag, as it is a different representation
+fn gzip_etag(etag: &str) -> String {
+    format!("{}-gzip\"", etag.trim_end_matches('"'))
+}
+
+fn not_modified(headers: &HeaderMap, prepared: &Prepared) -> bool {
+    let gzip_etag = gzip_etag(&prepared.etag);
+    headers
+        .get_all(header::IF_NONE_MATCH)
+        .iter()
+        .filter_map(|value| value.to_str().ok())
+        .any(|value| etag::matches(value, &prepared.etag) || etag::matches(value, &gzip_etag))
+}
+
+fn respond(prepared: &'static Prepared, headers: &HeaderMap) -> Response {
+    let gzip = accepts_gzip(headers);
+    let etag = if gzip { gzip_etag(&prepared.etag) } else { prepared.etag.clone() };
+    let mut response = if not_modified(headers, prepared) {
+        StatusCode::NOT_MODIFIED.into_response()
+    } else if gzip {
+        let mut response = Body::from(prepared.gzip.as_slice()).into_response();
+        response.headers_mut().insert(header::CONTENT_ENCODING, HeaderValue::from_static("gzip"));
+        response
+    } else {
+        Body::from(prepared.asset.body).into_response()
+    };
+
+    let response_headers = response.headers_mut();
+    response_headers.insert(header::CONTENT_TYPE, HeaderValue::from_static(prepared.asset.content_type));
+    response_headers.insert(header::CACHE_CONTROL, HeaderValue::from_static(CACHE_CONTROL));
+    response_headers.insert(header::VARY, HeaderValue::from_static("Accept-Encoding"));
+    if let Ok(etag) = HeaderValue::from_str(&etag) {
+        response_headers.insert(header::ETAG, etag);
+    }
+    response
+}
+
+async fn serve_embedded(method: Method, uri: Uri, headers: HeaderMap) -> Response {
+    if method != Method::GET && method != Method::HEAD {
+        return StatusCode::METHOD_NOT_ALLOWED.into_response();
+    }
+    match find(uri.path()) {
+        Some(prepared) => respond(prepared, &headers),
+        None => StatusCode::NOT_FOUND.into_response(),
+    }
+}
+
+/// Routes for the web UI: the embedded copy, or the files in `web_dir` when set
+/// (for working on the UI without rebuilding)
+pub fn router(web_dir: Option<&Path>) -> Router {
+    let Some(web_dir) = web_dir else {
+        return Router::new().fallback(serve_embedded);
+    };
+
+    PAGES
+        .iter()
+        .fold(Router::new(), |router, (route, name)| {
+            router.route(route, get_service(ServeFile::new(web_dir.join(name))))
+        })
+        .fallback_service(ServeDir::new(web_dir))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use flate2::read::GzDecoder;
+    use std::io::Read;
+
+    fn headers(pairs: &[(&'static str, &str)]) -> HeaderMap {
+        let mut headers = HeaderMap::new();
+        for (name, value) in pairs {
+            headers.append(*name, HeaderValue::from_str(value).unwrap());
+        }
+        headers
+    }
+
+    async fn body(response: Response) -> Vec<u8> {
+        axum::body::to_bytes(response.into_body(), usize::MAX).await.unwrap().to_vec()
+    }
+
+    #[test]
+    fn test_find_pages_and_files() {
+        assert_eq!(find("/").unwrap().asset.name, "index.html");
+        assert_eq!(find("/jobs").unwrap().asset.name, "jobs.html");
+        assert_eq!(find("/app.js").unwrap().asset.content_type, "text/javascript; charset=utf-8");
+        assert_eq!(find("/styles.css").unwrap().asset.content_type, "text/css; charset=utf-8");
+        assert!(find("/missing.js").is_none());
+        assert!(find("/../Cargo.toml").is_none());
+    }
+
+    #[tokio::test]
+    async fn test_gzip_when_accepted() {
+        let asset = find("/app.js").unwrap();
+
+        let response = respond(asset, &headers(&[("accept-encoding", "gzip, deflate, br")]));
+        assert_eq!(response.headers()[header::CONTENT_ENCODING], "gzip");
+        assert_eq!(response.headers()[header::VARY], "Accept-Encoding");
+        let mut decoded = Vec::new();
+        GzDecoder::new(body(response).await.as_slice()).read_to_end(&mut decoded).unwrap();
+        assert_eq!(decoded, asset.asset.body);
+
+        let response = respond(asset, &headers(&[("accept-encoding", "gzip;q=0, br")]));
+        assert!(response.headers().get(header::CONTENT_ENCODING).is_none());
+        assert_eq!(body(response).await, asset.asset.body);
+    }
+
+    #[tokio::test]
+    async fn test_etag_revalidation() {
+        let asset = find("/").unwrap();
+        let response = respond(asset, &HeaderMap::new());
+        assert_eq!(response.status(), StatusCode::OK);
+        assert_eq!(response.headers()[header::CACHE_CONTROL], CACHE_CONTROL);
+        let etag = response.headers()[header::ETAG].to_str().unwrap().to_string();
+
+        let response = respond(asset, &headers(&[("if-none-match", &etag)]));
+        assert_eq!(response.status(), StatusCode::NOT_MODIFIED);
+        assert!(body(response).await.is_empty());
+
+        let response = respond(asset, &headers(&[("if-none-match", "\"stale\"")]));
+        assert_eq!(response.status(), StatusCode::OK);
+    }
+}
@@ -0,0 +1,389 @@
+use crate::config::AuthConfig;
+use crate::secrets::{SecretStore, SecretValue};
+use anyhow::{Context, Result};
+use axum::http::{header, HeaderMap, Method};
+use base64::{engine::general_purpose::URL_SAFE_NO_PAD, Engine};
+use chacha20poly1305::aead::rand_core::RngCore;
+use chacha20poly1305::aead::OsRng;
+use chrono::{DateTime, Utc};
+use serde::Serialize;
+use std::collections::HashMap;
+use std::sync::Mutex;
+
+/// Static bearer token for scripts, accepted alongside the configured API keys
+pub const TOKEN_ENV: &str = "FRESH_POST_API_TOKEN";
+
+/// Login username, overriding `auth.username`
+pub const USERNAME_ENV: &str = "FRESH_POST_USERNAME";
+
+/// Login password, overriding `auth.password`
+pub const PASSWORD_ENV: &str = "FRESH_POST_PASSWORD";
+
+pub const SESSION_COOKIE: &str = "fresh_post_session";
+
+/// Readable by the UI's scripts, which echo it in [`CSRF_HEADER`] (double-submit)
+pub const CSRF_COOKIE: &str = "fresh_post_csrf";
+
+pub const CSRF_HEADER: &str = "x-csrf-token";
+
+pub const API_KEY_HEADER: &str = "x-api-key";
+
+/// Paths served without credentials: the liveness probe and what the login page needs
+const PUBLIC_PATHS: &[&str] = &["/healthz", "/login", "/api/login", "/api/auth", "/app.js", "/styles.css"];
+
+/// Username and password for the browser login
+#[derive(Debug, Clone)]
+pub struct Login {
+    pub username: String,
+    pub password: SecretValue,
+}
+
+/// An API token and the name it is logged under
+#[derive(Debug, Clone)]
+pub struct Token {
+    /// Secret name for configured API keys, the env var name for the static token
+    pub name: String,
+    pub value: SecretValue,
+}
+
+/// Everything that grants access; auth is off when this is empty
+#[derive(Debug, Clone, Default)]
+pub struct Credentials {
+    tokens: Vec<Token>,
+    login: Option<Login>,
+}
+
+impl Credentials {
+    pub fn new(tokens: Vec<Token>, login: Option<Login>) -> Self {
+        Self { tokens, login }
+    }
+
+    /// Resolve `[auth]` against the secret store, with env vars taking precedence
+    pub fn load(config: &AuthConfig, store: Option<&SecretStore>) -> Result<Self> {
+        let env = |name: &str| std::env::var(name).ok().filter(|value| !value.is_empty());
+
+        let mut tokens = Vec::new();
+        if let Some(token) = env(TOKEN_ENV) {
+            tokens.push(Token {
+                name: TOKEN_ENV.to_string(),
+                value: SecretValue::new(token),
+            });
+        }
+        for key in &config.api_keys {
+            let store = store.context("auth.api_keys refer to stored secrets, but the secret store is not configured")?;
+            tokens.push(Token {
+                name: key.name().to_string(),
+                value: store.resolve(key)?,
+            });
+        }
+
+        let username = env(USERNAME_ENV).or_else(|| config.username.clone().filter(|name| !name.is_empty()));
+        let password = match (env(PASSWORD_ENV), &config.password) {
+            (Some(password), _) => Some(SecretValue::new(password)),
+            (None, Some(secret)) => {
+                let store = store.context("auth.password refers to a stored secret, but the secret store is not configured")?;
+                Some(store.resolve(secret)?)
+            }
+            (None, None) => None,
+        };
+
+        let login = match (username, password) {
+            (Some(username), Some(password)) => Some(Login { username, password }),
+            (None, None) => None,
+            (Some(_), None) => anyhow::bail!("A login username is set without a password (auth.password or {})", PASSWORD_ENV),
+            (None, Some(_)) => anyhow::bail!("A login password is set without a username (auth.username or {})", USERNAME_ENV),
+        };
+
+        Ok(Self { tokens, login })
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.tokens.is_empty() && self.login.is_none()
+    }
+}
+
+/// A logged-in browser session
+#[derive(Debug, Clone)]
+pub struct Session {
+    pub username: String,
+    pub csrf_token: String,
+    pub expires_at: DateTime<Utc>,
+}
+
+/// How a request was let in, or why it wasn't
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Access {
+    /// Auth is disabled or the path is public
+    Open,
+    Token { name: String },
+    Session { username: String },
+    /// No valid token or session
+    Unauthenticated,
+    /// Valid session, but a state-changing request without the matching CSRF token
+    CsrfRejected,
+}
+
+impl Access {
+    pub fn is_allowed(&self) -> bool {
+        matches!(self, Access::Open | Access::Token { .. } | Access::Session { .. })
+    }
+
+    /// Who made the request, as recorded in the audit log
+    pub fn actor(&self) -> String {
+        match self {
+            Access::Token { name } => format!("token:{}", name),
+            Access::Session { username } => format!("user:{}", username),
+            _ => "anonymous".to_string(),
+        }
+    }
+}
+
+/// Auth state reported by `/api/auth`
+#[derive(Debug, Clone, Serialize)]
+pub struct AuthStatus {
+    pub enabled: bool,
+    pub login_enabled: bool,
+    pub username: Option<String>,
+}
+
+/// Credentials plus the sessions logged in with them. Sessions live in memory,
+/// so a restart logs everyone out.
+#[derive(Debug, Default)]
+pub struct Auth {
+    credentials: Credentials,
+    session_ttl: chrono::Duration,
+    sessions: Mutex<HashMap<String, Session>>,
+}
+
+impl Auth {
+    pub fn new(credentials: Credentials, session_hours: u64) -> Self {
+        Self {
+            credentials,
+            session_ttl: chrono::Duration::hours(session_hours.max(1) as i64),
+            sessions: Mutex::new(HashMap::new()),
+        }
+    }
+
+    pub fn is_enabled(&self) -> bool {
+        !self.credentials.is_empty()
+    }
+
+    pub fn login_enabled(&self) -> bool {
+        self.credentials.login.is_some()
+    }
+
+    pub fn session_ttl(&self) -> chrono::Duration {
+        self.session_ttl
+    }
+
+    /// Start a session for valid credentials. Returns the session id and its CSRF token.
+    pub fn login(&self, username: &str, password: &str) -> Option<(String, Session)> {
+        let login = self.credentials.login.as_ref()?;
+        // Compare both so timing doesn't reveal which one was wrong
+        let user_ok = constant_time_eq(username.as_bytes(), login.username.as_bytes());
+        let password_ok = constant_time_eq(password.as_bytes(), login.password.expose().as_bytes());
+        if !(user_ok && password_ok) {
+            return None;
+        }
+
+        let now = Utc::now();
+        let session = Session {
+            username: login.username.clone(),
+            csrf_token: random_token(),
+            expires_at: now + self.session_ttl,
+        };
+        let id = random_token();
+
+        let mut sessions = self.sessions.lock().unwrap_or_else(|poisoned| poisoned.into_inner());
+        sessions.retain(|_, session| session.expires_at > now);
+        sessions.insert(id.clone(), session.clone());
+        Some((id, session))
+    }
+
+    pub fn logout(&self, session_id: &str) {
+        self.sessions.lock().unwrap_or_else(|poisoned| poisoned.into_inner()).remove(session_id);
+    }
+
+    fn session(&self, session_id: &str, now: DateTime<Utc>) -> Option<Session> {
+        let sessions = self.sessions.lock().unwrap_or_else(|poisoned| poisoned.into_inner());
+        sessions.get(session_id).filter(|session| session.expires_at > now).cloned()
+    }
+
+    /// Name of the token matching `token`
+    fn find_token(&self, token: &str) -> Option<&str> {
+        // Check every key so timing doesn't reveal which one matched
+        self.credentials.tokens.iter().fold(None, |found, key| {
+            if constant_time_eq(token.as_bytes(), key.value.expose().as_bytes()) {
+                Some(key.name.as_str())
+            } else {
+                found
+            }
+        })
+    }
+
+    /// Decide whether a request may go through
+    pub fn authorize(&self, method: &Method, path: &str, headers: &HeaderMap) -> Access {
+        if !self.is_enabled() || PUBLIC_PATHS.contains(&path) {
+            return Access::Open;
+        }
+
+        if let Some(token) = request_token(headers) {
+            return match self.find_token(token) {
+                Some(name) => Access::Token { name: name.to_string() },
+                None => Access::Unauthenticated,
+            };
+        }
+
+        let Some(session) = self.request_session(headers) else {
+            return Access::Unauthenticated;
+        };
+
+        let safe = matches!(*method, Method::GET | Method::HEAD | Method::OPTIONS);
+        let csrf = headers.get(CSRF_HEADER).and_then(|value| value.to_str().ok()).unwrap_or("");
+        if !safe && !constant_time_eq(csrf.as_bytes(), session.csrf_token.as_bytes()) {
+            return Access::CsrfRejected;
+        }
+
+        Access::Session { username: session.username }
+    }
+
+    fn request_session(&self, headers: &HeaderMap) -> Option<Session> {
+        cookie(headers, SESSION_COOKIE).and_then(|id| self.session(id, Utc::now()))
+    }
+
+    /// Auth settings and the user logged in with the request's session cookie
+    pub fn status(&self, headers: &HeaderMap) -> AuthStatus {
+        AuthStatus {
+            enabled: self.is_enabled(),
+            login_enabled: self.login_enabled(),
+            username: self.request_session(headers).map(|session| session.username),
+        }
+    }
+}
+
+/// Token from `Authorization: Bearer <token>` or `X-Api-Key`
+fn request_token(headers: &HeaderMap) -> Option<&str> {
+    let bearer = headers
+        .get(header::AUTHORIZATION)
+        .and_then(|value| value.to_str().ok())
+        .and_then(|value| value.strip_prefix("Bearer "));
+    let api_key = || headers.get(API_KEY_HEADER).and_then(|value| value.to_str().ok());
+    bearer.or_else(api_key).map(str::trim)
+}
+
+/// Value of the request cookie `name`
+pub fn cookie<'a>(headers: &'a HeaderMap, name: &str) -> Option<&'a str> {
+    headers
+        .get_all(header::COOKIE)
+        .iter()
+        .filter_map(|value| value.to_str().ok())
+        .flat_map(|value| value.split(';'))
+        .filter_map(|pair| pair.trim().split_once('='))
+        .find(|(key, _)| *key == name)
+        .map(|(_, value)| value)
+}
+
+fn random_token() -> String {
+    let mut bytes = [0u8; 32];
+    OsRng.fill_bytes(&mut bytes);
+    URL_SAFE_NO_PAD.encode(bytes)
+}
+
+fn constant_time_eq(a: &[u8], b: &[u8]) -> bool {
+    if a.len() != b.len() {
+        return false;
+    }
+    a.iter().zip(b).fold(0u8, |diff, (x, y)| diff | (x ^ y)) == 0
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use axum::http::HeaderValue;
+
+    fn auth() -> Auth {
+        Auth::new(
+            Credentials::new(
+                vec![Token {
+                    name: "ci".to_string(),
+                    value: SecretValue::new("script-key"),
+                }],
+                Some(Login {
+                    username: "admin".to_string(),
+                    password: SecretValue::new("hunter2"),
+                }),
+            ),
+            24,
+        )
+    }
+
+    fn headers(pairs: &[(&'static str, &str)]) -> HeaderMap {
+        let mut headers = HeaderMap::new();
+        for (name, value) in pairs {
+            headers.append(*name, HeaderValue::from_str(value).unwrap());
+        }
+        headers
+    }
+
+    #[test]
+    fn test_disabled_without_credentials() {
+        let auth = Auth::new(Credentials::default(), 24);
+        assert!(!auth.is_enabled());
+        assert_eq!(auth.authorize(&Method::POST, "/api/config", &HeaderMap::new()), Access::Open);
+    }
+
+    #[test]
+    fn test_tokens_and_public_paths() {
+        let auth = auth();
+        assert_eq!(auth.authorize(&Method::GET, "/healthz", &HeaderMap::new()), Access::Open);
+        assert_eq!(auth.authorize(&Method::GET, "/api/jobs", &HeaderMap::new()), Access::Unauthenticated);
+
+        let bearer = headers(&[("authorization", "Bearer script-key")]);
+        let access = auth.authorize(&Method::POST, "/api/run", &bearer);
+        assert_eq!(access, Access::Token { name: "ci".to_string() });
+        assert_eq!(access.actor(), "token:ci");
+        let api_key = headers(&[("x-api-key", "script-key")]);
+        assert!(auth.authorize(&Method::GET, "/metrics", &api_key).is_allowed());
+        let wrong = headers(&[("authorization", "Bearer nope")]);
+        assert_eq!(auth.authorize(&Method::GET, "/api/jobs", &wrong), Access::Unauthenticated);
+    }
+
+    #[test]
+    fn test_session_requires_csrf_for_writes() {
+        let auth = auth();
+        assert!(auth.login("admin", "wrong").is_none());
+        let (id, session) = auth.login("admin", "hunter2").unwrap();
+
+        let cookie = format!("theme=dark; {}={}", SESSION_COOKIE, id);
+        let read = headers(&[("cookie", &cookie)]);
+        assert!(auth.authorize(&Method::GET, "/api/config", &read).is_allowed());
+        assert_eq!(auth.authorize(&Method::POST, "/api/config", &read), Access::CsrfRejected);
+
+        let write = headers(&[("cookie", &cookie), ("x-csrf-token", &session.csrf_token)]);
+        assert_eq!(
+            auth.authorize(&Method::POST, "/api/config", &write),
+            Access::Session { username: "admin".to_string() }
+        );
+
+        auth.logout(&id);
+        assert_eq!(auth.authorize(&Method::GET, "/api/config", &read), Access::Unauthenticated);
+    }
+
+    #[test]
+    fn test_expired_session_rejected() {
+        let auth = auth();
+        let (id, _) = auth.login("admin", "hunter2").unwrap();
+        assert!(auth.session(&id, Utc::now()).is_some());
+        assert!(auth.session(&id, Utc::now() + chrono::Duration::hours(25)).is_none());
+    }
+
+    #[test]
+    fn test_load_requires_username_and_password() {
+        let config = AuthConfig {
+            username: Some("admin".to_string()),
+            ..AuthConfig::default()
+        };
+        assert!(Credentials::load(&config, None).is_err());
+        assert!(Credentials::load(&AuthConfig::default(), None).unwrap().is_empty());
+    }
+}
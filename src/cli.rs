@@ -0,0 +1,357 @@
+use anyhow::{Context, Result};
+use chrono::Local;
+use clap::{Parser, Subcommand, ValueEnum};
+use std::io::Read;
+use std::path::PathBuf;
+
+use fresh_post::logging::LogFormat;
+use fresh_post::models::JobPosting;
+use fresh_post::state::State;
+use fresh_post::{audit, cookies, migrate, overrides, schema, server, service, session, storage};
+
+/// Scrape fresh LinkedIn job postings. Without a subcommand, runs the web UI and scheduler.
+#[derive(Debug, Parser)]
+#[command(name = "fresh-post", version)]
+pub struct Cli {
+    #[command(subcommand)]
+    pub command: Option<Command>,
+    /// Log line format; verbosity is set with RUST_LOG (e.g. RUST_LOG=debug)
+    #[arg(long, global = true, value_enum, env = "FRESH_POST_LOG_FORMAT", default_value_t = LogFormat::Text)]
+    pub log_format: LogFormat,
+}
+
+#[derive(Debug, Subcommand)]
+pub enum Command {
+    /// Run the scheduler and the web UI (the default)
+    Serve,
+    #[command(flatten)]
+    Task(Task),
+}
+
+/// Subcommands that do their work and exit, without the web UI
+#[derive(Debug, Subcommand)]
+pub enum Task {
+    /// Scrape once, then print the new jobs
+    Run {
+        /// Print new jobs as JSON
+        #[arg(long)]
+        json: bool,
+    },
+    /// Jobs from the latest scrape
+    Jobs {
+        #[command(subcommand)]
+        command: JobsCommand,
+    },
+    /// Inspect and change config.toml
+    Config {
+        #[command(subcommand)]
+        command: ConfigCommand,
+    },
+    /// Manage the record of already seen jobs
+    State {
+        #[command(subcommand)]
+        command: StateCommand,
+    },
+    /// Manage LinkedIn cookies
+    Cookies {
+        #[command(subcommand)]
+        command: CookiesCommand,
+    },
+    /// Export jobs from the latest scrape
+    Export {
+        #[arg(long, value_enum, default_value_t = ExportFormat::Json)]
+        format: ExportFormat,
+        /// Write to this file instead of stdout
+        #[arg(short, long)]
+        output: Option<PathBuf>,
+        /// Only jobs that were new in the latest scrape
+        #[arg(long)]
+        new: bool,
+    },
+    /// Check that the running server answers /healthz, using the [server] settings
+    /// (for container healthchecks); exits non-zero when it doesn't
+    Healthcheck,
+}
+
+#[derive(Debug, Subcommand)]
+pub enum JobsCommand {
+    /// List jobs from the latest scrape
+    List {
+        /// Only jobs that were new in the latest scrape
+        #[arg(long)]
+        new: bool,
+        #[arg(long)]
+        json: bool,
+    },
+}
+
+#[derive(Debug, Subcommand)]
+pub enum ConfigCommand {
+    /// Print the effective config
+    Show,
+    /// Check config.toml and selectors.toml without scraping
+    Validate,
+    /// Rewrite an older config.toml as the current version, keeping a backup
+    Upgrade,
+    /// Set one value, e.g. `config set search.keywords "rust developer"`
+    Set { key: String, value: String },
+    /// Print the JSON Schema of config.toml, for editor validation and autocomplete
+    Schema,
+}
+
+#[derive(Debug, Subcommand)]
+pub enum StateCommand {
+    /// Forget jobs seen more than this many days ago
+    Prune {
+        #[arg(long, default_value_t = 30)]
+        older_than_days: i64,
+    },
+    /// Forget all seen jobs, so the next run reports everything as new
+    Reset {
+        /// Confirm the reset
+        #[arg(long)]
+        yes: bool,
+    },
+}
+
+#[derive(Debug, Subcommand)]
+pub enum CookiesCommand {
+    /// Import a cookie export (simple, Netscape or JSON format); `-` reads stdin
+    Import { file: PathBuf },
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum)]
+pub enum ExportFormat {
+    Json,
+    Csv,
+}
+
+/// Run a subcommand other than `serve`
+pub async fn run(task: Task, paths: &storage::Paths) -> Result<()> {
+    match task {
+        Task::Run { json } => run_once(paths, json).await,
+        Task::Jobs { command: JobsCommand::List { new, json } } => list_jobs(paths, new, json),
+        Task::Config { command } => match command {
+            ConfigCommand::Show => show_config(paths),
+            ConfigCommand::Validate => validate_config(paths),
+            ConfigCommand::Upgrade => upgrade_config(paths),
+            ConfigCommand::Set { key, value } => set_config(paths, &key, &value),
+            ConfigCommand::Schema => {
+                println!("{}", serde_json::to_string_pretty(&schema::schema())?);
+                Ok(())
+            }
+        },
+        Task::State { command } => match command {
+            StateCommand::Prune { older_than_days } => prune_state(paths, older_than_days),
+            StateCommand::Reset { yes } => reset_state(paths, yes),
+        },
+        Task::Cookies { command: CookiesCommand::Import { file } } => import_cookies(paths, &file),
+        Task::Export { format, output, new } => export_jobs(paths, format, output, new),
+        Task::Healthcheck => {
+            server::check_health(&storage::load_config(paths)?.server).await?;
+            println!("ok");
+            Ok(())
+        }
+    }
+}
+
+async fn run_once(paths: &storage::Paths, json: bool) -> Result<()> {
+    let summary = service::run_scrape_once(paths).await?;
+    let snapshot = storage::load_latest_jobs(paths)?;
+
+    if json {
+        println!("{}", serde_json::to_string_pretty(&snapshot.new_jobs)?);
+        return Ok(());
+    }
+
+    println!(
+        "Scraped {} page(s): {} jobs today, {} new, {} reposts",
+        summary.pages_fetched, summary.today_jobs, summary.new_jobs, summary.duplicate_jobs
+    );
+    for anomaly in &summary.parse_anomalies {
+        tracing::warn!(?anomaly, "parse anomaly: {}", anomaly.describe());
+    }
+    print_jobs(&snapshot.new_jobs);
+    Ok(())
+}
+
+fn list_jobs(paths: &storage::Paths, new: bool, json: bool) -> Result<()> {
+    let snapshot = storage::load_latest_jobs(paths)?;
+    let jobs = if new { &snapshot.new_jobs } else { &snapshot.jobs };
+
+    if json {
+        println!("{}", serde_json::to_string_pretty(jobs)?);
+        return Ok(());
+    }
+
+    match snapshot.updated_at {
+        Some(updated_at) => println!("{} jobs from {}", jobs.len(), updated_at.format("%Y-%m-%d %H:%M")),
+        None => println!("No scrape has run yet"),
+    }
+    print_jobs(jobs);
+    Ok(())
+}
+
+fn print_jobs(jobs: &[JobPosting]) {
+    for job in jobs {
+        println!("\n{} — {} ({})", job.title, job.company, job.location);
+        println!("  {}", job.url);
+    }
+}
+
+fn show_config(paths: &storage::Paths) -> Result<()> {
+    let config = storage::load_config(paths)?;
+    if !paths.config.exists() {
+        println!("# No {:?}; showing defaults", paths.config);
+    }
+    for item in overrides::from_env() {
+        println!("# {} set by {}", item.field, item.var);
+    }
+    print!("{}", toml::to_string_pretty(&config).context("Failed to serialize config")?);
+    Ok(())
+}
+
+fn validate_config(paths: &storage::Paths) -> Result<()> {
+    let config = storage::load_config(paths)?;
+    config.validate()?;
+    storage::load_selector_profile(paths)?.compile()?;
+    storage::load_credentials(paths, &config.auth)?;
+    server::validate(&config.server)?;
+
+    println!("✅ Config is valid");
+    if paths.config.exists() {
+        let migrated = migrate::upgrade(
+            &std::fs::read_to_string(&paths.config)
+                .with_context(|| format!("Failed to read config file: {:?}", paths.config))?,
+        )?;
+        if migrated.is_upgrade() {
+            println!(
+                "Note: config.toml is version {} and needs upgrade to {}; run `config upgrade` or start the server",
+                migrated.from_version,
+                migrate::CURRENT_VERSION
+            );
+        }
+    }
+    Ok(())
+}
+
+fn upgrade_config(paths: &storage::Paths) -> Result<()> {
+    match storage::upgrade_config_file(paths)? {
+        Some(upgrade) => {
+            println!(
+                "Upgraded config.toml from version {} to {} (backup: {})",
+                upgrade.from_version,
+                migrate::CURRENT_VERSION,
+                upgrade.backup.display()
+            );
+            for step in upgrade.applied {
+                println!("  {}", step);
+            }
+        }
+        None => println!("config.toml is already current"),
+    }
+    Ok(())
+}
+
+fn set_config(paths: &storage::Paths, key: &str, value: &str) -> Result<()> {
+    let current = storage::load_config_or_default(paths)?;
+    let mut config = current.clone();
+    config.set_value(key, value)?;
+    config.validate()?;
+    storage::save_config(paths, &config)?;
+
+    let changes = audit::diff(&current, &config);
+    if !changes.is_empty() {
+        storage::append_config_audit(paths, &audit::AuditEntry::new("cli", "config set", changes))?;
+    }
+    println!("Set {} = {}", key, value);
+    if let Some(item) = overrides::from_env().into_iter().find(|item| item.field == key) {
+        println!("Note: {} overrides this while it is set", item.var);
+    }
+    Ok(())
+}
+
+fn prune_state(paths: &storage::Paths, older_than_days: i64) -> Result<()> {
+    let mut state = storage::load_state(paths)?;
+    let removed = state.prune_seen_before(Local::now() - chrono::Duration::days(older_than_days));
+    storage::save_state(paths, &state)?;
+    println!("Pruned {} job(s) seen more than {} days ago; {} remain", removed, older_than_days, state.seen_count());
+    Ok(())
+}
+
+fn reset_state(paths: &storage::Paths, yes: bool) -> Result<()> {
+    if !yes {
+        anyhow::bail!("Resetting forgets every seen job, so the next run reports them all as new. Pass --yes to confirm.");
+    }
+    let seen = storage::load_state(paths)?.seen_count();
+    storage::save_state(paths, &State::new())?;
+    println!("Forgot {} seen job(s)", seen);
+    Ok(())
+}
+
+fn import_cookies(paths: &storage::Paths, file: &PathBuf) -> Result<()> {
+    let content = if file.as_os_str() == "-" {
+        let mut content = String::new();
+        std::io::stdin().read_to_string(&mut content).context("Failed to read cookies from stdin")?;
+        content
+    } else {
+        std::fs::read_to_string(file).with_context(|| format!("Failed to read cookie file: {:?}", file))?
+    };
+
+    let format = cookies::detect_format(&content);
+    let parsed = cookies::parse_cookies(&content)?;
+    if parsed.is_empty() {
+        anyhow::bail!("No cookies found in {:?}", file);
+    }
+
+    let encrypted = storage::save_cookies(paths, &content)?;
+    println!(
+        "🍪 Imported {} cookies ({:?} format){}",
+        parsed.len(),
+        format,
+        if encrypted { ", encrypted" } else { "" }
+    );
+    if !parsed.iter().any(|cookie| cookie.name == session::SESSION_COOKIE) {
+        tracing::warn!("No {} cookie found; scrapes will see the guest view", session::SESSION_COOKIE);
+    }
+    Ok(())
+}
+
+fn export_jobs(paths: &storage::Paths, format: ExportFormat, output: Option<PathBuf>, new: bool) -> Result<()> {
+    let snapshot = storage::load_latest_jobs(paths)?;
+    let jobs = if new { &snapshot.new_jobs } else { &snapshot.jobs };
+
+    let content = match format {
+        ExportFormat::Json => serde_json::to_string_pretty(jobs)? + "\n",
+        ExportFormat::Csv => jobs_to_csv(jobs),
+    };
+
+    match output {
+        Some(path) => {
+            std::fs::write(&path, content).with_context(|| format!("Failed to write export: {:?}", path))?;
+            eprintln!("Exported {} jobs to {:?}", jobs.len(), path);
+        }
+        None => print!("{}", content),
+    }
+    Ok(())
+}
+
+fn jobs_to_csv(jobs: &[JobPosting]) -> String {
+    let mut csv = String::from("id,title,company,location,url,posted_date\n");
+    for job in jobs {
+        let posted_date = job.posted_date.map(|date| date.to_string()).unwrap_or_default();
+        let fields = [&job.id, &job.title, &job.company, &job.location, &job.url, &posted_date];
+        let row: Vec<String> = fields.iter().map(|field| csv_field(field)).collect();
+        csv.push_str(&row.join(","));
+        csv.push('\n');
+    }
+    csv
+}
+
+fn csv_field(value: &str) -> String {
+    if value.contains([',', '"', '\n', '\r']) {
+        format!("\"{}\"", value.replace('"', "\"\""))
+    } else {
+        value.to_string()
+    }
+}
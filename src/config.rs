@@ -1,49 +1,316 @@
+use crate::facets::{self, ExperienceLevel, JobType, SortBy, Workplace};
+use crate::secrets::SecretRef;
 use anyhow::{Context, Result};
 use serde::{Deserialize, Deserializer, Serialize};
-use std::path::Path;
+use std::path::{Path, PathBuf};
 
+/// `0` means no salary filter; other values are kept for `Config::validate` to check
 fn deserialize_salary_min<'de, D>(deserializer: D) -> Result<Option<u32>, D::Error>
 where
     D: Deserializer<'de>,
 {
     let value: Option<u32> = Option::deserialize(deserializer)?;
-    Ok(value.filter(|&v| v > 0 && v >= 40000))
+    Ok(value.filter(|&v| v != 0))
 }
 
+/// LinkedIn's salary filter: $40k to $200k in $20k steps
+pub const SALARY_MIN_RANGE: std::ops::RangeInclusive<u32> = 40_000..=200_000;
+pub const SALARY_STEP: u32 = 20_000;
+
+/// Whether LinkedIn's salary filter takes `salary`
+pub fn is_salary_step(salary: u32) -> bool {
+    SALARY_MIN_RANGE.contains(&salary) && salary.is_multiple_of(SALARY_STEP)
+}
+
+/// One invalid config value, e.g. `schedule.interval_hours`
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct FieldError {
+    pub field: String,
+    pub message: String,
+}
+
+/// Every invalid value found by `Config::validate`
+#[derive(Debug, Clone, Serialize)]
+pub struct ValidationErrors {
+    pub errors: Vec<FieldError>,
+}
+
+impl ValidationErrors {
+    pub fn fields(&self) -> Vec<&str> {
+        self.errors.iter().map(|error| error.field.as_str()).collect()
+    }
+}
+
+impl std::fmt::Display for ValidationErrors {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "Invalid config:")?;
+        for error in &self.errors {
+            write!(f, "\n  {}: {}", error.field, error.message)?;
+        }
+        Ok(())
+    }
+}
+
+impl std::error::Error for ValidationErrors {}
+
+/// Every section and field may be left out of `config.toml`; missing values come
+/// from the `Default` impls below, the one place defaults are defined.
 #[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
 pub struct Config {
+    /// Format of this config; older files are upgraded by `migrate` when loaded
+    pub version: u32,
     pub search: SearchConfig,
-    #[serde(default)]
     pub schedule: ScheduleConfig,
+    pub archive: ArchiveConfig,
+    pub proxy: ProxyConfig,
+    pub rate_limit: RateLimitConfig,
+    pub browser: BrowserConfig,
+    pub auth: AuthConfig,
+    pub server: ServerConfig,
+}
+
+impl Default for Config {
+    fn default() -> Self {
+        Self {
+            version: crate::migrate::CURRENT_VERSION,
+            search: SearchConfig::default(),
+            schedule: ScheduleConfig::default(),
+            archive: ArchiveConfig::default(),
+            proxy: ProxyConfig::default(),
+            rate_limit: RateLimitConfig::default(),
+            browser: BrowserConfig::default(),
+            auth: AuthConfig::default(),
+            server: ServerConfig::default(),
+        }
+    }
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
 pub struct SearchConfig {
     pub keywords: String,
     pub location: String,
-    /// Filter for remote jobs only (uses f_WT=2 parameter)
-    #[serde(default)]
-    pub remote: bool,
+    /// LinkedIn's id for the location (`geoId`), more precise than the name
+    pub geo_id: Option<u64>,
+    /// Radius around the location in miles, one of `facets::DISTANCES_MILES`
+    pub distance: Option<u32>,
+    /// On-site, remote and/or hybrid (`f_WT`); any when empty
+    pub workplace: Vec<Workplace>,
+    /// Experience levels (`f_E`); any when empty
+    pub experience: Vec<ExperienceLevel>,
+    /// Full-time, contract, ... (`f_JT`); any when empty
+    pub job_types: Vec<JobType>,
+    /// Only jobs that take LinkedIn's Easy Apply (`f_AL`)
+    pub easy_apply: bool,
+    /// Only these companies, by LinkedIn company id (`f_C`)
+    pub company_ids: Vec<u64>,
+    /// Only these industries, by LinkedIn industry id (`f_I`)
+    pub industry_ids: Vec<u32>,
     /// Minimum salary in USD (e.g., 100000 for $100k). LinkedIn uses increments of $20k.
     /// Valid range: $40k - $200k. Set to 0 or omit to disable salary filter.
-    #[serde(default, deserialize_with = "deserialize_salary_min")]
+    #[serde(deserialize_with = "deserialize_salary_min")]
     pub salary_min: Option<u32>,
+    /// Only jobs posted within this many hours (`f_TPR`); 0 for any time.
+    /// The job list still keeps only today's postings.
+    pub posted_within_hours: u32,
+    pub sort_by: SortBy,
+    /// Number of result pages (25 jobs each) to fetch per run
+    pub max_pages: u32,
+}
+
+impl Default for SearchConfig {
+    fn default() -> Self {
+        Self {
+            keywords: "rust developer".to_string(),
+            location: "San Francisco Bay Area".to_string(),
+            geo_id: None,
+            distance: None,
+            workplace: Vec::new(),
+            experience: Vec::new(),
+            job_types: Vec::new(),
+            easy_apply: false,
+            company_ids: Vec::new(),
+            industry_ids: Vec::new(),
+            salary_min: None,
+            posted_within_hours: 24,
+            sort_by: SortBy::default(),
+            max_pages: 1,
+        }
+    }
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
 pub struct ScheduleConfig {
-    #[serde(default = "default_interval_hours")]
     pub interval_hours: u64,
+    /// `/readyz` reports degraded once the last successful run is older than
+    /// this many intervals
+    pub stale_after_intervals: u32,
 }
 
-fn default_interval_hours() -> u64 {
-    4
+impl Default for ScheduleConfig {
+    fn default() -> Self {
+        Self {
+            interval_hours: 4,
+            stale_after_intervals: 3,
+        }
+    }
 }
 
-impl Default for ScheduleConfig {
+/// Retention of the per-run raw HTML archive
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct ArchiveConfig {
+    /// Number of runs to keep
+    pub max_runs: usize,
+    /// Total compressed size to keep, in bytes (0 disables the size limit)
+    pub max_total_bytes: u64,
+}
+
+impl Default for ArchiveConfig {
+    fn default() -> Self {
+        Self {
+            max_runs: 50,
+            max_total_bytes: 50 * 1024 * 1024,
+        }
+    }
+}
+
+/// Outbound proxies. With several URLs, requests rotate across them.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct ProxyConfig {
+    /// `http://`, `https://` or `socks5://` URLs, optionally with `user:pass@`
+    pub urls: Vec<String>,
+    /// How long a proxy that got blocked or timed out stays out of rotation
+    pub cooldown_minutes: u64,
+    /// Request timeout when going through a proxy
+    pub timeout_secs: u64,
+}
+
+impl Default for ProxyConfig {
+    fn default() -> Self {
+        Self {
+            urls: Vec::new(),
+            cooldown_minutes: 15,
+            timeout_secs: 30,
+        }
+    }
+}
+
+/// Outbound request budget, applied per host across all searches and pages
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct RateLimitConfig {
+    /// Sustained request rate (0 disables the token bucket)
+    pub requests_per_minute: f64,
+    /// Requests that may go out back to back before the rate applies
+    pub burst: u32,
+    /// Random pause added before every request, between these bounds
+    pub min_delay_ms: u64,
+    pub max_delay_ms: u64,
+}
+
+impl Default for RateLimitConfig {
+    fn default() -> Self {
+        Self {
+            requests_per_minute: 10.0,
+            burst: 2,
+            min_delay_ms: 2000,
+            max_delay_ms: 5000,
+        }
+    }
+}
+
+/// Browser the scraper presents itself as
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct BrowserConfig {
+    /// Header profile name (see `fingerprint::PROFILES`), or `"rotate"` to pick one per run
+    pub profile: String,
+    /// Profiles to rotate among (all when empty)
+    pub rotate: Vec<String>,
+}
+
+impl Default for BrowserConfig {
+    fn default() -> Self {
+        Self {
+            profile: crate::fingerprint::DEFAULT_PROFILE.to_string(),
+            rotate: Vec::new(),
+        }
+    }
+}
+
+/// Access to the web UI and API. Off until a credential is configured here or
+/// through `FRESH_POST_API_TOKEN` / `FRESH_POST_USERNAME` + `FRESH_POST_PASSWORD`.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct AuthConfig {
+    /// API keys for scripts, as `"secret:<name>"` references to the secret store
+    pub api_keys: Vec<SecretRef>,
+    /// Username for the browser login
+    pub username: Option<String>,
+    /// Password for the browser login, as a `"secret:<name>"` reference
+    pub password: Option<SecretRef>,
+    /// How long a browser login lasts
+    pub session_hours: u64,
+}
+
+impl Default for AuthConfig {
+    fn default() -> Self {
+        Self {
+            api_keys: Vec::new(),
+            username: None,
+            password: None,
+            session_hours: 24,
+        }
+    }
+}
+
+/// Where and how the web UI and API are served
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct ServerConfig {
+    /// `host:port` addresses and/or Unix sockets written as `unix:/path/to.sock`
+    pub listen: Vec<String>,
+    /// PEM certificate chain; together with `tls_key`, TCP listeners serve HTTPS
+    pub tls_cert: Option<PathBuf>,
+    /// PEM private key (PKCS#8) for `tls_cert`
+    pub tls_key: Option<PathBuf>,
+    /// URL prefix the app is served under, e.g. `/fresh-post` behind a reverse proxy
+    pub base_path: String,
+    /// Serve the web UI from this directory instead of the copy built into the
+    /// binary, e.g. `web` while working on it
+    pub web_dir: Option<PathBuf>,
+}
+
+impl Default for ServerConfig {
     fn default() -> Self {
         Self {
-            interval_hours: default_interval_hours(),
+            listen: vec!["0.0.0.0:8080".to_string()],
+            tls_cert: None,
+            tls_key: None,
+            base_path: String::new(),
+            web_dir: None,
+        }
+    }
+}
+
+fn merge_patch(target: &mut serde_json::Value, patch: &serde_json::Value) {
+    let serde_json::Value::Object(patch) = patch else {
+        *target = patch.clone();
+        return;
+    };
+    if !target.is_object() {
+        *target = serde_json::Value::Object(serde_json::Map::new());
+    }
+    let target = target.as_object_mut().expect("target was just made an object");
+    for (key, value) in patch {
+        if value.is_null() {
+            target.remove(key);
+        } else {
+            merge_patch(target.entry(key.clone()).or_insert(serde_json::Value::Null), value);
         }
     }
 }
@@ -53,32 +320,217 @@ impl Config {
         let content = std::fs::read_to_string(path.as_ref())
             .with_context(|| format!("Failed to read config file: {:?}", path.as_ref()))?;
         
-        let config: Config = toml::from_str(&content)
-            .with_context(|| "Failed to parse config file")?;
-        
-        Ok(config)
+        Self::from_toml(&content)
+    }
+
+    /// Parse `config.toml` contents, upgrading older versions in memory
+    pub fn from_toml(content: &str) -> Result<Self> {
+        Self::from_table(crate::migrate::upgrade(content)?.table)
+    }
+
+    pub fn from_table(table: toml::Table) -> Result<Self> {
+        toml::Value::Table(table).try_into().with_context(|| "Failed to parse config file")
+    }
+
+    /// Version of this config for `ETag` / `If-Match`; changes with any value
+    pub fn etag(&self) -> String {
+        crate::etag::of(&serde_json::to_vec(self).unwrap_or_default())
+    }
+
+    /// This config with a JSON Merge Patch (RFC 7396) applied: objects merge,
+    /// `null` resets a value to its default, anything else replaces it
+    pub fn merge_patch(&self, patch: &serde_json::Value) -> Result<Config> {
+        let mut value = serde_json::to_value(self).context("Failed to serialize config")?;
+        merge_patch(&mut value, patch);
+        serde_json::from_value(value).context("Invalid config patch")
+    }
+
+    /// Set a single value by dotted key, e.g. `search.keywords` or `schedule.interval_hours`.
+    /// `value` is parsed as a TOML value (`true`, `5`, `["a", "b"]`), falling back to a string
+    /// when it isn't one or the field wants text.
+    pub fn set_value(&mut self, key: &str, value: &str) -> Result<()> {
+        let (section, field) = key
+            .split_once('.')
+            .with_context(|| format!("Config keys look like section.field, got {:?}", key))?;
+
+        let typed = toml::from_str::<toml::Table>(&format!("value = {}", value))
+            .ok()
+            .and_then(|mut table| table.remove("value"))
+            .filter(|typed| !typed.is_str());
+        let text = toml::Value::String(value.to_string());
+
+        let mut root = toml::Table::try_from(&*self).context("Failed to serialize config")?;
+        let had_field = root
+            .get(section)
+            .and_then(toml::Value::as_table)
+            .is_some_and(|table| table.contains_key(field));
+        if !root.get(section).is_some_and(toml::Value::is_table) {
+            anyhow::bail!("Unknown config section: {}", section);
+        }
+
+        let mut with_value = |candidate: toml::Value| {
+            if let Some(table) = root.get_mut(section).and_then(toml::Value::as_table_mut) {
+                table.insert(field.to_string(), candidate);
+            }
+            toml::Value::Table(root.clone()).try_into::<Config>()
+        };
+        // e.g. `1234` for a username is still a name; when neither fits, the typed error says more
+        let updated = match typed.map(&mut with_value) {
+            Some(Ok(config)) => Ok(config),
+            Some(Err(typed_err)) => with_value(text).map_err(|_| typed_err),
+            None => with_value(text),
+        }
+        .with_context(|| format!("Invalid value for {}: {}", key, value))?;
+
+        let has_field = toml::Table::try_from(&updated)
+            .ok()
+            .and_then(|root| root.get(section).and_then(toml::Value::as_table).map(|table| table.contains_key(field)))
+            .unwrap_or(false);
+        if !had_field && !has_field {
+            anyhow::bail!("Unknown config key (or value ignored): {}", key);
+        }
+
+        *self = updated;
+        Ok(())
+    }
+
+    /// Check values serde accepts but the app can't use. Files referenced by the
+    /// config (TLS certificates, secrets) are checked where they are loaded.
+    pub fn validate(&self) -> std::result::Result<(), ValidationErrors> {
+        let mut errors = Vec::new();
+        let mut error = |field: String, message: String| errors.push(FieldError { field, message });
+
+        if self.version != crate::migrate::CURRENT_VERSION {
+            error("version".into(), format!("must be {}", crate::migrate::CURRENT_VERSION));
+        }
+
+        let search = &self.search;
+        if search.keywords.trim().is_empty() {
+            error("search.keywords".into(), "must not be empty".into());
+        }
+        if let Some(salary) = search.salary_min {
+            if !is_salary_step(salary) {
+                error(
+                    "search.salary_min".into(),
+                    format!(
+                        "must be 0 (no filter) or {} to {} in steps of {}, got {}",
+                        SALARY_MIN_RANGE.start(),
+                        SALARY_MIN_RANGE.end(),
+                        SALARY_STEP,
+                        salary
+                    ),
+                );
+            }
+        }
+        if search.geo_id == Some(0) {
+            error("search.geo_id".into(), "must be a LinkedIn geo id, not 0".into());
+        }
+        if let Some(distance) = search.distance {
+            if !facets::DISTANCES_MILES.contains(&distance) {
+                error(
+                    "search.distance".into(),
+                    format!("must be one of {:?} (miles), got {}", facets::DISTANCES_MILES, distance),
+                );
+            }
+        }
+        for (index, _) in search.company_ids.iter().enumerate().filter(|(_, id)| **id == 0) {
+            error(format!("search.company_ids[{}]", index), "must be a LinkedIn company id, not 0".into());
+        }
+        for (index, _) in search.industry_ids.iter().enumerate().filter(|(_, id)| **id == 0) {
+            error(format!("search.industry_ids[{}]", index), "must be a LinkedIn industry id, not 0".into());
+        }
+        if search.posted_within_hours > facets::MAX_POSTED_WITHIN_HOURS {
+            error(
+                "search.posted_within_hours".into(),
+                format!("must be at most {} (30 days), or 0 for any time", facets::MAX_POSTED_WITHIN_HOURS),
+            );
+        }
+        if search.max_pages == 0 {
+            error("search.max_pages".into(), "must be at least 1".into());
+        }
+
+        if self.schedule.interval_hours == 0 {
+            error("schedule.interval_hours".into(), "must be at least 1".into());
+        }
+        if self.schedule.stale_after_intervals == 0 {
+            error("schedule.stale_after_intervals".into(), "must be at least 1".into());
+        }
+
+        for (index, url) in self.proxy.urls.iter().enumerate() {
+            if let Err(err) = crate::proxy::validate_proxy_url(url) {
+                error(format!("proxy.urls[{}]", index), format!("{:#}", err));
+            }
+        }
+        if self.proxy.timeout_secs == 0 {
+            error("proxy.timeout_secs".into(), "must be at least 1".into());
+        }
+
+        let rate_limit = &self.rate_limit;
+        if !rate_limit.requests_per_minute.is_finite() || rate_limit.requests_per_minute < 0.0 {
+            error("rate_limit.requests_per_minute".into(), "must be 0 (no limit) or more".into());
+        }
+        if rate_limit.burst == 0 {
+            error("rate_limit.burst".into(), "must be at least 1".into());
+        }
+        if rate_limit.min_delay_ms > rate_limit.max_delay_ms {
+            error("rate_limit.min_delay_ms".into(), "must not exceed rate_limit.max_delay_ms".into());
+        }
+
+        let known_profiles = || {
+            let names: Vec<&str> = crate::fingerprint::PROFILES.iter().map(|profile| profile.name).collect();
+            names.join(", ")
+        };
+        let browser = &self.browser;
+        if browser.profile != crate::fingerprint::ROTATE && crate::fingerprint::BrowserProfile::find(&browser.profile).is_none() {
+            error(
+                "browser.profile".into(),
+                format!("unknown profile {:?}; expected {} or {:?}", browser.profile, known_profiles(), crate::fingerprint::ROTATE),
+            );
+        }
+        for (index, name) in browser.rotate.iter().enumerate() {
+            if crate::fingerprint::BrowserProfile::find(name).is_none() {
+                error(format!("browser.rotate[{}]", index), format!("unknown profile {:?}; expected {}", name, known_profiles()));
+            }
+        }
+
+        if self.auth.session_hours == 0 {
+            error("auth.session_hours".into(), "must be at least 1".into());
+        }
+
+        let server = &self.server;
+        if server.listen.is_empty() {
+            error("server.listen".into(), "must list at least one address".into());
+        }
+        for (index, addr) in server.listen.iter().enumerate() {
+            if let Err(err) = crate::server::ListenAddr::parse(addr) {
+                error(format!("server.listen[{}]", index), format!("{:#}", err));
+            }
+        }
+        if server.tls_cert.is_some() != server.tls_key.is_some() {
+            let missing = if server.tls_cert.is_some() { "server.tls_key" } else { "server.tls_cert" };
+            error(missing.into(), "TLS needs both server.tls_cert and server.tls_key".into());
+        }
+        if let Err(err) = crate::server::normalize_base_path(&server.base_path) {
+            error("server.base_path".into(), format!("{:#}", err));
+        }
+
+        if errors.is_empty() {
+            Ok(())
+        } else {
+            Err(ValidationErrors { errors })
+        }
     }
 
     pub fn load_or_default<P: AsRef<Path>>(path: P) -> Result<Self> {
         if path.as_ref().exists() {
             Self::load_from_file(path)
         } else {
-            // Return default config
-            Ok(Config {
-                search: SearchConfig {
-                    keywords: "rust developer".to_string(),
-                    location: "San Francisco Bay Area".to_string(),
-                    remote: false,
-                    salary_min: None,
-                },
-                schedule: ScheduleConfig {
-                    interval_hours: default_interval_hours(),
-                },
-            })
+            Ok(Config::default())
         }
     }
 }
 
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -86,15 +538,24 @@ mod tests {
     #[test]
     fn test_config_serialization() {
         let config = Config {
+            version: crate::migrate::CURRENT_VERSION,
             search: SearchConfig {
                 keywords: "test".to_string(),
                 location: "Test Location".to_string(),
-                remote: false,
                 salary_min: None,
+                max_pages: 1,
+                ..SearchConfig::default()
             },
             schedule: ScheduleConfig {
                 interval_hours: 4,
+                stale_after_intervals: 3,
             },
+            archive: ArchiveConfig::default(),
+            proxy: ProxyConfig::default(),
+            rate_limit: RateLimitConfig::default(),
+            browser: BrowserConfig::default(),
+            auth: AuthConfig::default(),
+            server: ServerConfig::default(),
         };
 
         let toml = toml::to_string(&config).unwrap();
@@ -102,6 +563,113 @@ mod tests {
         assert!(toml.contains("location"));
     }
 
+    #[test]
+    fn test_missing_values_use_defaults() {
+        assert_eq!(Config::from_toml("").unwrap().etag(), Config::default().etag());
+
+        let config = Config::from_toml("[schedule]\ninterval_hours = 2\n").unwrap();
+        assert_eq!(config.schedule.interval_hours, 2);
+        assert_eq!(config.schedule.stale_after_intervals, ScheduleConfig::default().stale_after_intervals);
+        assert_eq!(config.search.keywords, SearchConfig::default().keywords);
+        assert_eq!(config.version, crate::migrate::CURRENT_VERSION);
+    }
+
+    #[test]
+    fn test_unknown_keys_are_rejected() {
+        // A typo must not quietly scrape the default search
+        let err = Config::from_toml("[search]\nkeyword = \"embedded rust\"\n").unwrap_err();
+        assert!(format!("{:#}", err).contains("keyword"));
+        assert!(Config::from_toml("[serach]\nkeywords = \"rust\"\n").is_err());
+        assert!(Config::default().merge_patch(&serde_json::json!({ "schedule": { "interval": 2 } })).is_err());
+    }
+
+    #[test]
+    fn test_validate_reports_each_field() {
+        let mut config = Config::load_or_default("/nonexistent/config.toml").unwrap();
+        assert!(config.validate().is_ok());
+
+        config.search.keywords = "  ".to_string();
+        config.search.salary_min = Some(50_000);
+        config.schedule.interval_hours = 0;
+        config.proxy.urls = vec!["ftp://proxy.example.com".to_string()];
+        config.rate_limit.min_delay_ms = 9000;
+        config.browser.rotate = vec!["netscape".to_string()];
+        config.server.listen = vec!["0.0.0.0:8080".to_string(), "localhost".to_string()];
+
+        let errors = config.validate().unwrap_err();
+        assert_eq!(
+            errors.fields(),
+            vec![
+                "search.keywords",
+                "search.salary_min",
+                "schedule.interval_hours",
+                "proxy.urls[0]",
+                "rate_limit.min_delay_ms",
+                "browser.rotate[0]",
+                "server.listen[1]",
+            ]
+        );
+        assert!(errors.to_string().contains("\n  schedule.interval_hours: must be at least 1"));
+    }
+
+    #[test]
+    fn test_validate_search_facets() {
+        let mut config = Config::default();
+        config.search.geo_id = Some(0);
+        config.search.distance = Some(30);
+        config.search.company_ids = vec![1035, 0];
+        config.search.posted_within_hours = 24 * 60;
+        let errors = config.validate().unwrap_err();
+        assert_eq!(
+            errors.fields(),
+            vec!["search.geo_id", "search.distance", "search.company_ids[1]", "search.posted_within_hours"]
+        );
+
+        let config: Config = toml::from_str(
+            "[search]\nkeywords = \"rust\"\nworkplace = [\"remote\", \"hybrid\"]\nexperience = [\"mid_senior\"]\n\
+             job_types = [\"full_time\", \"contract\"]\nsort_by = \"date\"\ndistance = 25\n",
+        )
+        .unwrap();
+        assert!(config.validate().is_ok());
+        assert_eq!(config.search.sort_by, SortBy::Date);
+        assert!(toml::from_str::<Config>("[search]\nexperience = [\"senior\"]\n").is_err());
+    }
+
+    #[test]
+    fn test_salary_min_kept_for_validation() {
+        let config: Config = toml::from_str("[search]\nkeywords = \"rust\"\nlocation = \"\"\nsalary_min = 30000\n").unwrap();
+        assert_eq!(config.search.salary_min, Some(30_000));
+        assert_eq!(config.validate().unwrap_err().fields(), vec!["search.salary_min"]);
+
+        let config: Config = toml::from_str("[search]\nkeywords = \"rust\"\nlocation = \"\"\nsalary_min = 0\n").unwrap();
+        assert_eq!(config.search.salary_min, None);
+        assert!(config.validate().is_ok());
+    }
+
+    #[test]
+    fn test_merge_patch() {
+        let mut config = Config::load_or_default("/nonexistent/config.toml").unwrap();
+        config.server.tls_cert = Some(PathBuf::from("cert.pem"));
+        config.rate_limit.burst = 5;
+
+        let patch = serde_json::json!({
+            "schedule": { "interval_hours": 2 },
+            "server": { "tls_cert": null },
+            "rate_limit": { "burst": null },
+            "browser": { "rotate": ["safari-ios"] },
+        });
+        let patched = config.merge_patch(&patch).unwrap();
+        assert_eq!(patched.schedule.interval_hours, 2);
+        assert_eq!(patched.schedule.stale_after_intervals, 3);
+        assert_eq!(patched.search.keywords, config.search.keywords);
+        assert_eq!(patched.server.tls_cert, None);
+        assert_eq!(patched.rate_limit.burst, RateLimitConfig::default().burst);
+        assert_eq!(patched.browser.rotate, vec!["safari-ios"]);
+        assert_ne!(patched.etag(), config.etag());
+
+        assert!(config.merge_patch(&serde_json::json!({ "schedule": { "interval_hours": "soon" } })).is_err());
+    }
+
     #[test]
     fn test_config_deserialization() {
         let toml_content = r#"
@@ -131,4 +699,27 @@ interval_hours = 5
         assert_eq!(config.search.location, "San Francisco Bay Area");
         assert_eq!(config.schedule.interval_hours, 5);
     }
+
+    #[test]
+    fn test_set_value() {
+        let mut config: Config = toml::from_str("[search]\nkeywords = \"rust\"\nlocation = \"Berlin\"\n").unwrap();
+
+        config.set_value("search.keywords", "embedded rust").unwrap();
+        config.set_value("search.workplace", r#"["remote", "hybrid"]"#).unwrap();
+        config.set_value("schedule.interval_hours", "6").unwrap();
+        config.set_value("proxy.urls", r#"["http://proxy:3128"]"#).unwrap();
+        config.set_value("auth.username", "1234").unwrap();
+        assert_eq!(config.search.keywords, "embedded rust");
+        assert_eq!(config.auth.username.as_deref(), Some("1234"));
+        assert_eq!(config.search.workplace, vec![Workplace::Remote, Workplace::Hybrid]);
+        assert_eq!(config.schedule.interval_hours, 6);
+        assert_eq!(config.proxy.urls, vec!["http://proxy:3128"]);
+
+        assert!(config.set_value("schedule.interval_hours", "often").is_err());
+        assert!(config.set_value("search.keyword", "typo").is_err());
+        assert!(config.set_value("search.workplace", r#"["office"]"#).is_err());
+        assert!(config.set_value("nope.keywords", "x").is_err());
+        assert!(config.set_value("keywords", "x").is_err());
+        assert_eq!(config.schedule.interval_hours, 6);
+    }
 }
@@ -0,0 +1,403 @@
+use crate::secrets;
+use anyhow::{Context, Result};
+use chrono::{DateTime, TimeZone, Utc};
+use reqwest::cookie::Jar;
+use serde::{Deserialize, Serialize};
+use std::fmt;
+use std::path::Path;
+use std::sync::Arc;
+use url::Url;
+
+/// Domain used for cookies that don't carry one (plain `name=value` files)
+pub const DEFAULT_COOKIE_DOMAIN: &str = ".linkedin.com";
+
+/// On-disk formats accepted for the cookie file
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum CookieFormat {
+    /// `name=value` pairs, one per line or `;`-separated (document.cookie output)
+    Simple,
+    /// Netscape/Mozilla cookies.txt (curl, wget, yt-dlp, "Get cookies.txt" extensions)
+    Netscape,
+    /// JSON array exported by browser extensions (EditThisCookie, Cookie-Editor) or Puppeteer
+    Json,
+}
+
+#[derive(Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct StoredCookie {
+    pub name: String,
+    pub value: String,
+    pub domain: String,
+    pub path: String,
+    pub secure: bool,
+    pub http_only: bool,
+    /// `None` for session cookies
+    pub expires: Option<DateTime<Utc>>,
+}
+
+impl fmt::Debug for StoredCookie {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("StoredCookie")
+            .field("name", &self.name)
+            .field("value", &secrets::REDACTED)
+            .field("domain", &self.domain)
+            .field("path", &self.path)
+            .field("secure", &self.secure)
+            .field("http_only", &self.http_only)
+            .field("expires", &self.expires)
+            .finish()
+    }
+}
+
+impl StoredCookie {
+    fn simple(name: &str, value: &str) -> Self {
+        Self {
+            name: name.to_string(),
+            value: value.to_string(),
+            domain: DEFAULT_COOKIE_DOMAIN.to_string(),
+            path: "/".to_string(),
+            secure: true,
+            http_only: false,
+            expires: None,
+        }
+    }
+
+    pub fn is_expired(&self, now: DateTime<Utc>) -> bool {
+        self.expires.map(|expires| expires <= now).unwrap_or(false)
+    }
+
+    /// URL the cookie is scoped to, used as the origin when adding it to a jar
+    fn origin_url(&self) -> Option<Url> {
+        let host = self.domain.trim_start_matches('.');
+        if host.is_empty() {
+            return None;
+        }
+        let scheme = if self.secure { "https" } else { "http" };
+        Url::parse(&format!("{}://{}{}", scheme, host, self.path)).ok()
+    }
+
+    /// Render as a `Set-Cookie` header value so the jar applies domain/path/secure/expiry rules
+    fn to_set_cookie(&self) -> String {
+        let mut header = format!("{}={}; Path={}", self.name, self.value, self.path);
+        if self.domain.starts_with('.') {
+            header.push_str(&format!("; Domain={}", self.domain));
+        }
+        if self.secure {
+            header.push_str("; Secure");
+        }
+        if self.http_only {
+            header.push_str("; HttpOnly");
+        }
+        if let Some(expires) = self.expires {
+            header.push_str(&format!("; Expires={}", expires.format("%a, %d %b %Y %H:%M:%S GMT")));
+        }
+        header
+    }
+}
+
+/// Cookie metadata that is safe to show: never includes the value
+#[derive(Debug, Clone, Serialize)]
+pub struct CookieInfo {
+    pub name: String,
+    pub domain: String,
+    pub expires: Option<DateTime<Utc>>,
+    pub expired: bool,
+}
+
+impl CookieInfo {
+    pub fn from_cookie(cookie: &StoredCookie, now: DateTime<Utc>) -> Self {
+        Self {
+            name: cookie.name.clone(),
+            domain: cookie.domain.clone(),
+            expires: cookie.expires,
+            expired: cookie.is_expired(now),
+        }
+    }
+}
+
+/// Cookie as exported by browser extensions and Puppeteer
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct JsonCookie {
+    name: String,
+    value: String,
+    #[serde(default)]
+    domain: Option<String>,
+    #[serde(default)]
+    path: Option<String>,
+    #[serde(default)]
+    secure: bool,
+    #[serde(default)]
+    http_only: bool,
+    #[serde(default)]
+    host_only: bool,
+    #[serde(default)]
+    session: bool,
+    /// Seconds since the epoch (EditThisCookie / Cookie-Editor)
+    #[serde(default)]
+    expiration_date: Option<f64>,
+    /// Seconds since the epoch, -1 for session cookies (Puppeteer / Playwright)
+    #[serde(default)]
+    expires: Option<f64>,
+}
+
+impl From<JsonCookie> for StoredCookie {
+    fn from(cookie: JsonCookie) -> Self {
+        let mut domain = cookie
+            .domain
+            .unwrap_or_else(|| DEFAULT_COOKIE_DOMAIN.to_string());
+        if cookie.host_only {
+            domain = domain.trim_start_matches('.').to_string();
+        }
+
+        let expires = if cookie.session {
+            None
+        } else {
+            cookie
+                .expiration_date
+                .or(cookie.expires)
+                .filter(|&secs| secs > 0.0)
+                .and_then(|secs| Utc.timestamp_opt(secs as i64, 0).single())
+        };
+
+        Self {
+            name: cookie.name,
+            value: cookie.value,
+            domain,
+            path: cookie.path.unwrap_or_else(|| "/".to_string()),
+            secure: cookie.secure,
+            http_only: cookie.http_only,
+            expires,
+        }
+    }
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(untagged)]
+enum JsonCookieFile {
+    List(Vec<JsonCookie>),
+    Wrapped { cookies: Vec<JsonCookie> },
+}
+
+/// Guess the format of a cookie file from its content
+pub fn detect_format(content: &str) -> CookieFormat {
+    let trimmed = content.trim_start();
+    if trimmed.starts_with('[') || trimmed.starts_with('{') {
+        return CookieFormat::Json;
+    }
+
+    let is_netscape = content.lines().any(|line| {
+        let line = line.trim();
+        line.starts_with("# Netscape HTTP Cookie File")
+            || line.starts_with("# HTTP Cookie File")
+            || line.starts_with("#HttpOnly_")
+            || line.split('\t').count() == 7
+    });
+
+    if is_netscape {
+        CookieFormat::Netscape
+    } else {
+        CookieFormat::Simple
+    }
+}
+
+/// Parse cookies in any supported format
+pub fn parse_cookies(content: &str) -> Result<Vec<StoredCookie>> {
+    match detect_format(content) {
+        CookieFormat::Simple => Ok(parse_simple(content)),
+        CookieFormat::Netscape => parse_netscape(content),
+        CookieFormat::Json => parse_json(content),
+    }
+}
+
+fn parse_simple(content: &str) -> Vec<StoredCookie> {
+    content
+        .lines()
+        .map(|line| line.trim())
+        .filter(|line| !line.is_empty() && !line.starts_with('#'))
+        .flat_map(|line| line.split(';'))
+        .filter_map(|pair| {
+            let (name, value) = pair.trim().split_once('=')?;
+            let name = name.trim();
+            if name.is_empty() {
+                return None;
+            }
+            // Quotes are part of the value (LinkedIn's JSESSIONID is `"ajax:…"`)
+            Some(StoredCookie::simple(name, value.trim()))
+        })
+        .collect()
+}
+
+fn parse_netscape(content: &str) -> Result<Vec<StoredCookie>> {
+    let mut cookies = Vec::new();
+
+    for (index, line) in content.lines().enumerate() {
+        let line = line.trim_end_matches(['\r', '\n']);
+        let (line, http_only) = match line.strip_prefix("#HttpOnly_") {
+            Some(rest) => (rest, true),
+            None => (line, false),
+        };
+        if line.trim().is_empty() || line.starts_with('#') {
+            continue;
+        }
+
+        let fields: Vec<&str> = line.split('\t').collect();
+        if fields.len() != 7 {
+            anyhow::bail!(
+                "Invalid Netscape cookie line {}: expected 7 tab-separated fields, found {}",
+                index + 1,
+                fields.len()
+            );
+        }
+
+        let expires_secs: i64 = fields[4]
+            .trim()
+            .parse()
+            .with_context(|| format!("Invalid expiry on cookie line {}", index + 1))?;
+        let expires = if expires_secs > 0 {
+            Utc.timestamp_opt(expires_secs, 0).single()
+        } else {
+            None
+        };
+
+        cookies.push(StoredCookie {
+            name: fields[5].to_string(),
+            value: fields[6].to_string(),
+            domain: fields[0].to_string(),
+            path: fields[2].to_string(),
+            secure: fields[3].eq_ignore_ascii_case("TRUE"),
+            http_only,
+            expires,
+        });
+    }
+
+    Ok(cookies)
+}
+
+fn parse_json(content: &str) -> Result<Vec<StoredCookie>> {
+    let file: JsonCookieFile = serde_json::from_str(content)
+        .context("Failed to parse JSON cookie export")?;
+
+    let cookies = match file {
+        JsonCookieFile::List(cookies) => cookies,
+        JsonCookieFile::Wrapped { cookies } => cookies,
+    };
+
+    Ok(cookies.into_iter().map(StoredCookie::from).collect())
+}
+
+/// Load cookies from a file in any supported format
+pub fn load_cookies_from_file<P: AsRef<Path>>(path: P) -> Result<Vec<StoredCookie>> {
+    let content = std::fs::read_to_string(path.as_ref())
+        .with_context(|| format!("Failed to read cookie file: {:?}", path.as_ref()))?;
+
+    parse_cookies(&content)
+        .with_context(|| format!("Failed to parse cookie file: {:?}", path.as_ref()))
+}
+
+/// Build a cookie jar for the HTTP client, skipping cookies that have already expired
+pub fn build_cookie_jar(cookies: &[StoredCookie]) -> Arc<Jar> {
+    let jar = Jar::default();
+    let now = Utc::now();
+
+    for cookie in cookies.iter().filter(|cookie| !cookie.is_expired(now)) {
+        if let Some(url) = cookie.origin_url() {
+            jar.add_cookie_str(&cookie.to_set_cookie(), &url);
+        }
+    }
+
+    Arc::new(jar)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use reqwest::cookie::CookieStore;
+
+    #[test]
+    fn test_parse_simple_lines() {
+        let cookies = parse_cookies("# comment\nli_at=abc123\nJSESSIONID=\"ajax:42\"\n").unwrap();
+        assert_eq!(cookies.len(), 2);
+        assert_eq!(cookies[0].name, "li_at");
+        assert_eq!(cookies[0].value, "abc123");
+        assert_eq!(cookies[0].domain, DEFAULT_COOKIE_DOMAIN);
+        assert_eq!(cookies[1].value, "\"ajax:42\"");
+    }
+
+    #[test]
+    fn test_parse_document_cookie_string() {
+        let cookies = parse_cookies("li_at=abc; lang=v=2&lang=en-us; bcookie=xyz").unwrap();
+        assert_eq!(cookies.len(), 3);
+        assert_eq!(cookies[1].name, "lang");
+        assert_eq!(cookies[1].value, "v=2&lang=en-us");
+    }
+
+    #[test]
+    fn test_parse_netscape() {
+        let content = "# Netscape HTTP Cookie File\n\
+            .linkedin.com\tTRUE\t/\tTRUE\t1893456000\tli_at\tabc123\n\
+            #HttpOnly_www.linkedin.com\tFALSE\t/jobs\tFALSE\t0\tsession\txyz\n";
+
+        assert_eq!(detect_format(content), CookieFormat::Netscape);
+        let cookies = parse_cookies(content).unwrap();
+        assert_eq!(cookies.len(), 2);
+
+        assert_eq!(cookies[0].name, "li_at");
+        assert_eq!(cookies[0].domain, ".linkedin.com");
+        assert!(cookies[0].secure);
+        assert_eq!(cookies[0].expires.unwrap().timestamp(), 1893456000);
+
+        assert!(cookies[1].http_only);
+        assert_eq!(cookies[1].path, "/jobs");
+        assert_eq!(cookies[1].expires, None);
+    }
+
+    #[test]
+    fn test_parse_netscape_rejects_malformed_line() {
+        let content = "# Netscape HTTP Cookie File\n.linkedin.com\tTRUE\t/\n";
+        assert!(parse_cookies(content).is_err());
+    }
+
+    #[test]
+    fn test_parse_extension_json() {
+        let content = r#"[
+            {"domain": ".linkedin.com", "expirationDate": 1893456000.5, "hostOnly": false,
+             "httpOnly": true, "name": "li_at", "path": "/", "secure": true,
+             "session": false, "value": "abc123"},
+            {"domain": "www.linkedin.com", "hostOnly": true, "name": "lang",
+             "path": "/", "secure": false, "session": true, "value": "en"}
+        ]"#;
+
+        let cookies = parse_cookies(content).unwrap();
+        assert_eq!(cookies.len(), 2);
+        assert_eq!(cookies[0].expires.unwrap().timestamp(), 1893456000);
+        assert!(cookies[0].http_only);
+        assert_eq!(cookies[1].domain, "www.linkedin.com");
+        assert_eq!(cookies[1].expires, None);
+    }
+
+    #[test]
+    fn test_parse_puppeteer_json() {
+        let content = r#"{"cookies": [{"name": "li_at", "value": "abc", "domain": ".linkedin.com",
+            "path": "/", "expires": -1, "httpOnly": true, "secure": true}]}"#;
+
+        let cookies = parse_cookies(content).unwrap();
+        assert_eq!(cookies.len(), 1);
+        assert_eq!(cookies[0].expires, None);
+    }
+
+    #[test]
+    fn test_cookie_jar_honors_domain_and_expiry() {
+        let mut expired = StoredCookie::simple("old", "gone");
+        expired.expires = Some(Utc::now() - chrono::Duration::days(1));
+        let cookies = vec![StoredCookie::simple("li_at", "abc"), expired];
+
+        let jar = build_cookie_jar(&cookies);
+        let url = Url::parse("https://www.linkedin.com/jobs/search").unwrap();
+        let header = jar.cookies(&url).unwrap();
+        assert_eq!(header.to_str().unwrap(), "li_at=abc");
+
+        let other = Url::parse("https://example.com/").unwrap();
+        assert!(jar.cookies(&other).is_none());
+    }
+}
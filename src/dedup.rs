@@ -0,0 +1,147 @@
+use crate::models::JobPosting;
+use serde::{Deserialize, Serialize};
+use std::collections::HashSet;
+
+/// Minimum token similarity for two titles to be treated as the same role
+pub const TITLE_SIMILARITY_THRESHOLD: f64 = 0.8;
+
+/// Words that don't change what the role is (reposts often add or drop them)
+const TITLE_NOISE_WORDS: &[&str] = &[
+    "a", "an", "the", "and", "of", "for", "to", "in", "at", "with",
+    "remote", "hybrid", "onsite", "urgent", "urgently", "hiring", "new", "fulltime", "ft",
+];
+
+/// Suffixes that companies use inconsistently across boards
+const COMPANY_SUFFIXES: &[&str] = &["inc", "llc", "ltd", "corp", "corporation", "co", "gmbh", "plc"];
+
+/// Content fingerprint of a job posting, independent of its id
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+pub struct Fingerprint {
+    pub title: String,
+    pub company: String,
+    pub location: String,
+}
+
+impl Fingerprint {
+    pub fn from_job(job: &JobPosting) -> Self {
+        Self {
+            title: normalize_title(&job.title),
+            company: normalize_company(&job.company),
+            location: normalize_text(&job.location),
+        }
+    }
+
+    /// Same company and location, and titles that are close enough to be the same role
+    pub fn matches(&self, other: &Fingerprint) -> bool {
+        if self.company.is_empty() || self.title.is_empty() {
+            return false;
+        }
+
+        self.company == other.company
+            && self.location == other.location
+            && title_similarity(&self.title, &other.title) >= TITLE_SIMILARITY_THRESHOLD
+    }
+}
+
+/// A posting that was grouped with an earlier one instead of being reported as new
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DuplicateJob {
+    pub job: JobPosting,
+    pub original_id: String,
+}
+
+/// Lowercase, replace punctuation with spaces and collapse whitespace
+fn normalize_text(text: &str) -> String {
+    text.to_lowercase()
+        .chars()
+        .map(|c| if c.is_alphanumeric() { c } else { ' ' })
+        .collect::<String>()
+        .split_whitespace()
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+fn normalize_title(title: &str) -> String {
+    normalize_text(title)
+        .split_whitespace()
+        .filter(|word| !TITLE_NOISE_WORDS.contains(word))
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+fn normalize_company(company: &str) -> String {
+    let normalized = normalize_text(company);
+    if normalized.starts_with("unknown") {
+        return String::new();
+    }
+
+    normalized
+        .split_whitespace()
+        .filter(|word| !COMPANY_SUFFIXES.contains(word))
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+/// Jaccard similarity of the word sets of two normalized titles
+pub fn title_similarity(a: &str, b: &str) -> f64 {
+    if a == b {
+        return 1.0;
+    }
+
+    let a_words: HashSet<&str> = a.split_whitespace().collect();
+    let b_words: HashSet<&str> = b.split_whitespace().collect();
+    let union = a_words.union(&b_words).count();
+    if union == 0 {
+        return 0.0;
+    }
+
+    a_words.intersection(&b_words).count() as f64 / union as f64
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn job(id: &str, title: &str, company: &str, location: &str) -> JobPosting {
+        JobPosting::new(
+            id.to_string(),
+            title.to_string(),
+            company.to_string(),
+            location.to_string(),
+            format!("https://example.com/{}", id),
+            None,
+            None,
+        )
+    }
+
+    #[test]
+    fn test_fingerprint_normalization() {
+        let fp = Fingerprint::from_job(&job("1", "Senior Rust Engineer (Remote)", "Tech Corp, Inc.", "San Francisco, CA"));
+        assert_eq!(fp.title, "senior rust engineer");
+        assert_eq!(fp.company, "tech");
+        assert_eq!(fp.location, "san francisco ca");
+    }
+
+    #[test]
+    fn test_repost_matches() {
+        let original = Fingerprint::from_job(&job("1", "Senior Rust Engineer", "Tech Corp", "San Francisco, CA"));
+        let repost = Fingerprint::from_job(&job("2", "Senior Rust Engineer - Remote", "Tech Corp Inc", "San Francisco, CA"));
+        assert!(original.matches(&repost));
+    }
+
+    #[test]
+    fn test_different_roles_do_not_match() {
+        let backend = Fingerprint::from_job(&job("1", "Senior Rust Engineer", "Tech Corp", "SF"));
+        let frontend = Fingerprint::from_job(&job("2", "Junior Frontend Developer", "Tech Corp", "SF"));
+        let other_company = Fingerprint::from_job(&job("3", "Senior Rust Engineer", "Other Corp", "SF"));
+        assert!(!backend.matches(&frontend));
+        assert!(!backend.matches(&other_company));
+    }
+
+    #[test]
+    fn test_unknown_company_never_matches() {
+        let a = Fingerprint::from_job(&job("1", "Engineer", "Unknown Company", "SF"));
+        let b = Fingerprint::from_job(&job("2", "Engineer", "Unknown Company", "SF"));
+        assert!(!a.matches(&b));
+    }
+}
@@ -0,0 +1,232 @@
+use chrono::{DateTime, Local};
+use serde::{Deserialize, Serialize};
+
+/// Number of past runs kept for comparison
+pub const HISTORY_LEN: usize = 50;
+
+/// Runs needed before drift checks against history kick in
+const MIN_HISTORY_FOR_DRIFT: usize = 3;
+
+/// A page this large with no jobs is a parser failure, not an empty search
+const LARGE_PAGE_BYTES: usize = 100 * 1024;
+
+const HIGH_UNKNOWN_RATIO: f64 = 0.5;
+const UNKNOWN_RATIO_JUMP: f64 = 0.3;
+
+/// Flag a run whose job count falls below this share of the recent median
+const COUNT_DROP_RATIO: f64 = 0.25;
+
+/// Don't flag count drops when the recent median is this small
+const MIN_MEDIAN_FOR_COUNT_DROP: usize = 5;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ParsePath {
+    /// Embedded JSON of the logged-in view
+    Json,
+    /// Job cards of the guest view
+    Html,
+}
+
+impl ParsePath {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            ParsePath::Json => "json",
+            ParsePath::Html => "html",
+        }
+    }
+}
+
+/// What the parser saw on one page
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ParseDiagnostics {
+    pub path: ParsePath,
+    pub html_bytes: usize,
+    pub card_count: usize,
+    pub job_count: usize,
+    /// Title, company and location values that fell back to a placeholder
+    pub unknown_fields: usize,
+}
+
+impl ParseDiagnostics {
+    /// Fields checked per job when counting placeholders
+    pub const FIELDS_PER_JOB: usize = 3;
+
+    /// Combine diagnostics of several result pages of one run
+    pub fn merge(&mut self, other: &ParseDiagnostics) {
+        self.html_bytes += other.html_bytes;
+        self.card_count += other.card_count;
+        self.job_count += other.job_count;
+        self.unknown_fields += other.unknown_fields;
+    }
+
+    /// Share of title/company/location values that fell back to a placeholder
+    pub fn unknown_ratio(&self) -> f64 {
+        let total = self.job_count * Self::FIELDS_PER_JOB;
+        if total == 0 {
+            return 0.0;
+        }
+        self.unknown_fields as f64 / total as f64
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ParseAnomaly {
+    /// A large page produced no jobs
+    EmptyLargePage,
+    /// Cards matched but none yielded a job (links missing)
+    CardsWithoutJobs,
+    /// Most fields fell back to placeholders
+    HighUnknownRatio,
+    /// Far fewer jobs than recent runs
+    CountDrop,
+    /// Many more placeholder fields than recent runs
+    UnknownRatioJump,
+    /// Parsed via a different path than every recent run
+    PathChanged,
+}
+
+impl ParseAnomaly {
+    pub fn describe(&self) -> &'static str {
+        match self {
+            ParseAnomaly::EmptyLargePage => "large page parsed to zero jobs",
+            ParseAnomaly::CardsWithoutJobs => "job cards found but none had a link",
+            ParseAnomaly::HighUnknownRatio => "most title/company/location fields are unknown",
+            ParseAnomaly::CountDrop => "job count dropped sharply compared to recent runs",
+            ParseAnomaly::UnknownRatioJump => "share of unknown fields jumped compared to recent runs",
+            ParseAnomaly::PathChanged => "parse path differs from recent runs",
+        }
+    }
+}
+
+/// Parse health of a single run
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ParseHealth {
+    pub checked_at: DateTime<Local>,
+    pub diagnostics: ParseDiagnostics,
+    pub unknown_ratio: f64,
+    pub anomalies: Vec<ParseAnomaly>,
+    /// File name of the saved HTML when the run was anomalous
+    #[serde(default)]
+    pub saved_html: Option<String>,
+}
+
+impl ParseHealth {
+    pub fn is_healthy(&self) -> bool {
+        self.anomalies.is_empty()
+    }
+}
+
+fn median(mut values: Vec<usize>) -> usize {
+    values.sort_unstable();
+    values[values.len() / 2]
+}
+
+/// Check a run's diagnostics on their own and against recent history (oldest first)
+pub fn assess(diagnostics: ParseDiagnostics, history: &[ParseHealth]) -> ParseHealth {
+    let mut anomalies = Vec::new();
+    let unknown_ratio = diagnostics.unknown_ratio();
+
+    if diagnostics.job_count == 0 && diagnostics.html_bytes >= LARGE_PAGE_BYTES {
+        anomalies.push(ParseAnomaly::EmptyLargePage);
+    }
+
+    if diagnostics.card_count > 0 && diagnostics.job_count == 0 {
+        anomalies.push(ParseAnomaly::CardsWithoutJobs);
+    }
+
+    if diagnostics.job_count > 0 && unknown_ratio >= HIGH_UNKNOWN_RATIO {
+        anomalies.push(ParseAnomaly::HighUnknownRatio);
+    }
+
+    let recent: Vec<&ParseHealth> = history.iter().rev().take(10).collect();
+    if recent.len() >= MIN_HISTORY_FOR_DRIFT {
+        let median_jobs = median(recent.iter().map(|run| run.diagnostics.job_count).collect());
+        if median_jobs >= MIN_MEDIAN_FOR_COUNT_DROP
+            && (diagnostics.job_count as f64) < median_jobs as f64 * COUNT_DROP_RATIO
+        {
+            anomalies.push(ParseAnomaly::CountDrop);
+        }
+
+        let average_ratio = recent.iter().map(|run| run.unknown_ratio).sum::<f64>() / recent.len() as f64;
+        if diagnostics.job_count > 0 && unknown_ratio - average_ratio > UNKNOWN_RATIO_JUMP {
+            anomalies.push(ParseAnomaly::UnknownRatioJump);
+        }
+
+        if recent.iter().all(|run| run.diagnostics.path != diagnostics.path) {
+            anomalies.push(ParseAnomaly::PathChanged);
+        }
+    }
+
+    ParseHealth {
+        checked_at: Local::now(),
+        diagnostics,
+        unknown_ratio,
+        anomalies,
+        saved_html: None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn diagnostics(path: ParsePath, html_bytes: usize, cards: usize, jobs: usize, unknown: usize) -> ParseDiagnostics {
+        ParseDiagnostics {
+            path,
+            html_bytes,
+            card_count: cards,
+            job_count: jobs,
+            unknown_fields: unknown,
+        }
+    }
+
+    fn history(jobs: usize, count: usize) -> Vec<ParseHealth> {
+        (0..count)
+            .map(|_| assess(diagnostics(ParsePath::Html, 200_000, jobs, jobs, 0), &[]))
+            .collect()
+    }
+
+    #[test]
+    fn test_healthy_run() {
+        let health = assess(diagnostics(ParsePath::Html, 200_000, 25, 25, 2), &history(25, 5));
+        assert!(health.is_healthy());
+    }
+
+    #[test]
+    fn test_empty_large_page() {
+        let health = assess(diagnostics(ParsePath::Html, 500_000, 0, 0, 0), &[]);
+        assert_eq!(health.anomalies, vec![ParseAnomaly::EmptyLargePage]);
+
+        let small = assess(diagnostics(ParsePath::Html, 2_000, 0, 0, 0), &[]);
+        assert!(small.is_healthy());
+    }
+
+    #[test]
+    fn test_cards_without_jobs_and_unknown_fields() {
+        let health = assess(diagnostics(ParsePath::Html, 10_000, 10, 0, 0), &[]);
+        assert!(health.anomalies.contains(&ParseAnomaly::CardsWithoutJobs));
+
+        let health = assess(diagnostics(ParsePath::Html, 10_000, 10, 10, 20), &[]);
+        assert!(health.anomalies.contains(&ParseAnomaly::HighUnknownRatio));
+    }
+
+    #[test]
+    fn test_drift_against_history() {
+        let health = assess(diagnostics(ParsePath::Html, 200_000, 3, 3, 0), &history(25, 5));
+        assert_eq!(health.anomalies, vec![ParseAnomaly::CountDrop]);
+
+        let health = assess(diagnostics(ParsePath::Html, 200_000, 25, 25, 30), &history(25, 5));
+        assert!(health.anomalies.contains(&ParseAnomaly::UnknownRatioJump));
+
+        let health = assess(diagnostics(ParsePath::Json, 200_000, 25, 25, 0), &history(25, 5));
+        assert_eq!(health.anomalies, vec![ParseAnomaly::PathChanged]);
+    }
+
+    #[test]
+    fn test_no_drift_checks_without_history() {
+        let health = assess(diagnostics(ParsePath::Html, 200_000, 3, 3, 0), &history(25, 2));
+        assert!(health.is_healthy());
+    }
+}
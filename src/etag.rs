@@ -0,0 +1,36 @@
+use crate::hash;
+
+/// Strong ETag for `bytes`
+pub fn of(bytes: &[u8]) -> String {
+    format!("\"{:016x}\"", hash::fnv1a(bytes))
+}
+
+/// Whether an `If-Match` / `If-None-Match` value lists `etag` or is `*`.
+/// Weak tags (`W/"..."`) compare by their value.
+pub fn matches(header_value: &str, etag: &str) -> bool {
+    header_value
+        .split(',')
+        .map(|tag| tag.trim().trim_start_matches("W/"))
+        .any(|tag| tag == "*" || tag == etag)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_of_is_stable_and_quoted() {
+        assert_eq!(of(b""), "\"cbf29ce484222325\"");
+        assert_eq!(of(b"config"), of(b"config"));
+        assert_ne!(of(b"config"), of(b"config "));
+    }
+
+    #[test]
+    fn test_matches() {
+        let etag = of(b"config");
+        assert!(matches(&etag, &etag));
+        assert!(matches(&format!("\"other\", W/{}", etag), &etag));
+        assert!(matches("*", &etag));
+        assert!(!matches("\"other\"", &etag));
+    }
+}
@@ -0,0 +1,168 @@
+use serde::{Deserialize, Serialize};
+
+/// A LinkedIn search filter with a fixed set of values, each sent as a short code
+pub trait Facet: Copy + PartialEq + Serialize + 'static {
+    /// Every value, in the order codes are sent
+    const ALL: &'static [Self];
+
+    fn code(self) -> &'static str;
+}
+
+/// Codes of the selected values, comma-separated as LinkedIn expects
+/// (duplicates dropped, in `Facet::ALL` order so URLs are stable)
+pub fn join<F: Facet>(selected: &[F]) -> String {
+    F::ALL
+        .iter()
+        .filter(|value| selected.contains(value))
+        .map(|value| value.code())
+        .collect::<Vec<_>>()
+        .join(",")
+}
+
+/// Config names of every value, e.g. `["on_site", "remote", "hybrid"]`
+pub fn names<F: Facet>() -> Vec<String> {
+    F::ALL
+        .iter()
+        .filter_map(|value| serde_json::to_value(value).ok())
+        .filter_map(|value| value.as_str().map(str::to_string))
+        .collect()
+}
+
+/// `f_E`
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ExperienceLevel {
+    Internship,
+    Entry,
+    Associate,
+    MidSenior,
+    Director,
+    Executive,
+}
+
+impl Facet for ExperienceLevel {
+    const ALL: &'static [Self] = &[
+        Self::Internship,
+        Self::Entry,
+        Self::Associate,
+        Self::MidSenior,
+        Self::Director,
+        Self::Executive,
+    ];
+
+    fn code(self) -> &'static str {
+        match self {
+            Self::Internship => "1",
+            Self::Entry => "2",
+            Self::Associate => "3",
+            Self::MidSenior => "4",
+            Self::Director => "5",
+            Self::Executive => "6",
+        }
+    }
+}
+
+/// `f_JT`
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum JobType {
+    FullTime,
+    PartTime,
+    Contract,
+    Temporary,
+    Volunteer,
+    Internship,
+    Other,
+}
+
+impl Facet for JobType {
+    const ALL: &'static [Self] = &[
+        Self::FullTime,
+        Self::PartTime,
+        Self::Contract,
+        Self::Temporary,
+        Self::Volunteer,
+        Self::Internship,
+        Self::Other,
+    ];
+
+    fn code(self) -> &'static str {
+        match self {
+            Self::FullTime => "F",
+            Self::PartTime => "P",
+            Self::Contract => "C",
+            Self::Temporary => "T",
+            Self::Volunteer => "V",
+            Self::Internship => "I",
+            Self::Other => "O",
+        }
+    }
+}
+
+/// `f_WT`
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Workplace {
+    OnSite,
+    Remote,
+    Hybrid,
+}
+
+impl Facet for Workplace {
+    const ALL: &'static [Self] = &[Self::OnSite, Self::Remote, Self::Hybrid];
+
+    fn code(self) -> &'static str {
+        match self {
+            Self::OnSite => "1",
+            Self::Remote => "2",
+            Self::Hybrid => "3",
+        }
+    }
+}
+
+/// `sortBy`
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum SortBy {
+    /// LinkedIn's default; no parameter is sent
+    #[default]
+    Relevance,
+    /// Most recent first
+    Date,
+}
+
+impl Facet for SortBy {
+    const ALL: &'static [Self] = &[Self::Relevance, Self::Date];
+
+    fn code(self) -> &'static str {
+        match self {
+            Self::Relevance => "R",
+            Self::Date => "DD",
+        }
+    }
+}
+
+/// Radii (miles) LinkedIn offers for `distance`
+pub const DISTANCES_MILES: &[u32] = &[0, 5, 10, 25, 50, 100];
+
+/// Widest `f_TPR` window LinkedIn's own filter offers ("past month")
+pub const MAX_POSTED_WITHIN_HOURS: u32 = 30 * 24;
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_join_is_stable_and_deduplicated() {
+        assert_eq!(join(&[Workplace::Hybrid, Workplace::Remote, Workplace::Hybrid]), "2,3");
+        assert_eq!(join(&[JobType::Contract, JobType::FullTime]), "F,C");
+        assert_eq!(join::<ExperienceLevel>(&[]), "");
+    }
+
+    #[test]
+    fn test_names_match_config_spelling() {
+        assert_eq!(names::<Workplace>(), vec!["on_site", "remote", "hybrid"]);
+        assert!(names::<ExperienceLevel>().contains(&"mid_senior".to_string()));
+        assert_eq!(names::<SortBy>(), vec!["relevance", "date"]);
+    }
+}
@@ -0,0 +1,330 @@
+use crate::config::BrowserConfig;
+use crate::cookies::StoredCookie;
+use crate::session::SESSION_COOKIE;
+use anyhow::{Context, Result};
+use chrono::{DateTime, Local};
+use rand::seq::SliceRandom;
+use reqwest::header::{self, HeaderMap, HeaderName, HeaderValue};
+use serde::{Deserialize, Serialize};
+
+/// `profile` value that picks a profile per run instead of a fixed one
+pub const ROTATE: &str = "rotate";
+
+pub const DEFAULT_PROFILE: &str = "chrome-mac";
+
+/// Headers of one browser on one platform. Every header a profile sends has to
+/// agree with its User-Agent, or the mismatch itself becomes the fingerprint.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct BrowserProfile {
+    pub name: &'static str,
+    pub user_agent: &'static str,
+    pub accept: &'static str,
+    pub accept_language: &'static str,
+    /// Client hints, sent by Chromium-based browsers only
+    pub sec_ch_ua: Option<&'static str>,
+    pub sec_ch_ua_mobile: Option<&'static str>,
+    pub sec_ch_ua_platform: Option<&'static str>,
+    pub mobile: bool,
+}
+
+const CHROME_ACCEPT: &str = "text/html,application/xhtml+xml,application/xml;q=0.9,image/avif,image/webp,image/apng,*/*;q=0.8,application/signed-exchange;v=b3;q=0.7";
+const CHROME_SEC_CH_UA: &str = r#""Google Chrome";v="131", "Chromium";v="131", "Not_A Brand";v="24""#;
+const FIREFOX_ACCEPT: &str = "text/html,application/xhtml+xml,application/xml;q=0.9,image/avif,image/webp,*/*;q=0.8";
+const SAFARI_ACCEPT: &str = "text/html,application/xhtml+xml,application/xml;q=0.9,*/*;q=0.8";
+
+pub const PROFILES: &[BrowserProfile] = &[
+    BrowserProfile {
+        name: "chrome-mac",
+        user_agent: "Mozilla/5.0 (Macintosh; Intel Mac OS X 10_15_7) AppleWebKit/537.36 (KHTML, like Gecko) Chrome/131.0.0.0 Safari/537.36",
+        accept: CHROME_ACCEPT,
+        accept_language: "en-US,en;q=0.9",
+        sec_ch_ua: Some(CHROME_SEC_CH_UA),
+        sec_ch_ua_mobile: Some("?0"),
+        sec_ch_ua_platform: Some(r#""macOS""#),
+        mobile: false,
+    },
+    BrowserProfile {
+        name: "chrome-windows",
+        user_agent: "Mozilla/5.0 (Windows NT 10.0; Win64; x64) AppleWebKit/537.36 (KHTML, like Gecko) Chrome/131.0.0.0 Safari/537.36",
+        accept: CHROME_ACCEPT,
+        accept_language: "en-US,en;q=0.9",
+        sec_ch_ua: Some(CHROME_SEC_CH_UA),
+        sec_ch_ua_mobile: Some("?0"),
+        sec_ch_ua_platform: Some(r#""Windows""#),
+        mobile: false,
+    },
+    BrowserProfile {
+        name: "chrome-android",
+        user_agent: "Mozilla/5.0 (Linux; Android 10; K) AppleWebKit/537.36 (KHTML, like Gecko) Chrome/131.0.0.0 Mobile Safari/537.36",
+        accept: CHROME_ACCEPT,
+        accept_language: "en-US,en;q=0.9",
+        sec_ch_ua: Some(CHROME_SEC_CH_UA),
+        sec_ch_ua_mobile: Some("?1"),
+        sec_ch_ua_platform: Some(r#""Android""#),
+        mobile: true,
+    },
+    BrowserProfile {
+        name: "firefox-windows",
+        user_agent: "Mozilla/5.0 (Windows NT 10.0; Win64; x64; rv:133.0) Gecko/20100101 Firefox/133.0",
+        accept: FIREFOX_ACCEPT,
+        accept_language: "en-US,en;q=0.5",
+        sec_ch_ua: None,
+        sec_ch_ua_mobile: None,
+        sec_ch_ua_platform: None,
+        mobile: false,
+    },
+    BrowserProfile {
+        name: "firefox-mac",
+        user_agent: "Mozilla/5.0 (Macintosh; Intel Mac OS X 10.15; rv:133.0) Gecko/20100101 Firefox/133.0",
+        accept: FIREFOX_ACCEPT,
+        accept_language: "en-US,en;q=0.5",
+        sec_ch_ua: None,
+        sec_ch_ua_mobile: None,
+        sec_ch_ua_platform: None,
+        mobile: false,
+    },
+    BrowserProfile {
+        name: "firefox-android",
+        user_agent: "Mozilla/5.0 (Android 14; Mobile; rv:133.0) Gecko/133.0 Firefox/133.0",
+        accept: FIREFOX_ACCEPT,
+        accept_language: "en-US,en;q=0.5",
+        sec_ch_ua: None,
+        sec_ch_ua_mobile: None,
+        sec_ch_ua_platform: None,
+        mobile: true,
+    },
+    BrowserProfile {
+        name: "safari-mac",
+        user_agent: "Mozilla/5.0 (Macintosh; Intel Mac OS X 10_15_7) AppleWebKit/605.1.15 (KHTML, like Gecko) Version/18.1 Safari/605.1.15",
+        accept: SAFARI_ACCEPT,
+        accept_language: "en-US,en;q=0.9",
+        sec_ch_ua: None,
+        sec_ch_ua_mobile: None,
+        sec_ch_ua_platform: None,
+        mobile: false,
+    },
+    BrowserProfile {
+        name: "safari-ios",
+        user_agent: "Mozilla/5.0 (iPhone; CPU iPhone OS 18_1 like Mac OS X) AppleWebKit/605.1.15 (KHTML, like Gecko) Version/18.1 Mobile/15E148 Safari/604.1",
+        accept: SAFARI_ACCEPT,
+        accept_language: "en-US,en;q=0.9",
+        sec_ch_ua: None,
+        sec_ch_ua_mobile: None,
+        sec_ch_ua_platform: None,
+        mobile: true,
+    },
+];
+
+impl BrowserProfile {
+    pub fn find(name: &str) -> Option<&'static BrowserProfile> {
+        PROFILES.iter().find(|profile| profile.name == name)
+    }
+
+    pub fn default_profile() -> &'static BrowserProfile {
+        Self::find(DEFAULT_PROFILE).expect("default browser profile exists")
+    }
+
+    /// Default request headers of this browser navigating within linkedin.com
+    pub fn headers(&self) -> HeaderMap {
+        let mut headers = HeaderMap::new();
+        let mut insert = |name: HeaderName, value: &'static str| {
+            headers.insert(name, HeaderValue::from_static(value));
+        };
+
+        insert(header::USER_AGENT, self.user_agent);
+        insert(header::ACCEPT, self.accept);
+        insert(header::ACCEPT_LANGUAGE, self.accept_language);
+        insert(header::ACCEPT_ENCODING, "gzip, deflate, br");
+        insert(header::REFERER, "https://www.linkedin.com/");
+        if let (Some(ua), Some(mobile), Some(platform)) = (self.sec_ch_ua, self.sec_ch_ua_mobile, self.sec_ch_ua_platform) {
+            insert(HeaderName::from_static("sec-ch-ua"), ua);
+            insert(HeaderName::from_static("sec-ch-ua-mobile"), mobile);
+            insert(HeaderName::from_static("sec-ch-ua-platform"), platform);
+        }
+        insert(HeaderName::from_static("sec-fetch-dest"), "document");
+        insert(HeaderName::from_static("sec-fetch-mode"), "navigate");
+        insert(HeaderName::from_static("sec-fetch-site"), "same-origin");
+        insert(HeaderName::from_static("upgrade-insecure-requests"), "1");
+
+        headers
+    }
+}
+
+/// The profile a cookie session was last used with
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ProfilePin {
+    pub profile: String,
+    /// Hash of the session cookie, so the pin follows the session without storing it
+    pub session: String,
+    pub pinned_at: DateTime<Local>,
+}
+
+/// Stable identifier of the cookie session, if there is one
+pub fn session_key(cookies: &[StoredCookie]) -> Option<String> {
+    let session = cookies
+        .iter()
+        .find(|cookie| cookie.name == SESSION_COOKIE && !cookie.value.is_empty())?;
+    Some(format!("{:016x}", crate::hash::fnv1a(session.value.as_bytes())))
+}
+
+/// Profiles that `rotate` picks from
+fn rotation(config: &BrowserConfig) -> Result<Vec<&'static BrowserProfile>> {
+    if config.rotate.is_empty() {
+        return Ok(PROFILES.iter().collect());
+    }
+    config.rotate.iter().map(|name| lookup(name)).collect()
+}
+
+fn lookup(name: &str) -> Result<&'static BrowserProfile> {
+    BrowserProfile::find(name).with_context(|| {
+        let names: Vec<&str> = PROFILES.iter().map(|profile| profile.name).collect();
+        format!("Unknown browser profile {:?} (available: {})", name, names.join(", "))
+    })
+}
+
+/// Check that the configured profile names exist
+pub fn validate(config: &BrowserConfig) -> Result<()> {
+    if config.profile != ROTATE {
+        lookup(&config.profile)?;
+    }
+    rotation(config).map(|_| ())
+}
+
+/// Profile for a run, and the pin to store when it changed
+#[derive(Debug, Clone)]
+pub struct ProfileChoice {
+    pub profile: &'static BrowserProfile,
+    pub new_pin: Option<ProfilePin>,
+}
+
+/// Pick the profile for a run. A fixed profile is always used as configured; when
+/// rotating, a logged-in session keeps the profile it is pinned to so the same
+/// cookies never show up from a different browser.
+pub fn choose_profile(config: &BrowserConfig, session: Option<&str>, pin: Option<&ProfilePin>) -> Result<ProfileChoice> {
+    let pinned = pin
+        .filter(|pin| Some(pin.session.as_str()) == session)
+        .and_then(|pin| BrowserProfile::find(&pin.profile));
+
+    let profile = if config.profile == ROTATE {
+        let candidates = rotation(config)?;
+        match pinned {
+            Some(pinned) if candidates.contains(&pinned) => pinned,
+            _ => pick_random(&candidates),
+        }
+    } else {
+        lookup(&config.profile)?
+    };
+
+    let new_pin = match session {
+        Some(session) if pinned != Some(profile) => Some(ProfilePin {
+            profile: profile.name.to_string(),
+            session: session.to_string(),
+            pinned_at: Local::now(),
+        }),
+        _ => None,
+    };
+
+    Ok(ProfileChoice { profile, new_pin })
+}
+
+fn pick_random(candidates: &[&'static BrowserProfile]) -> &'static BrowserProfile {
+    candidates
+        .choose(&mut rand::thread_rng())
+        .copied()
+        .unwrap_or_else(BrowserProfile::default_profile)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn config(profile: &str, rotate: &[&str]) -> BrowserConfig {
+        BrowserConfig {
+            profile: profile.to_string(),
+            rotate: rotate.iter().map(|name| name.to_string()).collect(),
+        }
+    }
+
+    fn pin(profile: &str, session: &str) -> ProfilePin {
+        ProfilePin {
+            profile: profile.to_string(),
+            session: session.to_string(),
+            pinned_at: Local::now(),
+        }
+    }
+
+    #[test]
+    fn test_profiles_are_consistent() {
+        for profile in PROFILES {
+            let headers = profile.headers();
+            let ua = profile.user_agent;
+            let chromium = ua.contains("Chrome/");
+            assert_eq!(headers.contains_key("sec-ch-ua"), chromium, "{}", profile.name);
+            assert_eq!(profile.mobile, ua.contains("Mobile"), "{}", profile.name);
+            if let Some(mobile) = profile.sec_ch_ua_mobile {
+                assert_eq!(mobile == "?1", profile.mobile, "{}", profile.name);
+            }
+            if ua.contains("Firefox/") {
+                assert!(!ua.contains("AppleWebKit"), "{}", profile.name);
+            }
+            assert_eq!(headers.get(header::USER_AGENT).unwrap(), ua);
+        }
+    }
+
+    #[test]
+    fn test_fixed_profile() {
+        let choice = choose_profile(&config("firefox-mac", &[]), None, None).unwrap();
+        assert_eq!(choice.profile.name, "firefox-mac");
+        assert!(choice.new_pin.is_none());
+
+        assert!(choose_profile(&config("netscape", &[]), None, None).is_err());
+    }
+
+    #[test]
+    fn test_rotation_keeps_pinned_session_profile() {
+        let config = config(ROTATE, &["chrome-windows", "safari-mac"]);
+        let existing = pin("safari-mac", "abc");
+
+        for _ in 0..10 {
+            let choice = choose_profile(&config, Some("abc"), Some(&existing)).unwrap();
+            assert_eq!(choice.profile.name, "safari-mac");
+            assert!(choice.new_pin.is_none());
+        }
+    }
+
+    #[test]
+    fn test_new_session_gets_new_pin() {
+        let config = config(ROTATE, &["chrome-windows"]);
+        let existing = pin("safari-mac", "old");
+
+        let choice = choose_profile(&config, Some("new"), Some(&existing)).unwrap();
+        assert_eq!(choice.profile.name, "chrome-windows");
+        let new_pin = choice.new_pin.unwrap();
+        assert_eq!(new_pin.profile, "chrome-windows");
+        assert_eq!(new_pin.session, "new");
+    }
+
+    #[test]
+    fn test_session_key_is_stable() {
+        // Pins are persisted under this key, so it must not change between builds
+        let cookies = crate::cookies::parse_cookies("li_at=AQEDAS; lang=en").unwrap();
+        assert_eq!(session_key(&cookies).as_deref(), Some("b427a1a2bc070da2"));
+        assert!(session_key(&cookies[1..]).is_none());
+    }
+
+    #[test]
+    fn test_pin_outside_rotation_is_replaced() {
+        let config = config(ROTATE, &["firefox-windows"]);
+        let existing = pin("safari-mac", "abc");
+
+        let choice = choose_profile(&config, Some("abc"), Some(&existing)).unwrap();
+        assert_eq!(choice.profile.name, "firefox-windows");
+        assert!(choice.new_pin.is_some());
+    }
+
+    #[test]
+    fn test_validate() {
+        assert!(validate(&BrowserConfig::default()).is_ok());
+        assert!(validate(&config(ROTATE, &["chrome-mac", "lynx"])).is_err());
+    }
+}
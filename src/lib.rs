@@ -1,8 +1,34 @@
 pub mod models;
+pub mod logging;
+pub mod metrics;
 pub mod scraper;
+pub mod selectors;
+pub mod diagnostics;
+pub mod archive;
+pub mod replay;
+pub mod proxy;
+pub mod ratelimit;
+pub mod cookies;
+pub mod hash;
+pub mod fingerprint;
+pub mod session;
+pub mod notify;
+pub mod secrets;
+pub mod auth;
 pub mod state;
+pub mod dedup;
 pub mod filters;
 pub mod config;
+pub mod facets;
+pub mod migrate;
+pub mod schema;
+pub mod overrides;
 pub mod storage;
 pub mod service;
 pub mod scheduler;
+pub mod server;
+pub mod assets;
+pub mod etag;
+pub mod audit;
+pub mod readiness;
+pub mod reload;
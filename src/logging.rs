@@ -0,0 +1,97 @@
+use tracing_subscriber::fmt::MakeWriter;
+use tracing_subscriber::EnvFilter;
+
+/// Filter used when `RUST_LOG` is not set
+pub const DEFAULT_FILTER: &str = "info";
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, clap::ValueEnum)]
+pub enum LogFormat {
+    /// Human-readable lines
+    #[default]
+    Text,
+    /// One JSON object per line, with span fields (run id, URL, status)
+    Json,
+}
+
+fn env_filter() -> EnvFilter {
+    EnvFilter::try_from_default_env().unwrap_or_else(|_| EnvFilter::new(DEFAULT_FILTER))
+}
+
+/// Build a subscriber writing to `writer`; split out so tests can capture output
+fn subscriber<W>(format: LogFormat, filter: EnvFilter, writer: W) -> Box<dyn tracing::Subscriber + Send + Sync>
+where
+    W: for<'writer> MakeWriter<'writer> + Send + Sync + 'static,
+{
+    let builder = tracing_subscriber::fmt().with_env_filter(filter).with_writer(writer);
+    match format {
+        LogFormat::Text => Box::new(builder.finish()),
+        LogFormat::Json => Box::new(builder.json().with_current_span(true).with_span_list(true).finish()),
+    }
+}
+
+/// Install the global subscriber. Verbosity comes from `RUST_LOG` (default `info`),
+/// e.g. `RUST_LOG=fresh_post::scraper=debug` for per-card lines. Logs go to stderr
+/// so command output on stdout stays clean.
+pub fn init(format: LogFormat) {
+    let subscriber = subscriber(format, env_filter(), std::io::stderr);
+    // A second init (e.g. in tests) keeps the first subscriber
+    let _ = tracing::subscriber::set_global_default(subscriber);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io::Write;
+    use std::sync::{Arc, Mutex};
+
+    #[derive(Clone, Default)]
+    struct Buffer(Arc<Mutex<Vec<u8>>>);
+
+    impl Write for Buffer {
+        fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+            self.0.lock().unwrap().extend_from_slice(buf);
+            Ok(buf.len())
+        }
+
+        fn flush(&mut self) -> std::io::Result<()> {
+            Ok(())
+        }
+    }
+
+    fn capture(format: LogFormat, filter: &str, log: impl FnOnce()) -> String {
+        let buffer = Buffer::default();
+        let writer = buffer.clone();
+        let subscriber = subscriber(format, EnvFilter::new(filter), move || writer.clone());
+        tracing::subscriber::with_default(subscriber, log);
+        let output = buffer.0.lock().unwrap().clone();
+        String::from_utf8(output).unwrap()
+    }
+
+    #[test]
+    fn test_json_lines_carry_span_fields() {
+        let output = capture(LogFormat::Json, "info", || {
+            let _run = tracing::info_span!("run", run_id = "20240101-120000-000").entered();
+            tracing::info!(status = 200, "fetched page");
+        });
+
+        let line: serde_json::Value = serde_json::from_str(output.lines().next().unwrap()).unwrap();
+        assert_eq!(line["fields"]["message"], "fetched page");
+        assert_eq!(line["fields"]["status"], 200);
+        assert_eq!(line["span"]["run_id"], "20240101-120000-000");
+    }
+
+    #[test]
+    fn test_debug_lines_filtered_at_info() {
+        let log = || {
+            tracing::debug!("extracted job card");
+            tracing::info!("parsed job cards");
+        };
+
+        let output = capture(LogFormat::Text, "info", log);
+        assert!(!output.contains("extracted job card"));
+        assert!(output.contains("parsed job cards"));
+
+        let output = capture(LogFormat::Text, "debug", log);
+        assert!(output.contains("extracted job card"));
+    }
+}
@@ -1,34 +1,79 @@
 mod api;
+mod cli;
 
 use anyhow::Result;
-use axum::routing::get_service;
-use fresh_post::{scheduler, storage};
+use axum::middleware;
+use clap::Parser;
+use cli::{Cli, Command};
+use fresh_post::{assets, auth, logging, migrate, reload, scheduler, server, storage};
 use std::sync::Arc;
 use tokio::sync::Mutex;
-use tower_http::services::{ServeDir, ServeFile};
 
 #[tokio::main]
 async fn main() -> Result<()> {
+    let cli = Cli::parse();
+    logging::init(cli.log_format);
     let paths = storage::Paths::new()?;
+
+    match cli.command.unwrap_or(Command::Serve) {
+        Command::Serve => serve(paths).await,
+        Command::Task(task) => cli::run(task, &paths).await,
+    }
+}
+
+/// Rewrite an older config.toml as the current version. When that fails (e.g. a
+/// read-only mount), the file is still upgraded in memory each time it is loaded.
+fn upgrade_config(paths: &storage::Paths) {
+    match storage::upgrade_config_file(paths) {
+        Ok(Some(upgrade)) => tracing::info!(
+            from = upgrade.from_version,
+            to = migrate::CURRENT_VERSION,
+            backup = %upgrade.backup.display(),
+            steps = ?upgrade.applied,
+            "upgraded config.toml"
+        ),
+        Ok(None) => {}
+        Err(err) => tracing::warn!(error = %format!("{:#}", err), "could not upgrade config.toml on disk"),
+    }
+}
+
+/// Web UI and API, with the scheduler running in the background
+async fn serve(paths: storage::Paths) -> Result<()> {
+    upgrade_config(&paths);
+    // Fail fast on a broken selectors.toml instead of on every scrape
+    storage::load_selector_profile(&paths)?.compile()?;
+    storage::migrate_plaintext_cookies(&paths)?;
+    let live_config = Arc::new(reload::LiveConfig::load(&paths)?);
+    let config = live_config.current();
+    server::validate(&config.server)?;
+    let base_path = server::normalize_base_path(&config.server.base_path)?;
+    let credentials = storage::load_credentials(&paths, &config.auth)?;
+    let auth = Arc::new(auth::Auth::new(credentials, config.auth.session_hours));
+    if !auth.is_enabled() {
+        tracing::warn!("authentication is off; anyone who can reach the server can change the config");
+    }
     let last_run = Arc::new(Mutex::new(None));
 
     let app_state = api::AppState {
         paths: paths.clone(),
         last_run: last_run.clone(),
+        started_at: chrono::Local::now(),
+        auth,
+        base_path: base_path.clone(),
+        secure_cookies: server::is_tls(&config.server),
+        config_lock: Arc::new(Mutex::new(())),
+        config: live_config.clone(),
     };
 
-    tokio::spawn(scheduler::run_scheduler(paths.clone(), last_run.clone()));
-
-    let app = api::router(app_state)
-        .route("/", get_service(ServeFile::new("web/index.html")))
-        .route("/jobs", get_service(ServeFile::new("web/jobs.html")))
-        .fallback_service(ServeDir::new("web"));
-
-    let addr = "0.0.0.0:8080";
-    println!("Server running at http://{}", addr);
+    tokio::spawn(live_config.clone().watch());
+    tokio::spawn(scheduler::run_scheduler(paths.clone(), last_run.clone(), live_config));
 
-    let listener = tokio::net::TcpListener::bind(addr).await?;
-    axum::serve(listener, app).await?;
+    if let Some(web_dir) = &config.server.web_dir {
+        tracing::info!(web_dir = %web_dir.display(), "serving the web UI from disk");
+    }
+    let app = api::router(app_state.clone())
+        .merge(assets::router(config.server.web_dir.as_deref()))
+        .layer(middleware::from_fn_with_state(app_state, api::require_auth));
 
-    Ok(())
+    server::serve(server::with_base_path(app, &base_path), &config.server).await
 }
@@ -0,0 +1,291 @@
+use std::collections::BTreeMap;
+use std::fmt::Write;
+use std::sync::{Mutex, OnceLock};
+use std::time::Duration;
+
+/// Content type of the Prometheus text exposition format
+pub const CONTENT_TYPE: &str = "text/plain; version=0.0.4; charset=utf-8";
+
+/// Upper bounds of the run duration histogram, in seconds
+const DURATION_BUCKETS: &[f64] = &[1.0, 5.0, 10.0, 30.0, 60.0, 120.0, 300.0, 600.0];
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum RunOutcome {
+    Success,
+    Failure,
+}
+
+impl RunOutcome {
+    fn label(self) -> &'static str {
+        match self {
+            RunOutcome::Success => "success",
+            RunOutcome::Failure => "failure",
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum NotificationOutcome {
+    /// Sent to at least one connected web UI
+    Delivered,
+    /// No web UI was connected; it gets the warning when it connects
+    Dropped,
+    /// A connected web UI fell behind and skipped it
+    Failed,
+}
+
+impl NotificationOutcome {
+    fn label(self) -> &'static str {
+        match self {
+            NotificationOutcome::Delivered => "delivered",
+            NotificationOutcome::Dropped => "dropped",
+            NotificationOutcome::Failed => "failed",
+        }
+    }
+}
+
+/// Job counts of one search in one run
+#[derive(Debug, Clone, Copy, Default)]
+pub struct SearchCounts {
+    pub parsed: usize,
+    pub today: usize,
+    pub new: usize,
+}
+
+#[derive(Debug, Default)]
+struct Histogram {
+    /// Count per bucket in `DURATION_BUCKETS`, not cumulative
+    buckets: Vec<u64>,
+    sum: f64,
+    count: u64,
+}
+
+impl Histogram {
+    fn observe(&mut self, value: f64) {
+        if self.buckets.is_empty() {
+            self.buckets = vec![0; DURATION_BUCKETS.len()];
+        }
+        if let Some(index) = DURATION_BUCKETS.iter().position(|bound| value <= *bound) {
+            self.buckets[index] += 1;
+        }
+        self.sum += value;
+        self.count += 1;
+    }
+}
+
+#[derive(Debug, Default)]
+struct Registry {
+    runs: BTreeMap<&'static str, u64>,
+    run_duration: Histogram,
+    http_requests: BTreeMap<String, u64>,
+    jobs_parsed: BTreeMap<String, u64>,
+    jobs_new: BTreeMap<String, u64>,
+    jobs_today: BTreeMap<String, u64>,
+    parse_path: BTreeMap<String, u64>,
+    seen_jobs: Option<u64>,
+    notifications: BTreeMap<&'static str, u64>,
+    last_success: Option<i64>,
+    next_run: Option<i64>,
+}
+
+/// Process-wide counters and gauges, rendered at `/metrics`
+#[derive(Debug, Default)]
+pub struct Metrics {
+    registry: Mutex<Registry>,
+}
+
+impl Metrics {
+    pub fn global() -> &'static Metrics {
+        static GLOBAL: OnceLock<Metrics> = OnceLock::new();
+        GLOBAL.get_or_init(Metrics::default)
+    }
+
+    fn update(&self, update: impl FnOnce(&mut Registry)) {
+        let mut registry = self.registry.lock().unwrap_or_else(|poisoned| poisoned.into_inner());
+        update(&mut registry);
+    }
+
+    /// A finished scrape run; successful runs also set the last-success timestamp
+    pub fn record_run(&self, outcome: RunOutcome, duration: Duration) {
+        self.update(|registry| {
+            *registry.runs.entry(outcome.label()).or_default() += 1;
+            registry.run_duration.observe(duration.as_secs_f64());
+            if outcome == RunOutcome::Success {
+                registry.last_success = Some(chrono::Utc::now().timestamp());
+            }
+        });
+    }
+
+    /// An HTTP response by status code, or `None` when no response arrived
+    pub fn record_http_request(&self, status: Option<u16>) {
+        let label = status.map_or_else(|| "error".to_string(), |status| status.to_string());
+        self.update(|registry| *registry.http_requests.entry(label).or_default() += 1);
+    }
+
+    pub fn record_search(&self, search: &str, counts: SearchCounts) {
+        self.update(|registry| {
+            *registry.jobs_parsed.entry(search.to_string()).or_default() += counts.parsed as u64;
+            *registry.jobs_new.entry(search.to_string()).or_default() += counts.new as u64;
+            registry.jobs_today.insert(search.to_string(), counts.today as u64);
+        });
+    }
+
+    /// Parse path (`json` or `html`) used for a page
+    pub fn record_parse_path(&self, path: &str) {
+        self.update(|registry| *registry.parse_path.entry(path.to_string()).or_default() += 1);
+    }
+
+    pub fn set_seen_jobs(&self, count: usize) {
+        self.update(|registry| registry.seen_jobs = Some(count as u64));
+    }
+
+    /// `count` notifications from `notify::Notifier` with the same outcome
+    pub fn record_notifications(&self, outcome: NotificationOutcome, count: u64) {
+        self.update(|registry| *registry.notifications.entry(outcome.label()).or_default() += count);
+    }
+
+    pub fn set_next_run(&self, at: chrono::DateTime<chrono::Utc>) {
+        self.update(|registry| registry.next_run = Some(at.timestamp()));
+    }
+
+    /// All metrics in the Prometheus text format
+    pub fn render(&self) -> String {
+        let registry = self.registry.lock().unwrap_or_else(|poisoned| poisoned.into_inner());
+        let mut out = String::new();
+
+        let mut runs = registry.runs.clone();
+        for outcome in [RunOutcome::Success, RunOutcome::Failure] {
+            runs.entry(outcome.label()).or_default();
+        }
+        family(&mut out, "fresh_post_runs_total", "counter", "Scrape runs by outcome");
+        for (outcome, count) in &runs {
+            sample(&mut out, "fresh_post_runs_total", &[("outcome", outcome)], *count as f64);
+        }
+
+        family(&mut out, "fresh_post_run_duration_seconds", "histogram", "Duration of scrape runs");
+        let histogram = &registry.run_duration;
+        let mut cumulative = 0;
+        for (index, bound) in DURATION_BUCKETS.iter().enumerate() {
+            cumulative += histogram.buckets.get(index).copied().unwrap_or(0);
+            let bound = bound.to_string();
+            sample(&mut out, "fresh_post_run_duration_seconds_bucket", &[("le", &bound)], cumulative as f64);
+        }
+        sample(&mut out, "fresh_post_run_duration_seconds_bucket", &[("le", "+Inf")], histogram.count as f64);
+        sample(&mut out, "fresh_post_run_duration_seconds_sum", &[], histogram.sum);
+        sample(&mut out, "fresh_post_run_duration_seconds_count", &[], histogram.count as f64);
+
+        family(&mut out, "fresh_post_http_requests_total", "counter", "HTTP requests to LinkedIn by response status (999 = blocked, error = no response)");
+        for (status, count) in &registry.http_requests {
+            sample(&mut out, "fresh_post_http_requests_total", &[("status", status)], *count as f64);
+        }
+
+        family(&mut out, "fresh_post_jobs_parsed_total", "counter", "Jobs parsed from search results");
+        labelled(&mut out, "fresh_post_jobs_parsed_total", "search", &registry.jobs_parsed);
+        family(&mut out, "fresh_post_jobs_new_total", "counter", "Jobs not seen before");
+        labelled(&mut out, "fresh_post_jobs_new_total", "search", &registry.jobs_new);
+        family(&mut out, "fresh_post_jobs_today", "gauge", "Jobs posted today in the latest run");
+        labelled(&mut out, "fresh_post_jobs_today", "search", &registry.jobs_today);
+
+        family(&mut out, "fresh_post_parse_path_total", "counter", "Result pages by parse path (json = logged-in view, html = guest view)");
+        labelled(&mut out, "fresh_post_parse_path_total", "path", &registry.parse_path);
+
+        if let Some(seen) = registry.seen_jobs {
+            family(&mut out, "fresh_post_seen_jobs", "gauge", "Job ids in the seen state");
+            sample(&mut out, "fresh_post_seen_jobs", &[], seen as f64);
+        }
+
+        let mut notifications = registry.notifications.clone();
+        for outcome in ["delivered", "dropped", "failed"] {
+            notifications.entry(outcome).or_default();
+        }
+        family(&mut out, "fresh_post_notifications_total", "counter", "Session warnings pushed to the web UI, by outcome (dropped: no UI was connected; failed: a UI missed it)");
+        for (outcome, count) in &notifications {
+            sample(&mut out, "fresh_post_notifications_total", &[("outcome", outcome)], *count as f64);
+        }
+
+        if let Some(timestamp) = registry.last_success {
+            family(&mut out, "fresh_post_last_success_timestamp_seconds", "gauge", "Unix time of the last successful run");
+            sample(&mut out, "fresh_post_last_success_timestamp_seconds", &[], timestamp as f64);
+        }
+
+        if let Some(timestamp) = registry.next_run {
+            family(&mut out, "fresh_post_scheduler_next_run_timestamp_seconds", "gauge", "Unix time of the next scheduled run");
+            sample(&mut out, "fresh_post_scheduler_next_run_timestamp_seconds", &[], timestamp as f64);
+        }
+
+        out
+    }
+}
+
+fn family(out: &mut String, name: &str, kind: &str, help: &str) {
+    let _ = writeln!(out, "# HELP {} {}", name, help);
+    let _ = writeln!(out, "# TYPE {} {}", name, kind);
+}
+
+fn labelled(out: &mut String, name: &str, label: &str, values: &BTreeMap<String, u64>) {
+    for (value, count) in values {
+        sample(out, name, &[(label, value)], *count as f64);
+    }
+}
+
+fn sample(out: &mut String, name: &str, labels: &[(&str, &str)], value: f64) {
+    out.push_str(name);
+    if !labels.is_empty() {
+        let labels: Vec<String> = labels
+            .iter()
+            .map(|(key, value)| format!("{}=\"{}\"", key, escape_label(value)))
+            .collect();
+        let _ = write!(out, "{{{}}}", labels.join(","));
+    }
+    let _ = writeln!(out, " {}", value);
+}
+
+fn escape_label(value: &str) -> String {
+    value.replace('\\', "\\\\").replace('"', "\\\"").replace('\n', "\\n")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_render_runs_and_histogram() {
+        let metrics = Metrics::default();
+        metrics.record_run(RunOutcome::Success, Duration::from_secs(3));
+        metrics.record_run(RunOutcome::Failure, Duration::from_secs(45));
+
+        let text = metrics.render();
+        assert!(text.contains("# TYPE fresh_post_runs_total counter"));
+        assert!(text.contains("fresh_post_runs_total{outcome=\"success\"} 1"));
+        assert!(text.contains("fresh_post_runs_total{outcome=\"failure\"} 1"));
+        assert!(text.contains("fresh_post_run_duration_seconds_bucket{le=\"1\"} 0"));
+        assert!(text.contains("fresh_post_run_duration_seconds_bucket{le=\"5\"} 1"));
+        assert!(text.contains("fresh_post_run_duration_seconds_bucket{le=\"60\"} 2"));
+        assert!(text.contains("fresh_post_run_duration_seconds_bucket{le=\"+Inf\"} 2"));
+        assert!(text.contains("fresh_post_run_duration_seconds_sum 48"));
+        assert!(text.contains("fresh_post_last_success_timestamp_seconds "));
+    }
+
+    #[test]
+    fn test_render_requests_and_searches() {
+        let metrics = Metrics::default();
+        metrics.record_http_request(Some(200));
+        metrics.record_http_request(Some(999));
+        metrics.record_http_request(None);
+        metrics.record_search("rust \"dev\" @ Berlin", SearchCounts { parsed: 25, today: 10, new: 4 });
+        metrics.record_search("rust \"dev\" @ Berlin", SearchCounts { parsed: 25, today: 8, new: 1 });
+        metrics.record_parse_path("html");
+        metrics.set_seen_jobs(42);
+
+        let text = metrics.render();
+        assert!(text.contains("fresh_post_http_requests_total{status=\"999\"} 1"));
+        assert!(text.contains("fresh_post_http_requests_total{status=\"error\"} 1"));
+        assert!(text.contains("fresh_post_jobs_parsed_total{search=\"rust \\\"dev\\\" @ Berlin\"} 50"));
+        assert!(text.contains("fresh_post_jobs_new_total{search=\"rust \\\"dev\\\" @ Berlin\"} 5"));
+        assert!(text.contains("fresh_post_jobs_today{search=\"rust \\\"dev\\\" @ Berlin\"} 8"));
+        assert!(text.contains("fresh_post_parse_path_total{path=\"html\"} 1"));
+        assert!(text.contains("fresh_post_seen_jobs 42"));
+        assert!(text.contains("fresh_post_notifications_total{outcome=\"dropped\"} 0"));
+        assert!(text.contains("fresh_post_notifications_total{outcome=\"failed\"} 0"));
+    }
+}
@@ -0,0 +1,190 @@
+use anyhow::{Context, Result};
+use toml::{Table, Value};
+
+/// `version` written by this build. Files without one are version 1.
+pub const CURRENT_VERSION: u32 = 3;
+
+/// Upgrades a config table from version `from` to `from + 1`
+struct Step {
+    from: u32,
+    description: &'static str,
+    apply: fn(&mut Table),
+}
+
+/// In order; each step upgrades by one version
+const STEPS: &[Step] = &[
+    Step {
+        from: 1,
+        description: "drop settings that only restated old defaults (server.web_dir = \"web\", search.salary_min = 0)",
+        apply: v1_to_v2,
+    },
+    Step {
+        from: 2,
+        description: "replace search.remote with the search.workplace list",
+        apply: v2_to_v3,
+    },
+];
+
+/// `web_dir` used to default to `"web"` and was written out by every save, which
+/// would keep serving the UI from disk now that it is built in; `salary_min = 0`
+/// was the old way to turn the salary filter off.
+fn v1_to_v2(table: &mut Table) {
+    if let Some(server) = table.get_mut("server").and_then(Value::as_table_mut) {
+        if server.get("web_dir").and_then(Value::as_str) == Some("web") {
+            server.remove("web_dir");
+        }
+    }
+    if let Some(search) = table.get_mut("search").and_then(Value::as_table_mut) {
+        if search.get("salary_min").and_then(Value::as_integer) == Some(0) {
+            search.remove("salary_min");
+        }
+    }
+}
+
+/// `remote = true` becomes `workplace = ["remote"]`; `false` meant no filter
+fn v2_to_v3(table: &mut Table) {
+    let Some(search) = table.get_mut("search").and_then(Value::as_table_mut) else {
+        return;
+    };
+    let remote = search.remove("remote").and_then(|remote| remote.as_bool());
+    if remote == Some(true) && !search.contains_key("workplace") {
+        search.insert("workplace".to_string(), Value::Array(vec![Value::String("remote".to_string())]));
+    }
+}
+
+/// A config table brought up to `CURRENT_VERSION`
+#[derive(Debug, Clone)]
+pub struct Migrated {
+    pub table: Table,
+    /// Version the file was written as
+    pub from_version: u32,
+    /// What each applied step did, oldest first
+    pub applied: Vec<&'static str>,
+}
+
+impl Migrated {
+    pub fn is_upgrade(&self) -> bool {
+        self.from_version != CURRENT_VERSION
+    }
+}
+
+/// Parse `config.toml` contents and run the steps between its version and this build's
+pub fn upgrade(content: &str) -> Result<Migrated> {
+    let mut table: Table = toml::from_str(content).context("Failed to parse config file")?;
+    let from_version = match table.get("version") {
+        None => 1,
+        Some(value) => value
+            .as_integer()
+            .and_then(|version| u32::try_from(version).ok())
+            .filter(|version| *version >= 1)
+            .context("Config version must be a positive whole number")?,
+    };
+    if from_version > CURRENT_VERSION {
+        anyhow::bail!(
+            "Config is version {}, newer than this build understands ({}); upgrade fresh-post",
+            from_version,
+            CURRENT_VERSION
+        );
+    }
+
+    let mut applied = Vec::new();
+    for step in STEPS.iter().filter(|step| step.from >= from_version) {
+        (step.apply)(&mut table);
+        applied.push(step.description);
+    }
+    table.insert("version".to_string(), Value::Integer(CURRENT_VERSION.into()));
+
+    Ok(Migrated {
+        table,
+        from_version,
+        applied,
+    })
+}
+
+/// `content` rewritten as `CURRENT_VERSION`. When the steps only had to change the
+/// version, just that line is added or replaced so comments and layout survive.
+pub fn rewrite(content: &str, migrated: &Migrated) -> Result<String> {
+    let mut original: Table = toml::from_str(content).context("Failed to parse config file")?;
+    let had_version = original.remove("version").is_some();
+    let mut unchanged = migrated.table.clone();
+    unchanged.remove("version");
+    if unchanged == original {
+        if !had_version {
+            return Ok(format!("version = {}\n\n{}", CURRENT_VERSION, content));
+        }
+        let line = regex::Regex::new(r"(?m)^[ \t]*version[ \t]*=[ \t]*\d+").expect("valid regex");
+        if line.is_match(content) {
+            return Ok(line.replace(content, format!("version = {}", CURRENT_VERSION).as_str()).into_owned());
+        }
+    }
+    toml::to_string_pretty(&migrated.table).context("Failed to serialize config")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::Config;
+
+    #[test]
+    fn test_steps_cover_every_version() {
+        let froms: Vec<u32> = STEPS.iter().map(|step| step.from).collect();
+        let expected: Vec<u32> = (1..CURRENT_VERSION).collect();
+        assert_eq!(froms, expected);
+    }
+
+    #[test]
+    fn test_upgrade_v1() {
+        let v1 = "[search]\nkeywords = \"rust\"\nlocation = \"Berlin\"\nsalary_min = 0\n\n[server]\nweb_dir = \"web\"\n";
+        let migrated = upgrade(v1).unwrap();
+        assert_eq!(migrated.from_version, 1);
+        assert!(migrated.is_upgrade());
+        assert_eq!(migrated.applied.len(), 2);
+
+        let config: Config = Value::Table(migrated.table.clone()).try_into().unwrap();
+        assert_eq!(config.version, CURRENT_VERSION);
+        assert!(config.server.web_dir.is_none());
+        assert_eq!(config.search.keywords, "rust");
+
+        let rewritten = rewrite(v1, &migrated).unwrap();
+        assert!(!rewritten.contains("web_dir"));
+        assert!(upgrade(&rewritten).unwrap().applied.is_empty());
+    }
+
+    #[test]
+    fn test_rewrite_keeps_comments_when_only_the_version_is_new() {
+        let v1 = "# my searches\n[search]\nkeywords = \"rust\"\n\n[server]\nweb_dir = \"ui\"\n";
+        let rewritten = rewrite(v1, &upgrade(v1).unwrap()).unwrap();
+        assert!(rewritten.starts_with(&format!("version = {}\n", CURRENT_VERSION)));
+        assert!(rewritten.contains("# my searches"));
+        assert!(rewritten.contains("web_dir = \"ui\""));
+    }
+
+    #[test]
+    fn test_upgrade_v2_remote_becomes_workplace() {
+        let v2 = "# mine\nversion = 2\n\n[search]\nkeywords = \"rust\"\nremote = true\n";
+        let migrated = upgrade(v2).unwrap();
+        assert_eq!(migrated.applied.len(), 1);
+        let config = Config::from_table(migrated.table.clone()).unwrap();
+        assert_eq!(config.search.workplace, vec![crate::facets::Workplace::Remote]);
+
+        let rewritten = rewrite(v2, &migrated).unwrap();
+        assert!(!rewritten.contains("remote = true"));
+        assert!(upgrade(&rewritten).unwrap().applied.is_empty());
+
+        let off = "# mine\nversion = 2\n\n[search]\nkeywords = \"rust\"\n";
+        let rewritten = rewrite(off, &upgrade(off).unwrap()).unwrap();
+        assert!(rewritten.starts_with(&format!("# mine\nversion = {}\n", CURRENT_VERSION)));
+    }
+
+    #[test]
+    fn test_current_and_future_versions() {
+        let current = format!("version = {}\n[search]\nkeywords = \"rust\"\n", CURRENT_VERSION);
+        let migrated = upgrade(&current).unwrap();
+        assert!(!migrated.is_upgrade());
+        assert!(migrated.applied.is_empty());
+
+        let err = upgrade(&format!("version = {}\n", CURRENT_VERSION + 1)).unwrap_err();
+        assert!(err.to_string().contains("newer"));
+        assert!(upgrade("version = \"two\"\n").is_err());
+    }
+}
@@ -0,0 +1,114 @@
+use crate::metrics::{Metrics, NotificationOutcome};
+use crate::session::SessionReport;
+use serde::Serialize;
+use std::sync::{Mutex, OnceLock};
+use tokio::sync::broadcast;
+
+/// Something the user should act on, pushed to open web UIs over `GET /api/events`
+#[derive(Debug, Clone, Serialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum Notification {
+    /// The LinkedIn session is about to expire, or already has
+    SessionWarning { session: SessionReport },
+}
+
+/// Process-wide notification channel
+pub struct Notifier {
+    sender: broadcast::Sender<Notification>,
+    /// Latest session warning that still applies, for clients that connect later
+    pending: Mutex<Option<Notification>>,
+}
+
+impl Notifier {
+    pub fn global() -> &'static Notifier {
+        static GLOBAL: OnceLock<Notifier> = OnceLock::new();
+        GLOBAL.get_or_init(|| Notifier {
+            sender: broadcast::channel(16).0,
+            pending: Mutex::new(None),
+        })
+    }
+
+    pub fn subscribe(&self) -> broadcast::Receiver<Notification> {
+        self.sender.subscribe()
+    }
+
+    /// The warning a newly connected client should see first, if any
+    pub fn pending(&self) -> Option<Notification> {
+        self.pending.lock().unwrap_or_else(|poisoned| poisoned.into_inner()).clone()
+    }
+
+    /// The session is fine again; stop replaying the last warning
+    pub fn clear_pending(&self) {
+        *self.pending.lock().unwrap_or_else(|poisoned| poisoned.into_inner()) = None;
+    }
+
+    /// Send to every connected client and keep it as `pending`; false when none is connected
+    pub fn send(&self, notification: Notification) -> bool {
+        *self.pending.lock().unwrap_or_else(|poisoned| poisoned.into_inner()) = Some(notification.clone());
+        let delivered = self.sender.send(notification).is_ok();
+        // Not a failure: the warning is still pending for the next client
+        let outcome = if delivered { NotificationOutcome::Delivered } else { NotificationOutcome::Dropped };
+        Metrics::global().record_notifications(outcome, 1);
+        delivered
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::session::SessionStatus;
+
+    #[test]
+    fn test_send_reaches_subscribers() {
+        let notifier = Notifier::global();
+        let mut receiver = notifier.subscribe();
+        let session = SessionReport {
+            status: SessionStatus::Expiring,
+            expires_at: None,
+            page: None,
+            checked_at: chrono::Local::now(),
+            message: "expires in 2 days".to_string(),
+        };
+        assert!(notifier.send(Notification::SessionWarning { session }));
+        let delivered: f64 = Metrics::global()
+            .render()
+            .lines()
+            .find_map(|line| line.strip_prefix("fresh_post_notifications_total{outcome=\"delivered\"} "))
+            .and_then(|count| count.parse().ok())
+            .unwrap();
+        assert!(delivered >= 1.0);
+
+        let Notification::SessionWarning { session } = receiver.try_recv().unwrap();
+        assert_eq!(session.status, SessionStatus::Expiring);
+        assert!(notifier.pending().is_some());
+        let json = serde_json::to_value(Notification::SessionWarning { session }).unwrap();
+        assert_eq!(json["type"], "session_warning");
+    }
+
+    #[test]
+    fn test_pending_warning_outlives_send_without_clients() {
+        let notifier = Notifier {
+            sender: broadcast::channel(16).0,
+            pending: Mutex::new(None),
+        };
+        let session = SessionReport {
+            status: SessionStatus::Expired,
+            expires_at: None,
+            page: None,
+            checked_at: chrono::Local::now(),
+            message: "session expired".to_string(),
+        };
+        assert!(!notifier.send(Notification::SessionWarning { session }));
+        assert!(Metrics::global().render().lines().any(|line| {
+            line.strip_prefix("fresh_post_notifications_total{outcome=\"dropped\"} ")
+                .is_some_and(|count| count != "0")
+        }));
+
+        let Some(Notification::SessionWarning { session }) = notifier.pending() else {
+            panic!("warning was not kept");
+        };
+        assert_eq!(session.status, SessionStatus::Expired);
+        notifier.clear_pending();
+        assert!(notifier.pending().is_none());
+    }
+}
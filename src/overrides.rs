@@ -0,0 +1,199 @@
+use crate::config::Config;
+use crate::migrate;
+use anyhow::{Context, Result};
+use serde::Serialize;
+use std::collections::BTreeMap;
+
+/// Prefix of env vars that set config fields: `FRESH_POST__<SECTION>__<FIELD>`,
+/// e.g. `FRESH_POST__SCHEDULE__INTERVAL_HOURS=6`
+pub const ENV_PREFIX: &str = "FRESH_POST__";
+const SEPARATOR: &str = "__";
+
+/// Where a config value came from. Later layers win: defaults, then `config.toml`, then env.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Source {
+    Default,
+    File,
+    Env,
+}
+
+/// A config field set by an env var
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct EnvOverride {
+    pub var: String,
+    /// Dotted field, e.g. `schedule.interval_hours`
+    pub field: String,
+    pub value: String,
+}
+
+/// Overrides set in this process's environment
+pub fn from_env() -> Vec<EnvOverride> {
+    from_vars(std::env::vars())
+}
+
+/// The `FRESH_POST__*` entries of `vars`, sorted by name
+pub fn from_vars(vars: impl IntoIterator<Item = (String, String)>) -> Vec<EnvOverride> {
+    let mut overrides: Vec<EnvOverride> = vars
+        .into_iter()
+        .filter_map(|(var, value)| {
+            let field = var.strip_prefix(ENV_PREFIX)?.to_lowercase().replace(SEPARATOR, ".");
+            Some(EnvOverride { var, field, value })
+        })
+        .collect();
+    overrides.sort_by(|a, b| a.var.cmp(&b.var));
+    overrides
+}
+
+/// A config put together from defaults, `config.toml` and env overrides
+#[derive(Debug, Clone)]
+pub struct Layered {
+    /// The config in effect
+    pub config: Config,
+    /// Defaults and `config.toml` only; what saving writes back to the file
+    pub file_config: Config,
+    /// Source of every field, by dotted name
+    pub sources: BTreeMap<String, Source>,
+}
+
+/// Layer `overrides` over `file` (the contents of `config.toml`, if there is one)
+pub fn layer(file: Option<&str>, overrides: &[EnvOverride]) -> Result<Layered> {
+    let file_table = match file {
+        Some(content) => migrate::upgrade(content)?.table,
+        None => toml::Table::new(),
+    };
+    let file_config = match file {
+        Some(_) => Config::from_table(file_table.clone())?,
+        None => Config::default(),
+    };
+
+    let mut config = file_config.clone();
+    for item in overrides {
+        config
+            .set_value(&item.field, &item.value)
+            .with_context(|| format!("Invalid {}", item.var))?;
+    }
+
+    let sources = fields(&config)
+        .into_iter()
+        .map(|field| {
+            let source = if overrides.iter().any(|item| item.field == field) {
+                Source::Env
+            } else if in_table(&file_table, &field) {
+                Source::File
+            } else {
+                Source::Default
+            };
+            (field, source)
+        })
+        .collect();
+
+    Ok(Layered {
+        config,
+        file_config,
+        sources,
+    })
+}
+
+/// `config` with the overridden fields put back to their `file_config` values, so
+/// env values don't get written into `config.toml`
+pub fn without_overrides(config: &Config, file_config: &Config, overrides: &[EnvOverride]) -> Result<Config> {
+    let mut root = serde_json::to_value(config).context("Failed to serialize config")?;
+    let file_root = serde_json::to_value(file_config).context("Failed to serialize config")?;
+    for item in overrides {
+        let pointer = format!("/{}", item.field.replace('.', "/"));
+        if let (Some(value), Some(file_value)) = (root.pointer_mut(&pointer), file_root.pointer(&pointer)) {
+            *value = file_value.clone();
+        }
+    }
+    serde_json::from_value(root).context("Failed to restore config fields set by env")
+}
+
+/// Dotted names of every field (`section.field`)
+fn fields(config: &Config) -> Vec<String> {
+    let Ok(serde_json::Value::Object(sections)) = serde_json::to_value(config) else {
+        return Vec::new();
+    };
+    sections
+        .iter()
+        .flat_map(|(section, fields)| {
+            fields
+                .as_object()
+                .into_iter()
+                .flat_map(|fields| fields.keys())
+                .map(move |field| format!("{}.{}", section, field))
+        })
+        .collect()
+}
+
+fn in_table(table: &toml::Table, field: &str) -> bool {
+    let Some((section, field)) = field.split_once('.') else {
+        return false;
+    };
+    table
+        .get(section)
+        .and_then(toml::Value::as_table)
+        .is_some_and(|section| section.contains_key(field))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const FILE: &str = "[search]\nkeywords = \"rust\"\nlocation = \"Berlin\"\n\n[schedule]\ninterval_hours = 2\n";
+
+    fn vars(pairs: &[(&str, &str)]) -> Vec<EnvOverride> {
+        from_vars(pairs.iter().map(|(var, value)| (var.to_string(), value.to_string())))
+    }
+
+    #[test]
+    fn test_from_vars_maps_names_to_fields() {
+        let overrides = vars(&[
+            ("FRESH_POST__SCHEDULE__INTERVAL_HOURS", "6"),
+            ("FRESH_POST_API_TOKEN", "not a field"),
+            ("DATA_DIR", "/data"),
+        ]);
+        assert_eq!(overrides.len(), 1);
+        assert_eq!(overrides[0].field, "schedule.interval_hours");
+    }
+
+    #[test]
+    fn test_layer_env_over_file_over_defaults() {
+        let overrides = vars(&[
+            ("FRESH_POST__SEARCH__KEYWORDS", "embedded rust"),
+            ("FRESH_POST__PROXY__URLS", r#"["http://proxy:3128"]"#),
+        ]);
+        let layered = layer(Some(FILE), &overrides).unwrap();
+
+        assert_eq!(layered.config.search.keywords, "embedded rust");
+        assert_eq!(layered.config.proxy.urls, vec!["http://proxy:3128"]);
+        assert_eq!(layered.config.schedule.interval_hours, 2);
+        assert_eq!(layered.file_config.search.keywords, "rust");
+
+        assert_eq!(layered.sources["search.keywords"], Source::Env);
+        assert_eq!(layered.sources["search.location"], Source::File);
+        assert_eq!(layered.sources["schedule.interval_hours"], Source::File);
+        assert_eq!(layered.sources["search.easy_apply"], Source::Default);
+        assert_eq!(layered.sources["server.tls_cert"], Source::Default);
+    }
+
+    #[test]
+    fn test_layer_rejects_bad_overrides() {
+        let err = layer(Some(FILE), &vars(&[("FRESH_POST__SEARCH__KEYWORD", "typo")])).unwrap_err();
+        assert!(format!("{:#}", err).contains("FRESH_POST__SEARCH__KEYWORD"));
+        assert!(layer(Some(FILE), &vars(&[("FRESH_POST__SCHEDULE__INTERVAL_HOURS", "often")])).is_err());
+        assert!(layer(None, &vars(&[("FRESH_POST__SCHEDULE", "6")])).is_err());
+    }
+
+    #[test]
+    fn test_without_overrides_keeps_env_values_out_of_the_file() {
+        let overrides = vars(&[("FRESH_POST__SEARCH__KEYWORDS", "embedded rust")]);
+        let layered = layer(Some(FILE), &overrides).unwrap();
+        let mut edited = layered.config.clone();
+        edited.search.easy_apply = true;
+
+        let saved = without_overrides(&edited, &layered.file_config, &overrides).unwrap();
+        assert_eq!(saved.search.keywords, "rust");
+        assert!(saved.search.easy_apply);
+    }
+}
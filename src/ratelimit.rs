@@ -0,0 +1,284 @@
+use crate::config::RateLimitConfig;
+use rand::Rng;
+use serde::Serialize;
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex, OnceLock};
+use std::time::{Duration, Instant};
+use tracing::info;
+use url::Url;
+
+/// Token bucket for one host. Tokens may go negative: each request reserves a
+/// token up front and waits until the bucket has refilled past it, so concurrent
+/// callers queue up instead of all waking at once.
+#[derive(Debug, Clone)]
+struct Bucket {
+    tokens: f64,
+    updated: Instant,
+    total_requests: u64,
+    total_wait: Duration,
+}
+
+impl Bucket {
+    fn new(capacity: f64, now: Instant) -> Self {
+        Self {
+            tokens: capacity,
+            updated: now,
+            total_requests: 0,
+            total_wait: Duration::ZERO,
+        }
+    }
+
+    fn refill(&mut self, now: Instant, per_second: f64, capacity: f64) {
+        let elapsed = now.saturating_duration_since(self.updated).as_secs_f64();
+        self.tokens = (self.tokens + elapsed * per_second).min(capacity);
+        self.updated = now;
+    }
+
+    /// Take a token and return how long to wait before using it
+    fn reserve(&mut self, now: Instant, per_second: f64, capacity: f64) -> Duration {
+        self.refill(now, per_second, capacity);
+        self.tokens -= 1.0;
+        self.total_requests += 1;
+        if self.tokens >= 0.0 {
+            Duration::ZERO
+        } else {
+            Duration::from_secs_f64(-self.tokens / per_second)
+        }
+    }
+}
+
+/// Remaining request budget for one host, as reported by the API
+#[derive(Debug, Clone, Serialize)]
+pub struct HostBudget {
+    pub host: String,
+    /// Whole requests that can go out right now without waiting
+    pub available: u32,
+    pub burst: u32,
+    pub requests_per_minute: f64,
+    /// Time until the next token is added, 0 when the bucket is full
+    pub next_token_ms: u64,
+    pub total_requests: u64,
+    /// Time requests spent waiting for the limiter, jitter included
+    pub total_wait_ms: u64,
+}
+
+/// Limiter state reported by `/api/rate-limit`
+#[derive(Debug, Clone, Serialize)]
+pub struct RateLimitStatus {
+    pub config: RateLimitConfig,
+    pub hosts: Vec<HostBudget>,
+}
+
+/// Token-bucket limiter shared by every outbound request, one bucket per host,
+/// with a random human-like pause added to each request
+#[derive(Debug, Default)]
+pub struct RateLimiter {
+    config: Mutex<RateLimitConfig>,
+    buckets: Mutex<HashMap<String, Bucket>>,
+}
+
+impl RateLimiter {
+    pub fn new(config: RateLimitConfig) -> Self {
+        Self {
+            config: Mutex::new(config),
+            buckets: Mutex::new(HashMap::new()),
+        }
+    }
+
+    /// The process-wide limiter, so scheduled and manual runs share one budget
+    pub fn shared() -> Arc<RateLimiter> {
+        static SHARED: OnceLock<Arc<RateLimiter>> = OnceLock::new();
+        SHARED.get_or_init(|| Arc::new(RateLimiter::default())).clone()
+    }
+
+    /// Apply new limits; existing buckets keep their tokens (capped at the new burst)
+    pub fn configure(&self, config: &RateLimitConfig) {
+        *self.config.lock().unwrap_or_else(|poisoned| poisoned.into_inner()) = config.clone();
+    }
+
+    fn config(&self) -> RateLimitConfig {
+        self.config.lock().unwrap_or_else(|poisoned| poisoned.into_inner()).clone()
+    }
+
+    /// How long a request to `host` has to wait, reserving its slot
+    fn reserve(&self, host: &str, now: Instant) -> Duration {
+        let config = self.config();
+        let wait = if config.requests_per_minute > 0.0 {
+            let per_second = config.requests_per_minute / 60.0;
+            let capacity = config.burst.max(1) as f64;
+            let mut buckets = self.buckets.lock().unwrap_or_else(|poisoned| poisoned.into_inner());
+            buckets
+                .entry(host.to_string())
+                .or_insert_with(|| Bucket::new(capacity, now))
+                .reserve(now, per_second, capacity)
+        } else {
+            Duration::ZERO
+        };
+
+        wait + jitter(config.min_delay_ms, config.max_delay_ms)
+    }
+
+    fn record_wait(&self, host: &str, wait: Duration) {
+        let mut buckets = self.buckets.lock().unwrap_or_else(|poisoned| poisoned.into_inner());
+        if let Some(bucket) = buckets.get_mut(host) {
+            bucket.total_wait += wait;
+        }
+    }
+
+    /// Wait until a request to `url` fits the budget of its host. Returns the time waited.
+    pub async fn acquire(&self, url: &str) -> Duration {
+        let host = host_of(url);
+        let wait = self.reserve(&host, Instant::now());
+        self.record_wait(&host, wait);
+
+        if !wait.is_zero() {
+            info!(%host, wait_ms = wait.as_millis() as u64, "waiting for rate limiter");
+            tokio::time::sleep(wait).await;
+        }
+        wait
+    }
+
+    pub fn status(&self) -> RateLimitStatus {
+        self.status_at(Instant::now())
+    }
+
+    fn status_at(&self, now: Instant) -> RateLimitStatus {
+        let config = self.config();
+        let per_second = config.requests_per_minute / 60.0;
+        let capacity = config.burst.max(1) as f64;
+
+        let buckets = self.buckets.lock().unwrap_or_else(|poisoned| poisoned.into_inner());
+        let mut hosts: Vec<HostBudget> = buckets
+            .iter()
+            .map(|(host, bucket)| {
+                let mut bucket = bucket.clone();
+                if per_second > 0.0 {
+                    bucket.refill(now, per_second, capacity);
+                } else {
+                    bucket.tokens = capacity;
+                }
+
+                let next_token_ms = if bucket.tokens >= capacity || per_second <= 0.0 {
+                    0
+                } else {
+                    let missing = 1.0 - bucket.tokens.fract().rem_euclid(1.0);
+                    (missing / per_second * 1000.0).ceil() as u64
+                };
+
+                HostBudget {
+                    host: host.clone(),
+                    available: bucket.tokens.max(0.0).floor() as u32,
+                    burst: config.burst.max(1),
+                    requests_per_minute: config.requests_per_minute,
+                    next_token_ms,
+                    total_requests: bucket.total_requests,
+                    total_wait_ms: bucket.total_wait.as_millis() as u64,
+                }
+            })
+            .collect();
+        hosts.sort_by(|a, b| a.host.cmp(&b.host));
+
+        RateLimitStatus { config, hosts }
+    }
+}
+
+/// Host (and port, if any) a request goes to; the whole string when it isn't a URL
+fn host_of(url: &str) -> String {
+    Url::parse(url)
+        .ok()
+        .and_then(|parsed| {
+            let host = parsed.host_str()?.to_string();
+            Some(match parsed.port() {
+                Some(port) => format!("{}:{}", host, port),
+                None => host,
+            })
+        })
+        .unwrap_or_else(|| url.to_string())
+}
+
+/// Random pause between `min_ms` and `max_ms`
+fn jitter(min_ms: u64, max_ms: u64) -> Duration {
+    if max_ms <= min_ms {
+        return Duration::from_millis(min_ms);
+    }
+    Duration::from_millis(rand::thread_rng().gen_range(min_ms..=max_ms))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn limiter(requests_per_minute: f64, burst: u32) -> RateLimiter {
+        RateLimiter::new(RateLimitConfig {
+            requests_per_minute,
+            burst,
+            min_delay_ms: 0,
+            max_delay_ms: 0,
+        })
+    }
+
+    #[test]
+    fn test_burst_then_steady_rate() {
+        let limiter = limiter(60.0, 2);
+        let now = Instant::now();
+
+        assert_eq!(limiter.reserve("a", now), Duration::ZERO);
+        assert_eq!(limiter.reserve("a", now), Duration::ZERO);
+        assert_eq!(limiter.reserve("a", now), Duration::from_secs(1));
+        assert_eq!(limiter.reserve("a", now), Duration::from_secs(2));
+
+        // After the queue drains and the bucket refills, a burst is possible again
+        let later = now + Duration::from_secs(10);
+        assert_eq!(limiter.reserve("a", later), Duration::ZERO);
+    }
+
+    #[test]
+    fn test_hosts_have_separate_buckets() {
+        let limiter = limiter(60.0, 1);
+        let now = Instant::now();
+
+        assert_eq!(limiter.reserve("a", now), Duration::ZERO);
+        assert_eq!(limiter.reserve("b", now), Duration::ZERO);
+        assert_eq!(limiter.reserve("a", now), Duration::from_secs(1));
+    }
+
+    #[test]
+    fn test_zero_rate_disables_limit() {
+        let limiter = limiter(0.0, 1);
+        let now = Instant::now();
+        for _ in 0..5 {
+            assert_eq!(limiter.reserve("a", now), Duration::ZERO);
+        }
+    }
+
+    #[test]
+    fn test_jitter_range() {
+        for _ in 0..50 {
+            let delay = jitter(100, 200);
+            assert!(delay >= Duration::from_millis(100) && delay <= Duration::from_millis(200));
+        }
+        assert_eq!(jitter(300, 300), Duration::from_millis(300));
+    }
+
+    #[test]
+    fn test_status_reports_budget() {
+        let limiter = limiter(60.0, 3);
+        let now = Instant::now();
+        limiter.reserve("www.linkedin.com", now);
+        limiter.reserve("www.linkedin.com", now);
+
+        let status = limiter.status_at(now);
+        assert_eq!(status.hosts.len(), 1);
+        let budget = &status.hosts[0];
+        assert_eq!(budget.available, 1);
+        assert_eq!(budget.burst, 3);
+        assert_eq!(budget.next_token_ms, 1000);
+        assert_eq!(budget.total_requests, 2);
+    }
+
+    #[test]
+    fn test_host_of() {
+        assert_eq!(host_of("https://www.linkedin.com/jobs/search?keywords=rust"), "www.linkedin.com");
+        assert_eq!(host_of("http://127.0.0.1:8080/jobs"), "127.0.0.1:8080");
+    }
+}
@@ -0,0 +1,194 @@
+use crate::config::ScheduleConfig;
+use crate::session::{self, SessionStatus};
+use crate::storage;
+use chrono::{DateTime, Local};
+use serde::Serialize;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ReadyStatus {
+    Ok,
+    Degraded,
+}
+
+/// Outcome of one readiness check
+#[derive(Debug, Clone, Serialize)]
+pub struct Check {
+    pub name: &'static str,
+    pub ok: bool,
+    pub message: String,
+}
+
+impl Check {
+    fn pass(name: &'static str, message: impl Into<String>) -> Self {
+        Self { name, ok: true, message: message.into() }
+    }
+
+    fn fail(name: &'static str, message: impl Into<String>) -> Self {
+        Self { name, ok: false, message: message.into() }
+    }
+}
+
+/// Body of `/readyz`
+#[derive(Debug, Clone, Serialize)]
+pub struct ReadinessReport {
+    pub status: ReadyStatus,
+    pub last_success: Option<DateTime<Local>>,
+    /// Age after which the last successful scrape counts as stale
+    pub stale_after_seconds: i64,
+    pub checks: Vec<Check>,
+    pub checked_at: DateTime<Local>,
+}
+
+impl ReadinessReport {
+    pub fn is_ready(&self) -> bool {
+        self.status == ReadyStatus::Ok
+    }
+}
+
+/// What the readiness checks look at, gathered by [`check`]
+#[derive(Debug, Clone)]
+pub struct Inputs {
+    pub last_success: Option<DateTime<Local>>,
+    /// When the process started; before the first successful run, staleness counts from here
+    pub started_at: DateTime<Local>,
+    pub interval_hours: u64,
+    pub stale_after_intervals: u32,
+    pub storage: Result<(), String>,
+    pub session: Result<SessionStatus, String>,
+}
+
+/// Evaluate readiness at `now`
+pub fn assess(inputs: &Inputs, now: DateTime<Local>) -> ReadinessReport {
+    let stale_after = chrono::Duration::hours(
+        inputs.interval_hours.max(1) as i64 * inputs.stale_after_intervals.max(1) as i64,
+    );
+
+    let freshness = match inputs.last_success {
+        Some(last) if now - last > stale_after => Check::fail(
+            "last_success",
+            format!("Last successful scrape was {} minutes ago", (now - last).num_minutes()),
+        ),
+        Some(last) => Check::pass(
+            "last_success",
+            format!("Last successful scrape was {} minutes ago", (now - last).num_minutes()),
+        ),
+        None if now - inputs.started_at > stale_after => {
+            Check::fail("last_success", "No successful scrape since startup")
+        }
+        None => Check::pass("last_success", "No successful scrape yet; still within the first interval"),
+    };
+
+    let storage = match &inputs.storage {
+        Ok(()) => Check::pass("storage", "Data directory is writable"),
+        Err(err) => Check::fail("storage", format!("Data directory is not writable: {}", err)),
+    };
+
+    let session = match &inputs.session {
+        Ok(SessionStatus::Expired) => Check::fail("session", "LinkedIn session expired; export fresh cookies"),
+        Ok(status) => Check::pass("session", format!("Session {:?}", status)),
+        Err(err) => Check::fail("session", format!("Session check failed: {}", err)),
+    };
+
+    let checks = vec![freshness, storage, session];
+    let status = if checks.iter().all(|check| check.ok) {
+        ReadyStatus::Ok
+    } else {
+        ReadyStatus::Degraded
+    };
+
+    ReadinessReport {
+        status,
+        last_success: inputs.last_success,
+        stale_after_seconds: stale_after.num_seconds(),
+        checks,
+        checked_at: now,
+    }
+}
+
+/// Gather inputs from the data directory and evaluate readiness. `last_run` is the
+/// last success this process saw; the latest snapshot covers runs before a restart.
+pub fn check(
+    paths: &storage::Paths,
+    schedule: &ScheduleConfig,
+    last_run: Option<DateTime<Local>>,
+    started_at: DateTime<Local>,
+) -> ReadinessReport {
+    let snapshot_time = storage::load_latest_jobs(paths)
+        .ok()
+        .and_then(|snapshot| snapshot.updated_at);
+
+    let inputs = Inputs {
+        last_success: last_run.max(snapshot_time),
+        started_at,
+        interval_hours: schedule.interval_hours,
+        stale_after_intervals: schedule.stale_after_intervals,
+        storage: storage::check_writable(paths).map_err(|err| format!("{:#}", err)),
+        session: session::check_session(paths)
+            .map(|report| report.status)
+            .map_err(|err| format!("{:#}", err)),
+    };
+
+    assess(&inputs, Local::now())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::Duration;
+
+    fn inputs(last_success: Option<DateTime<Local>>) -> Inputs {
+        Inputs {
+            last_success,
+            started_at: Local::now() - Duration::hours(1),
+            interval_hours: 4,
+            stale_after_intervals: 3,
+            storage: Ok(()),
+            session: Ok(SessionStatus::Valid),
+        }
+    }
+
+    #[test]
+    fn test_ready_with_recent_success() {
+        let now = Local::now();
+        let report = assess(&inputs(Some(now - Duration::hours(5))), now);
+        assert!(report.is_ready());
+        assert_eq!(report.stale_after_seconds, 12 * 3600);
+    }
+
+    #[test]
+    fn test_stale_success_degrades() {
+        let now = Local::now();
+        let report = assess(&inputs(Some(now - Duration::hours(13))), now);
+        assert_eq!(report.status, ReadyStatus::Degraded);
+        assert!(!report.checks[0].ok);
+    }
+
+    #[test]
+    fn test_grace_period_before_first_run() {
+        let now = Local::now();
+        assert!(assess(&inputs(None), now).is_ready());
+
+        let mut long_running = inputs(None);
+        long_running.started_at = now - Duration::hours(24);
+        assert!(!assess(&long_running, now).is_ready());
+    }
+
+    #[test]
+    fn test_storage_and_session_failures() {
+        let now = Local::now();
+        let mut unwritable = inputs(Some(now));
+        unwritable.storage = Err("read-only file system".to_string());
+        assert!(!assess(&unwritable, now).is_ready());
+
+        let mut expired = inputs(Some(now));
+        expired.session = Ok(SessionStatus::Expired);
+        let report = assess(&expired, now);
+        assert!(!report.is_ready());
+        assert_eq!(report.checks.iter().filter(|check| !check.ok).count(), 1);
+
+        let mut guest = inputs(Some(now));
+        guest.session = Ok(SessionStatus::Absent);
+        assert!(assess(&guest, now).is_ready());
+    }
+}
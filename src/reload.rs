@@ -0,0 +1,381 @@
+use crate::audit::{self, AuditEntry};
+use crate::config::{Config, FieldError};
+use crate::etag;
+use crate::overrides::{self, EnvOverride, Layered, Source};
+use crate::storage::{self, Paths};
+use anyhow::{Context, Result};
+use chrono::{DateTime, Local};
+use serde::Serialize;
+use std::collections::BTreeMap;
+use std::path::Path;
+use std::sync::{Arc, Mutex, MutexGuard};
+use std::time::Duration;
+use tokio::sync::{broadcast, watch};
+use tracing::{info, warn};
+
+/// How often `config.toml` is checked for edits. Polled rather than watched with
+/// inotify, which misses edits made from the host side of many Docker volume mounts.
+pub const POLL_INTERVAL: Duration = Duration::from_secs(2);
+
+/// Audit actor and event source for edits picked up from the file
+pub const FILE_ACTOR: &str = "file";
+pub const FILE_SOURCE: &str = "config.toml";
+
+/// Settings read once at startup; changing them needs a restart
+const RESTART_SECTIONS: &[&str] = &["auth.", "server."];
+
+/// Events buffered per subscriber before slow ones start missing some
+const EVENT_CAPACITY: usize = 16;
+
+/// Pushed to `/api/events` subscribers
+#[derive(Debug, Clone, Serialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum ConfigEvent {
+    /// A new config is in effect
+    ConfigChanged {
+        etag: String,
+        /// `config.toml` for file edits, else the API call that made the change
+        source: String,
+        fields: Vec<String>,
+        at: DateTime<Local>,
+    },
+    /// An edit to `config.toml` was rejected; the previous config keeps running
+    ConfigInvalid { error: ReloadError },
+}
+
+/// Why the last edit to `config.toml` wasn't applied
+#[derive(Debug, Clone, Serialize)]
+pub struct ReloadError {
+    pub message: String,
+    /// Per-field problems when the file parsed but didn't validate
+    pub errors: Vec<FieldError>,
+    pub at: DateTime<Local>,
+}
+
+impl ReloadError {
+    fn new(message: String, errors: Vec<FieldError>) -> Self {
+        Self {
+            message,
+            errors,
+            at: Local::now(),
+        }
+    }
+}
+
+/// The config in effect and whether `config.toml` holds an edit that wasn't applied
+#[derive(Debug, Clone, Serialize)]
+pub struct ReloadStatus {
+    pub etag: String,
+    pub loaded_at: DateTime<Local>,
+    pub error: Option<ReloadError>,
+}
+
+struct FileState {
+    /// Hash of the file contents last seen, to spot edits
+    fingerprint: Option<String>,
+    /// The config without env overrides, as saved to the file
+    file_config: Config,
+    sources: BTreeMap<String, Source>,
+    loaded_at: DateTime<Local>,
+    error: Option<ReloadError>,
+}
+
+/// The running config, swapped as a whole when `config.toml` is edited or the API
+/// saves a change. Readers take a snapshot with `current` or follow changes with `subscribe`.
+pub struct LiveConfig {
+    paths: Paths,
+    /// Read once at startup; they win over the file
+    overrides: Vec<EnvOverride>,
+    current: watch::Sender<Arc<Config>>,
+    events: broadcast::Sender<ConfigEvent>,
+    /// Held while reading or writing the file, so a save isn't mistaken for an edit
+    file: Mutex<FileState>,
+}
+
+impl LiveConfig {
+    /// Load and validate `config.toml` (or the defaults when there is none) with
+    /// the `FRESH_POST__*` env overrides on top
+    pub fn load(paths: &Paths) -> Result<Self> {
+        Self::load_with(paths, overrides::from_env())
+    }
+
+    pub fn load_with(paths: &Paths, overrides: Vec<EnvOverride>) -> Result<Self> {
+        let content = read_file(&paths.config)?;
+        let layered = overrides::layer(content.as_deref(), &overrides)?;
+        layered.config.validate()?;
+
+        Ok(Self {
+            paths: paths.clone(),
+            overrides,
+            current: watch::Sender::new(Arc::new(layered.config)),
+            events: broadcast::channel(EVENT_CAPACITY).0,
+            file: Mutex::new(FileState {
+                fingerprint: content.as_deref().map(fingerprint),
+                file_config: layered.file_config,
+                sources: layered.sources,
+                loaded_at: Local::now(),
+                error: None,
+            }),
+        })
+    }
+
+    pub fn current(&self) -> Arc<Config> {
+        self.current.borrow().clone()
+    }
+
+    /// Wakes on every config swap
+    pub fn subscribe(&self) -> watch::Receiver<Arc<Config>> {
+        self.current.subscribe()
+    }
+
+    pub fn events(&self) -> broadcast::Receiver<ConfigEvent> {
+        self.events.subscribe()
+    }
+
+    /// Where each field's value came from
+    pub fn sources(&self) -> BTreeMap<String, Source> {
+        self.file().sources.clone()
+    }
+
+    /// Fields set by env vars, which only a restart can change
+    pub fn overrides(&self) -> &[EnvOverride] {
+        &self.overrides
+    }
+
+    pub fn status(&self) -> ReloadStatus {
+        let file = self.file();
+        ReloadStatus {
+            etag: self.current().etag(),
+            loaded_at: file.loaded_at,
+            error: file.error.clone(),
+        }
+    }
+
+    /// The file state, even if a panic left the lock poisoned
+    fn file(&self) -> MutexGuard<'_, FileState> {
+        self.file.lock().unwrap_or_else(|poisoned| poisoned.into_inner())
+    }
+
+    /// Write an already validated `config` to `config.toml` and put it in effect.
+    /// Fields set by env keep their file values in the file.
+    pub fn save(&self, config: Config, source: &str, fields: Vec<String>) -> Result<()> {
+        let mut file = self.file();
+        let file_config = overrides::without_overrides(&config, &file.file_config, &self.overrides)?;
+        storage::save_config(&self.paths, &file_config)?;
+        let content = read_file(&self.paths.config)?;
+        file.fingerprint = content.as_deref().map(fingerprint);
+        file.sources = overrides::layer(content.as_deref(), &self.overrides)?.sources;
+        file.file_config = file_config;
+        // Whatever broken edit the file held has been overwritten
+        file.error = None;
+        self.apply(&mut file, config, source, fields);
+        Ok(())
+    }
+
+    /// Pick up an edit to `config.toml`, if there is one. A valid edit takes effect
+    /// and is audited; an invalid one is kept in `status` and the previous config stays.
+    pub fn reload_if_changed(&self) -> Option<ConfigEvent> {
+        let mut file = self.file();
+        let content = match read_file(&self.paths.config) {
+            Ok(Some(content)) => content,
+            // Removed, perhaps mid-save by an editor: keep running what we have
+            Ok(None) => return None,
+            Err(err) => {
+                warn!(error = %format!("{:#}", err), "failed to check config.toml for changes");
+                return None;
+            }
+        };
+        let new_fingerprint = fingerprint(&content);
+        if file.fingerprint.as_deref() == Some(new_fingerprint.as_str()) {
+            return None;
+        }
+        file.fingerprint = Some(new_fingerprint);
+
+        let layered = match parse(&content, &self.overrides) {
+            Ok(layered) => layered,
+            Err(error) => {
+                warn!(error = %error.message, "ignoring invalid edit to config.toml; the previous config stays in effect");
+                file.error = Some(error.clone());
+                let event = ConfigEvent::ConfigInvalid { error };
+                let _ = self.events.send(event.clone());
+                return Some(event);
+            }
+        };
+
+        let recovered = file.error.take().is_some();
+        let changes = audit::diff(&self.current(), &layered.config);
+        file.file_config = layered.file_config;
+        file.sources = layered.sources;
+        // Comments or formatting only; still announce a fix so clients drop the error
+        if changes.is_empty() && !recovered {
+            return None;
+        }
+        let fields: Vec<String> = changes.iter().map(|change| change.field.clone()).collect();
+        info!(?fields, "config reloaded from config.toml");
+        if !changes.is_empty() {
+            let entry = AuditEntry::new(FILE_ACTOR, FILE_SOURCE, changes);
+            if let Err(err) = storage::append_config_audit(&self.paths, &entry) {
+                tracing::error!(error = %format!("{:#}", err), "failed to write config audit log");
+            }
+        }
+        Some(self.apply(&mut file, layered.config, FILE_SOURCE, fields))
+    }
+
+    fn apply(&self, file: &mut FileState, config: Config, source: &str, fields: Vec<String>) -> ConfigEvent {
+        let restart: Vec<&String> = fields
+            .iter()
+            .filter(|field| RESTART_SECTIONS.iter().any(|section| field.starts_with(section)))
+            .collect();
+        if !restart.is_empty() {
+            warn!(fields = ?restart, "these settings take effect after a restart");
+        }
+
+        let event = ConfigEvent::ConfigChanged {
+            etag: config.etag(),
+            source: source.to_string(),
+            fields,
+            at: Local::now(),
+        };
+        file.loaded_at = Local::now();
+        self.current.send_replace(Arc::new(config));
+        // No subscribers is fine
+        let _ = self.events.send(event.clone());
+        event
+    }
+
+    /// Check `config.toml` every `POLL_INTERVAL` for the life of the process
+    pub async fn watch(self: Arc<Self>) {
+        let mut interval = tokio::time::interval(POLL_INTERVAL);
+        interval.set_missed_tick_behavior(tokio::time::MissedTickBehavior::Delay);
+        loop {
+            interval.tick().await;
+            // Reads the file while holding a std mutex; keep that off the runtime threads
+            let live = self.clone();
+            if let Err(err) = tokio::task::spawn_blocking(move || live.reload_if_changed()).await {
+                warn!(error = %err, "config reload check panicked");
+            }
+        }
+    }
+}
+
+fn fingerprint(content: &str) -> String {
+    etag::of(content.as_bytes())
+}
+
+fn read_file(path: &Path) -> Result<Option<String>> {
+    match std::fs::read_to_string(path) {
+        Ok(content) => Ok(Some(content)),
+        Err(err) if err.kind() == std::io::ErrorKind::NotFound => Ok(None),
+        Err(err) => Err(err).with_context(|| format!("Failed to read config file: {:?}", path)),
+    }
+}
+
+fn parse(content: &str, overrides: &[EnvOverride]) -> std::result::Result<Layered, ReloadError> {
+    let layered =
+        overrides::layer(Some(content), overrides).map_err(|err| ReloadError::new(format!("{:#}", err), Vec::new()))?;
+    layered
+        .config
+        .validate()
+        .map_err(|errors| ReloadError::new(errors.to_string(), errors.errors))?;
+    Ok(layered)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn paths() -> (tempfile::TempDir, Paths) {
+        let dir = tempfile::tempdir().unwrap();
+        let paths = Paths::in_dir(dir.path()).unwrap();
+        (dir, paths)
+    }
+
+    fn edit(paths: &Paths, change: impl FnOnce(&mut Config)) {
+        let mut config = storage::load_config_or_default(paths).unwrap();
+        change(&mut config);
+        storage::save_config(paths, &config).unwrap();
+    }
+
+    #[test]
+    fn test_reload_applies_valid_edit() {
+        let (_dir, paths) = paths();
+        edit(&paths, |_| {});
+        let live = LiveConfig::load(&paths).unwrap();
+        let changes = live.subscribe();
+        assert!(live.reload_if_changed().is_none());
+
+        edit(&paths, |config| config.schedule.interval_hours = 2);
+        match live.reload_if_changed() {
+            Some(ConfigEvent::ConfigChanged { fields, source, .. }) => {
+                assert_eq!(fields, vec!["schedule.interval_hours"]);
+                assert_eq!(source, FILE_SOURCE);
+            }
+            other => panic!("expected a change, got {:?}", other),
+        }
+        assert!(changes.has_changed().unwrap());
+        assert_eq!(live.current().schedule.interval_hours, 2);
+        assert_eq!(storage::load_config_audit(&paths).unwrap()[0].actor, FILE_ACTOR);
+        assert!(live.reload_if_changed().is_none());
+    }
+
+    #[test]
+    fn test_reload_keeps_previous_config_on_invalid_edit() {
+        let (_dir, paths) = paths();
+        edit(&paths, |_| {});
+        let original = std::fs::read_to_string(&paths.config).unwrap();
+        let live = LiveConfig::load(&paths).unwrap();
+
+        edit(&paths, |config| config.schedule.interval_hours = 0);
+        match live.reload_if_changed() {
+            Some(ConfigEvent::ConfigInvalid { error }) => {
+                assert_eq!(error.errors[0].field, "schedule.interval_hours");
+            }
+            other => panic!("expected an error, got {:?}", other),
+        }
+        assert_eq!(live.current().schedule.interval_hours, 4);
+        assert!(live.status().error.is_some());
+
+        std::fs::write(&paths.config, "search = [").unwrap();
+        assert!(matches!(live.reload_if_changed(), Some(ConfigEvent::ConfigInvalid { .. })));
+
+        // Putting the old values back clears the error
+        std::fs::write(&paths.config, original).unwrap();
+        assert!(matches!(live.reload_if_changed(), Some(ConfigEvent::ConfigChanged { .. })));
+        assert!(live.status().error.is_none());
+    }
+
+    #[test]
+    fn test_env_overrides_stay_out_of_the_file() {
+        let (_dir, paths) = paths();
+        edit(&paths, |_| {});
+        let env = overrides::from_vars([("FRESH_POST__SEARCH__KEYWORDS".to_string(), "embedded rust".to_string())]);
+        let live = LiveConfig::load_with(&paths, env).unwrap();
+        assert_eq!(live.current().search.keywords, "embedded rust");
+        assert_eq!(live.sources()["search.keywords"], Source::Env);
+
+        let mut config = (*live.current()).clone();
+        config.search.easy_apply = true;
+        live.save(config, "PATCH /api/config", vec!["search.easy_apply".to_string()]).unwrap();
+        let saved = storage::load_config_or_default(&paths).unwrap();
+        assert_eq!(saved.search.keywords, "rust developer");
+        assert!(saved.search.easy_apply);
+        assert_eq!(live.current().search.keywords, "embedded rust");
+        assert_eq!(live.sources()["search.easy_apply"], Source::File);
+    }
+
+    #[test]
+    fn test_save_is_not_reloaded_as_an_edit() {
+        let (_dir, paths) = paths();
+        let live = LiveConfig::load(&paths).unwrap();
+        let mut events = live.events();
+
+        let mut config = (*live.current()).clone();
+        config.search.easy_apply = true;
+        live.save(config, "PATCH /api/config", vec!["search.easy_apply".to_string()]).unwrap();
+
+        assert!(matches!(events.try_recv(), Ok(ConfigEvent::ConfigChanged { .. })));
+        assert!(live.current().search.easy_apply);
+        assert!(live.reload_if_changed().is_none());
+        assert!(storage::load_config_audit(&paths).unwrap().is_empty());
+    }
+}
@@ -0,0 +1,353 @@
+use crate::archive;
+use anyhow::{Context, Result};
+use axum::body::Body;
+use axum::extract::{Request, State};
+use axum::http::{header, HeaderValue, StatusCode};
+use axum::response::Response;
+use axum::Router;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex};
+use tokio::task::JoinHandle;
+use url::Url;
+
+pub const MANIFEST_FILE: &str = "recording.json";
+
+/// One recorded response
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct RecordedResponse {
+    /// Request path to match, e.g. `/jobs/search`
+    pub path: String,
+    /// Query parameters that must be present with these values (others are ignored)
+    #[serde(default)]
+    pub query: BTreeMap<String, String>,
+    #[serde(default = "default_status")]
+    pub status: u16,
+    /// `Location` header, for redirects
+    #[serde(default)]
+    pub location: Option<String>,
+    /// Response body file, relative to the recording directory
+    #[serde(default)]
+    pub body_file: Option<String>,
+    /// Inline response body, used when there is no `body_file`
+    #[serde(default)]
+    pub body: Option<String>,
+}
+
+fn default_status() -> u16 {
+    200
+}
+
+impl RecordedResponse {
+    fn matches(&self, path: &str, query: &BTreeMap<String, String>) -> bool {
+        self.path == path
+            && self
+                .query
+                .iter()
+                .all(|(key, value)| query.get(key) == Some(value))
+    }
+}
+
+/// A set of recorded responses (manifest plus body files in one directory);
+/// the entry matching the most query parameters wins. Captured by setting `ScrapeEnv::record_dir`
+/// on a live run.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct Recording {
+    pub responses: Vec<RecordedResponse>,
+}
+
+impl Recording {
+    /// Matching entry with the most query constraints (earliest on ties)
+    fn best_match(&self, path: &str, query: &BTreeMap<String, String>) -> Option<&RecordedResponse> {
+        self.responses
+            .iter()
+            .filter(|recorded| recorded.matches(path, query))
+            .fold(None, |best: Option<&RecordedResponse>, recorded| match best {
+                Some(best) if best.query.len() >= recorded.query.len() => Some(best),
+                _ => Some(recorded),
+            })
+    }
+
+    pub fn load<P: AsRef<Path>>(dir: P) -> Result<Self> {
+        let path = dir.as_ref().join(MANIFEST_FILE);
+        if !path.exists() {
+            return Ok(Self::default());
+        }
+
+        let content = std::fs::read_to_string(&path)
+            .with_context(|| format!("Failed to read recording: {:?}", path))?;
+        serde_json::from_str(&content).with_context(|| format!("Failed to parse recording: {:?}", path))
+    }
+
+    pub fn save<P: AsRef<Path>>(&self, dir: P) -> Result<()> {
+        let path = dir.as_ref().join(MANIFEST_FILE);
+        let content = serde_json::to_string_pretty(self).context("Failed to serialize recording")?;
+        std::fs::write(&path, content).with_context(|| format!("Failed to write recording: {:?}", path))
+    }
+
+    /// Append a live response to the recording in `dir`: `body` as served at
+    /// `final_url`, behind a redirect from `url` when the request was redirected.
+    /// The body is redacted like the run archive before it is written.
+    pub fn record_response(dir: &Path, url: &str, final_url: &str, status: u16, body: &str) -> Result<()> {
+        std::fs::create_dir_all(dir)
+            .with_context(|| format!("Failed to create recording dir: {:?}", dir))?;
+
+        let url = Url::parse(url).with_context(|| format!("Invalid recorded URL: {}", url))?;
+        let final_url = Url::parse(final_url).with_context(|| format!("Invalid recorded URL: {}", final_url))?;
+        let mut recording = Self::load(dir)?;
+
+        let target = |url: &Url| match url.query() {
+            Some(query) => format!("{}?{}", url.path(), query),
+            None => url.path().to_string(),
+        };
+        if target(&url) != target(&final_url) {
+            // The client follows redirects itself, so a chain is recorded as one hop
+            recording.responses.push(RecordedResponse {
+                path: url.path().to_string(),
+                query: url.query_pairs().into_owned().collect(),
+                status: StatusCode::FOUND.as_u16(),
+                location: Some(target(&final_url)),
+                ..Default::default()
+            });
+        }
+
+        let body_file = format!("{:03}.html", recording.responses.len() + 1);
+        std::fs::write(dir.join(&body_file), archive::redact_html(body))
+            .with_context(|| format!("Failed to write recorded body: {}", body_file))?;
+
+        recording.responses.push(RecordedResponse {
+            path: final_url.path().to_string(),
+            query: final_url.query_pairs().into_owned().collect(),
+            status,
+            location: None,
+            body_file: Some(body_file),
+            body: None,
+        });
+
+        recording.save(dir)
+    }
+}
+
+/// A request the replay server received
+#[derive(Debug, Clone)]
+pub struct ReceivedRequest {
+    pub path: String,
+    pub query: BTreeMap<String, String>,
+    pub cookie: Option<String>,
+    pub user_agent: Option<String>,
+}
+
+#[derive(Clone)]
+struct ReplayState {
+    recording: Arc<Recording>,
+    dir: Arc<PathBuf>,
+    requests: Arc<Mutex<Vec<ReceivedRequest>>>,
+}
+
+/// Local HTTP server replaying a [`Recording`], standing in for linkedin.com
+/// when `ScrapeEnv::base_url` points at it
+pub struct ReplayServer {
+    base_url: String,
+    requests: Arc<Mutex<Vec<ReceivedRequest>>>,
+    handle: JoinHandle<()>,
+}
+
+impl ReplayServer {
+    /// Serve the recording in `dir` on an ephemeral localhost port
+    pub async fn start<P: AsRef<Path>>(dir: P) -> Result<Self> {
+        let recording = Recording::load(dir.as_ref())?;
+        Self::start_with(recording, dir.as_ref().to_path_buf()).await
+    }
+
+    /// Serve `recording`, resolving body files relative to `dir`
+    pub async fn start_with(recording: Recording, dir: PathBuf) -> Result<Self> {
+        let requests = Arc::new(Mutex::new(Vec::new()));
+        let state = ReplayState {
+            recording: Arc::new(recording),
+            dir: Arc::new(dir),
+            requests: requests.clone(),
+        };
+
+        let app = Router::new().fallback(replay).with_state(state);
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0")
+            .await
+            .context("Failed to bind replay server")?;
+        let addr = listener.local_addr()?;
+
+        let handle = tokio::spawn(async move {
+            let _ = axum::serve(listener, app).await;
+        });
+
+        Ok(Self {
+            base_url: format!("http://{}", addr),
+            requests,
+            handle,
+        })
+    }
+
+    pub fn base_url(&self) -> &str {
+        &self.base_url
+    }
+
+    /// Requests received so far, in order
+    pub fn requests(&self) -> Vec<ReceivedRequest> {
+        self.requests.lock().map(|requests| requests.clone()).unwrap_or_default()
+    }
+}
+
+impl Drop for ReplayServer {
+    fn drop(&mut self) {
+        self.handle.abort();
+    }
+}
+
+async fn replay(State(state): State<ReplayState>, request: Request) -> Response {
+    let path = request.uri().path().to_string();
+    let query: BTreeMap<String, String> = request
+        .uri()
+        .query()
+        .map(|query| url::form_urlencoded::parse(query.as_bytes()).into_owned().collect())
+        .unwrap_or_default();
+    let header_value = |name: header::HeaderName| {
+        request
+            .headers()
+            .get(name)
+            .and_then(|value| value.to_str().ok())
+            .map(str::to_string)
+    };
+
+    if let Ok(mut requests) = state.requests.lock() {
+        requests.push(ReceivedRequest {
+            path: path.clone(),
+            query: query.clone(),
+            cookie: header_value(header::COOKIE),
+            user_agent: header_value(header::USER_AGENT),
+        });
+    }
+
+    let Some(recorded) = state.recording.best_match(&path, &query) else {
+        return plain_response(StatusCode::NOT_FOUND, format!("No recorded response for {}", path));
+    };
+
+    let body = match (&recorded.body_file, &recorded.body) {
+        (Some(file), _) => match std::fs::read_to_string(state.dir.join(file)) {
+            Ok(body) => body,
+            Err(err) => {
+                return plain_response(StatusCode::INTERNAL_SERVER_ERROR, format!("{}: {}", file, err));
+            }
+        },
+        (None, Some(body)) => body.clone(),
+        (None, None) => String::new(),
+    };
+
+    let status = StatusCode::from_u16(recorded.status).unwrap_or(StatusCode::INTERNAL_SERVER_ERROR);
+    let mut response = plain_response(status, body);
+    response
+        .headers_mut()
+        .insert(header::CONTENT_TYPE, HeaderValue::from_static("text/html; charset=utf-8"));
+    if let Some(location) = &recorded.location {
+        if let Ok(value) = HeaderValue::from_str(location) {
+            response.headers_mut().insert(header::LOCATION, value);
+        }
+    }
+    response
+}
+
+fn plain_response(status: StatusCode, body: String) -> Response {
+    let mut response = Response::new(Body::from(body));
+    *response.status_mut() = status;
+    response
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_response_matching() {
+        let recorded = RecordedResponse {
+            path: "/jobs/search".to_string(),
+            query: BTreeMap::from([("start".to_string(), "25".to_string())]),
+            ..Default::default()
+        };
+
+        let mut query = BTreeMap::from([("keywords".to_string(), "rust".to_string())]);
+        assert!(!recorded.matches("/jobs/search", &query));
+
+        query.insert("start".to_string(), "25".to_string());
+        assert!(recorded.matches("/jobs/search", &query));
+        assert!(!recorded.matches("/jobs/view", &query));
+    }
+
+    #[test]
+    fn test_most_specific_match_wins() {
+        let recording = Recording {
+            responses: vec![
+                RecordedResponse {
+                    path: "/jobs/search".to_string(),
+                    body: Some("first page".to_string()),
+                    ..Default::default()
+                },
+                RecordedResponse {
+                    path: "/jobs/search".to_string(),
+                    query: BTreeMap::from([("start".to_string(), "25".to_string())]),
+                    body: Some("second page".to_string()),
+                    ..Default::default()
+                },
+            ],
+        };
+
+        let second = BTreeMap::from([("start".to_string(), "25".to_string())]);
+        let matched = recording.best_match("/jobs/search", &second).unwrap();
+        assert_eq!(matched.body.as_deref(), Some("second page"));
+
+        let matched = recording.best_match("/jobs/search", &BTreeMap::new()).unwrap();
+        assert_eq!(matched.body.as_deref(), Some("first page"));
+    }
+
+    #[test]
+    fn test_record_response_appends_redacted_entry() {
+        let dir = tempfile::TempDir::new().unwrap();
+        let url = "https://www.linkedin.com/jobs/search?keywords=rust&start=0";
+        Recording::record_response(
+            dir.path(),
+            url,
+            url,
+            200,
+            "<meta name=\"csrf-token\" content=\"ajax:42\">",
+        )
+        .unwrap();
+
+        let recording = Recording::load(dir.path()).unwrap();
+        assert_eq!(recording.responses.len(), 1);
+        assert_eq!(recording.responses[0].path, "/jobs/search");
+        assert_eq!(recording.responses[0].query.get("start").map(String::as_str), Some("0"));
+
+        let body = std::fs::read_to_string(dir.path().join("001.html")).unwrap();
+        assert!(!body.contains("ajax:42"));
+    }
+
+    #[test]
+    fn test_record_response_keeps_redirect() {
+        let dir = tempfile::TempDir::new().unwrap();
+        Recording::record_response(
+            dir.path(),
+            "https://www.linkedin.com/jobs/search?keywords=rust",
+            "https://www.linkedin.com/authwall?trk=jobs_search",
+            200,
+            "<form class=\"join-form\"></form>",
+        )
+        .unwrap();
+
+        let recording = Recording::load(dir.path()).unwrap();
+        assert_eq!(recording.responses.len(), 2);
+        let redirect = &recording.responses[0];
+        assert_eq!((redirect.path.as_str(), redirect.status), ("/jobs/search", 302));
+        assert_eq!(redirect.location.as_deref(), Some("/authwall?trk=jobs_search"));
+        assert_eq!(redirect.body_file, None);
+        let wall = &recording.responses[1];
+        assert_eq!((wall.path.as_str(), wall.status), ("/authwall", 200));
+        assert_eq!(wall.body_file.as_deref(), Some("002.html"));
+    }
+}
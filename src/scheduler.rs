@@ -1,35 +1,85 @@
+use crate::metrics::Metrics;
+use crate::notify::{Notification, Notifier};
+use crate::reload::LiveConfig;
+use crate::session::{self, SessionStatus};
 use crate::{service, storage};
 use chrono::Local;
 use std::sync::Arc;
 use tokio::sync::Mutex;
-use tokio::time::{sleep, Duration};
+use tokio::time::{sleep_until, Duration, Instant};
+use tracing::{error, info, warn};
 
-pub async fn run_scheduler(paths: storage::Paths, last_run: Arc<Mutex<Option<chrono::DateTime<Local>>>>) {
+pub async fn run_scheduler(
+    paths: storage::Paths,
+    last_run: Arc<Mutex<Option<chrono::DateTime<Local>>>>,
+    config: Arc<LiveConfig>,
+) {
+    let mut changes = config.subscribe();
     loop {
-        match service::run_scrape_once(&paths).await {
+        match service::run_scrape_with_config(&paths, config.current()).await {
             Ok(summary) => {
                 let mut guard = last_run.lock().await;
                 *guard = Some(summary.updated_at);
-                println!(
-                    "[scheduler] ran scrape: total={}, today={}, new={} url={} at={} ",
-                    summary.total_jobs,
-                    summary.today_jobs,
-                    summary.new_jobs,
-                    summary.search_url,
-                    summary.updated_at
+                info!(
+                    run_id = %summary.run_id,
+                    total = summary.total_jobs,
+                    today = summary.today_jobs,
+                    new = summary.new_jobs,
+                    duplicates = summary.duplicate_jobs,
+                    pages = summary.pages_fetched,
+                    url = %summary.search_url,
+                    "scrape finished"
                 );
+                for anomaly in &summary.parse_anomalies {
+                    warn!(run_id = %summary.run_id, ?anomaly, "parse anomaly: {}", anomaly.describe());
+                }
             }
             Err(err) => {
-                eprintln!("[scheduler] scrape failed: {:#}", err);
+                error!(error = format!("{:#}", err), "scrape failed");
             }
         }
 
-        let interval_hours = storage::load_config_or_default(&paths)
-            .map(|config| config.schedule.interval_hours)
-            .unwrap_or(4);
+        warn_on_session_problems(&paths);
 
-        let sleep_seconds = interval_hours.saturating_mul(3600);
-        println!("[scheduler] next run in {} seconds", sleep_seconds);
-        sleep(Duration::from_secs(sleep_seconds)).await;
+        // Counted from the end of this run, and recomputed when the interval changes
+        let finished = Instant::now();
+        let mut interval_hours = None;
+        loop {
+            let hours = config.current().schedule.interval_hours;
+            let next_run = finished + Duration::from_secs(hours.saturating_mul(3600));
+            if interval_hours != Some(hours) {
+                let seconds = next_run.saturating_duration_since(Instant::now()).as_secs();
+                Metrics::global().set_next_run(chrono::Utc::now() + chrono::Duration::seconds(seconds as i64));
+                info!(seconds, interval_hours = hours, "next run scheduled");
+                interval_hours = Some(hours);
+            }
+            tokio::select! {
+                _ = sleep_until(next_run) => break,
+                changed = changes.changed() => {
+                    if changed.is_err() {
+                        // The config can no longer change
+                        sleep_until(next_run).await;
+                        break;
+                    }
+                }
+            }
+        }
+    }
+}
+
+/// Warn ahead of cookie expiry and when LinkedIn stops honoring the session,
+/// in the log and in web UIs (open ones now, others when they connect)
+pub fn warn_on_session_problems(paths: &storage::Paths) {
+    match session::check_session(paths) {
+        Ok(report) => match report.status {
+            SessionStatus::Expiring | SessionStatus::Expired => {
+                warn!(status = ?report.status, "LinkedIn session: {}", report.message);
+                Notifier::global().send(Notification::SessionWarning { session: report });
+            }
+            SessionStatus::Valid | SessionStatus::Absent => Notifier::global().clear_pending(),
+        },
+        Err(err) => {
+            error!(error = format!("{:#}", err), "session check failed");
+        }
     }
 }
@@ -0,0 +1,294 @@
+use crate::config::{Config, SALARY_MIN_RANGE, SALARY_STEP};
+use crate::facets::{self, ExperienceLevel, JobType, SortBy, Workplace, DISTANCES_MILES, MAX_POSTED_WITHIN_HOURS};
+use crate::fingerprint::{PROFILES, ROTATE};
+use crate::migrate::CURRENT_VERSION;
+use crate::secrets::SECRET_REF_PREFIX;
+use serde_json::{json, Map, Value};
+
+/// Sections in `config.toml` order, with a description
+const SECTIONS: &[(&str, &str)] = &[
+    ("search", "What to search LinkedIn for"),
+    ("schedule", "How often to scrape"),
+    ("archive", "Retention of the per-run raw HTML archive"),
+    ("proxy", "Outbound proxies; with several URLs, requests rotate across them"),
+    ("rate_limit", "Outbound request budget, per host"),
+    ("browser", "Browser the scraper presents itself as"),
+    ("auth", "Access to the web UI and API"),
+    ("server", "Where and how the web UI and API are served (changes need a restart)"),
+];
+
+/// Type and constraints of every field, mirroring `Config::validate`
+fn fields() -> Vec<(&'static str, &'static str, Value)> {
+    let profiles: Vec<&str> = PROFILES.iter().map(|profile| profile.name).collect();
+    let secret_ref = json!({ "type": "string", "pattern": format!("^{}.+", SECRET_REF_PREFIX) });
+    let secret_hint = format!("a `{}<name>` reference to the secret store", SECRET_REF_PREFIX);
+
+    vec![
+        ("search", "keywords", json!({ "type": "string", "minLength": 1, "description": "Job search keywords" })),
+        ("search", "location", json!({ "type": "string", "description": "Location for the job search" })),
+        ("search", "geo_id", json!({ "type": ["integer", "null"], "minimum": 1, "description": "LinkedIn geoId of the location" })),
+        (
+            "search",
+            "distance",
+            json!({
+                "enum": DISTANCES_MILES.iter().map(|miles| json!(miles)).chain([Value::Null]).collect::<Vec<_>>(),
+                "description": "Radius around the location, in miles",
+            }),
+        ),
+        (
+            "search",
+            "workplace",
+            json!({
+                "type": "array",
+                "items": { "enum": facets::names::<Workplace>() },
+                "uniqueItems": true,
+                "description": "On-site, remote and/or hybrid jobs (any when empty)",
+            }),
+        ),
+        (
+            "search",
+            "experience",
+            json!({
+                "type": "array",
+                "items": { "enum": facets::names::<ExperienceLevel>() },
+                "uniqueItems": true,
+                "description": "Experience levels (any when empty)",
+            }),
+        ),
+        (
+            "search",
+            "job_types",
+            json!({
+                "type": "array",
+                "items": { "enum": facets::names::<JobType>() },
+                "uniqueItems": true,
+                "description": "Job types (any when empty)",
+            }),
+        ),
+        ("search", "easy_apply", json!({ "type": "boolean", "description": "Only jobs with LinkedIn Easy Apply" })),
+        (
+            "search",
+            "company_ids",
+            json!({ "type": "array", "items": { "type": "integer", "minimum": 1 }, "description": "Only these LinkedIn company ids" }),
+        ),
+        (
+            "search",
+            "industry_ids",
+            json!({ "type": "array", "items": { "type": "integer", "minimum": 1 }, "description": "Only these LinkedIn industry ids" }),
+        ),
+        (
+            "search",
+            "salary_min",
+            json!({
+                "anyOf": [
+                    { "const": 0 },
+                    {
+                        "type": "integer",
+                        "minimum": SALARY_MIN_RANGE.start(),
+                        "maximum": SALARY_MIN_RANGE.end(),
+                        "multipleOf": SALARY_STEP,
+                    },
+                    { "type": "null" },
+                ],
+                "description": format!(
+                    "Minimum salary in USD, ${}k to ${}k in ${}k steps; 0 or unset for no filter",
+                    SALARY_MIN_RANGE.start() / 1000,
+                    SALARY_MIN_RANGE.end() / 1000,
+                    SALARY_STEP / 1000
+                ),
+            }),
+        ),
+        (
+            "search",
+            "posted_within_hours",
+            json!({
+                "type": "integer",
+                "minimum": 0,
+                "maximum": MAX_POSTED_WITHIN_HOURS,
+                "description": "Only jobs posted within this many hours (0: any time)",
+            }),
+        ),
+        ("search", "sort_by", json!({ "enum": facets::names::<SortBy>(), "description": "Result order" })),
+        ("search", "max_pages", json!({ "type": "integer", "minimum": 1, "description": "Result pages (25 jobs each) per run" })),
+        ("schedule", "interval_hours", json!({ "type": "integer", "minimum": 1, "description": "Hours between runs" })),
+        (
+            "schedule",
+            "stale_after_intervals",
+            json!({ "type": "integer", "minimum": 1, "description": "/readyz fails once the last success is this many intervals old" }),
+        ),
+        ("archive", "max_runs", json!({ "type": "integer", "minimum": 0, "description": "Runs to keep" })),
+        (
+            "archive",
+            "max_total_bytes",
+            json!({ "type": "integer", "minimum": 0, "description": "Total compressed size to keep, in bytes (0: no limit)" }),
+        ),
+        (
+            "proxy",
+            "urls",
+            json!({
+                "type": "array",
+                "items": { "type": "string", "pattern": "^(https?|socks5h?)://" },
+                "description": "http://, https:// or socks5:// URLs, optionally with user:pass@",
+            }),
+        ),
+        (
+            "proxy",
+            "cooldown_minutes",
+            json!({ "type": "integer", "minimum": 0, "description": "How long a blocked or failing proxy sits out" }),
+        ),
+        ("proxy", "timeout_secs", json!({ "type": "integer", "minimum": 1, "description": "Request timeout through a proxy" })),
+        (
+            "rate_limit",
+            "requests_per_minute",
+            json!({ "type": "number", "minimum": 0, "description": "Sustained request rate (0: no token bucket)" }),
+        ),
+        ("rate_limit", "burst", json!({ "type": "integer", "minimum": 1, "description": "Requests allowed back to back" })),
+        ("rate_limit", "min_delay_ms", json!({ "type": "integer", "minimum": 0, "description": "Shortest random pause before a request" })),
+        (
+            "rate_limit",
+            "max_delay_ms",
+            json!({ "type": "integer", "minimum": 0, "description": "Longest random pause before a request; at least min_delay_ms" }),
+        ),
+        (
+            "browser",
+            "profile",
+            json!({
+                "enum": profiles.iter().copied().chain([ROTATE]).collect::<Vec<_>>(),
+                "description": format!("Header profile, or \"{}\" to pick one per run", ROTATE),
+            }),
+        ),
+        (
+            "browser",
+            "rotate",
+            json!({ "type": "array", "items": { "enum": profiles }, "description": "Profiles to rotate among (all when empty)" }),
+        ),
+        (
+            "auth",
+            "api_keys",
+            json!({ "type": "array", "items": secret_ref, "description": format!("API keys, each {}", secret_hint) }),
+        ),
+        ("auth", "username", json!({ "type": ["string", "null"], "description": "Username for the browser login" })),
+        (
+            "auth",
+            "password",
+            json!({
+                "type": ["string", "null"],
+                "pattern": secret_ref["pattern"],
+                "description": format!("Password for the browser login, {}", secret_hint),
+            }),
+        ),
+        ("auth", "session_hours", json!({ "type": "integer", "minimum": 1, "description": "How long a browser login lasts" })),
+        (
+            "server",
+            "listen",
+            json!({
+                "type": "array",
+                "minItems": 1,
+                "items": { "type": "string" },
+                "description": "ip:port addresses and/or unix:/path/to.sock sockets",
+            }),
+        ),
+        ("server", "tls_cert", json!({ "type": ["string", "null"], "description": "PEM certificate chain; with tls_key, serve HTTPS" })),
+        ("server", "tls_key", json!({ "type": ["string", "null"], "description": "PEM private key (PKCS#8) for tls_cert" })),
+        ("server", "base_path", json!({ "type": "string", "description": "URL prefix behind a reverse proxy, e.g. /fresh-post" })),
+        (
+            "server",
+            "web_dir",
+            json!({ "type": ["string", "null"], "description": "Serve the web UI from this directory instead of the built-in copy" }),
+        ),
+    ]
+}
+
+/// JSON Schema (draft 2020-12) of `config.toml`, with defaults taken from `Config::default`
+pub fn schema() -> Value {
+    let defaults = serde_json::to_value(Config::default()).unwrap_or_default();
+    let mut sections = Map::new();
+    for (section, description) in SECTIONS {
+        sections.insert(
+            section.to_string(),
+            json!({
+                "type": "object",
+                "description": description,
+                "additionalProperties": false,
+                "properties": {},
+            }),
+        );
+    }
+    for (section, field, mut spec) in fields() {
+        let default = &defaults[section][field];
+        if !default.is_null() {
+            spec["default"] = default.clone();
+        }
+        sections[section]["properties"][field] = spec;
+    }
+
+    let mut properties = Map::new();
+    properties.insert(
+        "version".to_string(),
+        json!({
+            "type": "integer",
+            "const": CURRENT_VERSION,
+            "description": "Config format version; older files are upgraded (with a backup) when loaded",
+        }),
+    );
+    properties.extend(sections);
+
+    json!({
+        "$schema": "https://json-schema.org/draft/2020-12/schema",
+        "title": "fresh-post config.toml",
+        "type": "object",
+        "additionalProperties": false,
+        "properties": properties,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_schema_covers_every_field() {
+        let schema = schema();
+        let defaults = serde_json::to_value(Config::default()).unwrap();
+        for (section, fields) in defaults.as_object().unwrap() {
+            let properties = &schema["properties"][section];
+            match fields.as_object() {
+                Some(fields) => {
+                    let documented: Vec<&String> = properties["properties"].as_object().unwrap().keys().collect();
+                    let actual: Vec<&String> = fields.keys().collect();
+                    assert_eq!(documented.len(), actual.len(), "{}: {:?} vs {:?}", section, documented, actual);
+                    for field in actual {
+                        assert!(properties["properties"].get(field).is_some(), "{}.{} missing", section, field);
+                    }
+                }
+                None => assert!(properties.is_object(), "{} missing", section),
+            }
+        }
+        assert_eq!(schema["properties"].as_object().unwrap().len(), defaults.as_object().unwrap().len());
+    }
+
+    #[test]
+    fn test_schema_defaults_come_from_config() {
+        let schema = schema();
+        let interval = &schema["properties"]["schedule"]["properties"]["interval_hours"];
+        assert_eq!(interval["default"], json!(Config::default().schedule.interval_hours));
+        assert_eq!(interval["minimum"], json!(1));
+        assert!(schema["properties"]["server"]["properties"]["tls_cert"].get("default").is_none());
+    }
+
+    #[test]
+    fn test_salary_min_matches_validate() {
+        let schema = schema();
+        let salary = &schema["properties"]["search"]["properties"]["salary_min"]["anyOf"];
+        assert_eq!(salary[0], json!({ "const": 0 }));
+        // 20000 is a multiple of the step but below what LinkedIn accepts
+        assert_eq!(salary[1]["minimum"], json!(40_000));
+        assert_eq!(salary[1]["maximum"], json!(200_000));
+        assert_eq!(salary[1]["multipleOf"], json!(SALARY_STEP));
+        assert_eq!(salary[2], json!({ "type": "null" }));
+
+        let mut config = Config::default();
+        config.search.salary_min = Some(20_000);
+        assert!(config.validate().is_err());
+    }
+}
@@ -1,36 +1,95 @@
+use crate::config::{self, SearchConfig};
+use crate::cookies::{self, StoredCookie};
+use crate::diagnostics::{ParseDiagnostics, ParsePath};
+use crate::facets::{self, Facet, SortBy};
+use crate::fingerprint::BrowserProfile;
+use crate::metrics::Metrics;
 use crate::models::JobPosting;
+use crate::ratelimit::RateLimiter;
+use crate::selectors::CompiledSelectors;
 use anyhow::{Context, Result};
+use reqwest::cookie::Jar;
 use reqwest::Client;
-use scraper::{Html, Selector};
+use scraper::{ElementRef, Html, Selector};
 use std::collections::hash_map::DefaultHasher;
 use std::hash::{Hash, Hasher};
+use std::sync::Arc;
 use url::Url;
 use regex::Regex;
+use std::time::Duration;
+use tracing::{debug, info, warn};
+
+/// Origin that search requests go to (overridable for offline replay)
+pub const LINKEDIN_BASE_URL: &str = "https://www.linkedin.com";
+
+/// Jobs per page of LinkedIn search results (the `start` offset step)
+pub const RESULTS_PER_PAGE: u32 = 25;
 
 /// Build a LinkedIn job search URL
-pub fn build_search_url(keywords: &str, location: &str, remote: bool, salary_min: Option<u32>) -> String {
-    let base_url = "https://www.linkedin.com/jobs/search";
-    let mut url = Url::parse(base_url).unwrap();
-    
-    url.query_pairs_mut()
-        .append_pair("keywords", keywords)
-        .append_pair("location", location)
-        .append_pair("f_TPR", "r86400"); // Last 24 hours filter
+pub fn build_search_url(search: &SearchConfig) -> Result<String> {
+    build_search_url_with_base(LINKEDIN_BASE_URL, search)
+}
+
+/// Build a job search URL against `base_url` instead of linkedin.com
+pub fn build_search_url_with_base(base_url: &str, search: &SearchConfig) -> Result<String> {
+    let mut url = Url::parse(base_url)
+        .and_then(|base| base.join("/jobs/search"))
+        .with_context(|| format!("Invalid search base URL: {:?}", base_url))?;
+    
+    let mut query = url.query_pairs_mut();
+    query
+        .append_pair("keywords", &search.keywords)
+        .append_pair("location", &search.location);
+    if let Some(geo_id) = search.geo_id {
+        query.append_pair("geoId", &geo_id.to_string());
+    }
+    if let Some(distance) = search.distance {
+        query.append_pair("distance", &distance.to_string());
+    }
+    // Posted within the last N seconds (r86400: the last 24 hours)
+    if search.posted_within_hours > 0 {
+        query.append_pair("f_TPR", &format!("r{}", u64::from(search.posted_within_hours) * 3600));
+    }
     
-    // Add remote filter if requested (f_WT=2 means remote only)
-    if remote {
-        url.query_pairs_mut().append_pair("f_WT", "2");
+    for (name, codes) in [
+        ("f_E", facets::join(&search.experience)),
+        ("f_JT", facets::join(&search.job_types)),
+        ("f_WT", facets::join(&search.workplace)),
+    ] {
+        if !codes.is_empty() {
+            query.append_pair(name, &codes);
+        }
+    }
+    if search.easy_apply {
+        query.append_pair("f_AL", "true");
+    }
+    for (name, ids) in [
+        ("f_C", join_ids(&search.company_ids)),
+        ("f_I", join_ids(&search.industry_ids)),
+    ] {
+        if !ids.is_empty() {
+            query.append_pair(name, &ids);
+        }
     }
     
     // Add salary filter if specified (f_SB2 is minimum salary in USD)
-    // LinkedIn uses increments of $20k, valid range: $40k - $200k
-    if let Some(salary) = salary_min {
-        if salary >= 40000 && salary <= 200000 {
-            url.query_pairs_mut().append_pair("f_SB2", &salary.to_string());
+    if let Some(salary) = search.salary_min {
+        if config::is_salary_step(salary) {
+            query.append_pair("f_SB2", &salary.to_string());
         }
     }
     
-    url.to_string()
+    // Relevance is LinkedIn's default, so only date sorting is sent
+    if search.sort_by == SortBy::Date {
+        query.append_pair("sortBy", SortBy::Date.code());
+    }
+    drop(query);
+    
+    Ok(url.to_string())
+}
+
+fn join_ids<T: ToString>(ids: &[T]) -> String {
+    ids.iter().map(ToString::to_string).collect::<Vec<_>>().join(",")
 }
 
 /// Create an HTTP client with appropriate headers
@@ -38,121 +97,153 @@ pub fn create_client() -> Result<Client> {
     create_client_with_cookies(None)
 }
 
-/// Create an HTTP client (cookie loading is handled separately in main.rs)
-pub fn create_client_with_cookies(_cookie_file: Option<&str>) -> Result<Client> {
-    use reqwest::header;
-    
-    // Build headers to mimic a real browser more closely
-    let mut headers = header::HeaderMap::new();
-    headers.insert(header::USER_AGENT, header::HeaderValue::from_static(
-        "Mozilla/5.0 (Macintosh; Intel Mac OS X 10_15_7) AppleWebKit/537.36 (KHTML, like Gecko) Chrome/120.0.0.0 Safari/537.36"
-    ));
-    headers.insert(header::ACCEPT, header::HeaderValue::from_static(
-        "text/html,application/xhtml+xml,application/xml;q=0.9,image/avif,image/webp,*/*;q=0.8"
-    ));
-    headers.insert(header::ACCEPT_LANGUAGE, header::HeaderValue::from_static(
-        "en-US,en;q=0.9"
-    ));
-    headers.insert(header::ACCEPT_ENCODING, header::HeaderValue::from_static(
-        "gzip, deflate, br"
-    ));
-    headers.insert(header::REFERER, header::HeaderValue::from_static(
-        "https://www.linkedin.com/"
-    ));
-    headers.insert(
-        header::HeaderName::from_static("sec-fetch-dest"),
-        header::HeaderValue::from_static("document")
-    );
-    headers.insert(
-        header::HeaderName::from_static("sec-fetch-mode"),
-        header::HeaderValue::from_static("navigate")
-    );
-    headers.insert(
-        header::HeaderName::from_static("sec-fetch-site"),
-        header::HeaderValue::from_static("same-origin")
-    );
-    headers.insert(
-        header::HeaderName::from_static("upgrade-insecure-requests"),
-        header::HeaderValue::from_static("1")
-    );
-    
-    // Build the client directly instead of calling create_client() to avoid recursion
-    Client::builder()
-        .cookie_store(true)
-        .default_headers(headers)
-        .build()
-        .context("Failed to create HTTP client")
+/// Create an HTTP client, loading cookies from `cookie_file` (simple, Netscape or JSON format)
+/// into the client's cookie jar
+pub fn create_client_with_cookies(cookie_file: Option<&str>) -> Result<Client> {
+    let stored = match cookie_file {
+        Some(path) => {
+            let stored = cookies::load_cookies_from_file(path)?;
+            if !stored.is_empty() {
+                info!(count = stored.len(), path, "loaded cookies");
+            }
+            Some(stored)
+        }
+        None => None,
+    };
+
+    create_client_with_cookie_list(stored.as_deref())
 }
 
-/// Load cookies from a simple text file (format: name=value, one per line)
-/// Returns a cookie header string ready to use
-pub fn load_cookies_from_file(path: &str) -> Result<String> {
-    let content = std::fs::read_to_string(path)
-        .with_context(|| format!("Failed to read cookie file: {}", path))?;
-    
-    let cookies: Vec<String> = content
-        .lines()
-        .map(|line| line.trim())
-        .filter(|line| !line.is_empty() && !line.starts_with('#'))
-        .map(|line| line.to_string())
-        .collect();
-    
-    let cookie_count = cookies.len();
-    let cookie_header = cookies.join("; ");
-    
-    if !cookie_header.is_empty() {
-        println!("🍪 Loaded {} cookies from {}", cookie_count, path);
-    }
-    
-    Ok(cookie_header)
+/// Create an HTTP client with already-loaded cookies in its cookie jar
+pub fn create_client_with_cookie_list(stored: Option<&[StoredCookie]>) -> Result<Client> {
+    let jar = cookies::build_cookie_jar(stored.unwrap_or_default());
+    create_client_with_jar(jar, BrowserProfile::default_profile(), None)
 }
 
-/// Fetch HTML content from a URL
-pub async fn fetch_jobs_page(client: &Client, url: &str) -> Result<String> {
-    fetch_jobs_page_with_cookies(client, url, None).await
+/// Outbound proxy for a client
+#[derive(Debug, Clone)]
+pub struct ProxySettings {
+    /// `http://`, `https://` or `socks5://` URL
+    pub url: String,
+    pub timeout: Duration,
 }
 
-/// Fetch HTML content from a URL with optional cookies
-pub async fn fetch_jobs_page_with_cookies(client: &Client, url: &str, cookies: Option<&str>) -> Result<String> {
-    // Add a delay to avoid rate limiting (LinkedIn may block if requests are too frequent)
-    // This delay helps mimic human behavior
-    println!("⏳ Waiting 3 seconds before request (to avoid rate limiting)...");
-    tokio::time::sleep(tokio::time::Duration::from_secs(3)).await;
-    
-    let mut request = client.get(url);
-    
-    // Add cookies if provided
-    if let Some(cookie_header) = cookies {
-        request = request.header(reqwest::header::COOKIE, cookie_header);
+/// Create an HTTP client around a shared cookie jar, sending the headers of
+/// `profile` and optionally going through a proxy. Clients for different proxies
+/// can share one jar, so the session stays the same.
+pub fn create_client_with_jar(jar: Arc<Jar>, profile: &BrowserProfile, proxy: Option<&ProxySettings>) -> Result<Client> {
+    let mut builder = Client::builder()
+        .default_headers(profile.headers())
+        .cookie_provider(jar);
+
+    if let Some(proxy) = proxy {
+        let proxy_url = reqwest::Proxy::all(&proxy.url)
+            .with_context(|| format!("Invalid proxy URL: {}", crate::proxy::redact_proxy_url(&proxy.url)))?;
+        builder = builder.proxy(proxy_url).timeout(proxy.timeout);
     }
-    
-    let response = request
-        .send()
-        .await
-        .context("Failed to send HTTP request")?;
-    
-    let status = response.status();
-    
-    // Handle LinkedIn's anti-bot protection (status 999)
-    if status.as_u16() == 999 {
-        anyhow::bail!(
+
+    builder.build().context("Failed to create HTTP client")
+}
+
+/// Status LinkedIn answers with when its anti-bot protection kicks in
+pub const BLOCKED_STATUS: u16 = 999;
+
+/// LinkedIn blocked the request (status 999)
+#[derive(Debug)]
+pub struct BlockedError;
+
+impl std::fmt::Display for BlockedError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
             "LinkedIn blocked the request (status 999 - anti-bot protection).\n\
             Possible solutions:\n\
             1. Wait a few minutes and try again\n\
             2. Refresh your cookies (export fresh cookies from browser)\n\
-            3. Use a VPN or different network\n\
+            3. Use a VPN, a different network or a proxy pool ([proxy] in config.toml)\n\
             4. Reduce request frequency (add longer delays)"
-        );
+        )
+    }
+}
+
+impl std::error::Error for BlockedError {}
+
+/// A fetched page along with the URL it ended up at after redirects
+#[derive(Debug, Clone)]
+pub struct FetchedPage {
+    pub final_url: String,
+    /// Status of the final response
+    pub status: u16,
+    pub html: String,
+}
+
+/// Search URL for results page `page` (0-based)
+pub fn page_url(search_url: &str, page: u32) -> String {
+    if page == 0 {
+        return search_url.to_string();
+    }
+
+    match Url::parse(search_url) {
+        Ok(mut url) => {
+            url.query_pairs_mut()
+                .append_pair("start", &(page * RESULTS_PER_PAGE).to_string());
+            url.to_string()
+        }
+        Err(_) => search_url.to_string(),
+    }
+}
+
+/// Fetch HTML content from a URL (cookies come from the client's jar),
+/// waiting for the shared rate limiter first
+pub async fn fetch_jobs_page(client: &Client, url: &str) -> Result<FetchedPage> {
+    fetch_jobs_page_with(client, url, Some(&RateLimiter::shared())).await
+}
+
+/// Fetch HTML content from a URL, going through `limiter` unless it is `None`
+#[tracing::instrument(name = "request", skip(client, limiter), fields(status, bytes))]
+pub async fn fetch_jobs_page_with(client: &Client, url: &str, limiter: Option<&RateLimiter>) -> Result<FetchedPage> {
+    // Pace requests so LinkedIn doesn't block us for hammering it
+    if let Some(limiter) = limiter {
+        limiter.acquire(url).await;
+    }
+
+    let response = match client.get(url).send().await {
+        Ok(response) => response,
+        Err(err) => {
+            Metrics::global().record_http_request(None);
+            return Err(err).context("Failed to send HTTP request");
+        }
+    };
+    
+    let status = response.status();
+    Metrics::global().record_http_request(Some(status.as_u16()));
+    let span = tracing::Span::current();
+    span.record("status", status.as_u16());
+    
+    // Handle LinkedIn's anti-bot protection (status 999)
+    if status.as_u16() == BLOCKED_STATUS {
+        warn!(status = status.as_u16(), "blocked by anti-bot protection");
+        return Err(BlockedError.into());
     }
     
     if !status.is_success() {
+        warn!(status = status.as_u16(), "request failed");
         anyhow::bail!("HTTP request failed with status: {} ({})", status, status.as_u16());
     }
     
-    response
+    let final_url = response.url().to_string();
+    let html = response
         .text()
         .await
-        .context("Failed to read response body")
+        .context("Failed to read response body")?;
+
+    span.record("bytes", html.len());
+    info!(status = status.as_u16(), bytes = html.len(), %final_url, "fetched page");
+    Ok(FetchedPage {
+        final_url,
+        status: status.as_u16(),
+        html,
+    })
 }
 
 /// Generate a unique ID for a job from its URL
@@ -188,14 +279,15 @@ fn parse_relative_date(date_str: &str) -> Option<chrono::NaiveDate> {
     None
 }
 
-/// Try to parse jobs from embedded JSON (logged-in view)
-fn parse_jobs_from_json(html: &str) -> Option<Vec<JobPosting>> {
+/// Try to parse jobs from embedded JSON (logged-in view).
+/// Returns the jobs along with the number of job titles found.
+fn parse_jobs_from_json(html: &str) -> Option<(Vec<JobPosting>, usize)> {
     // Look for embedded JSON data - LinkedIn embeds job data in JSON format for logged-in users
     if !html.contains("jobPostingTitle") && !html.contains("JobPostingCard") {
         return None;
     }
     
-    println!("🔍 Attempting to parse JSON data from logged-in view...");
+    debug!("attempting to parse JSON data from logged-in view");
     
     let mut jobs = Vec::new();
     
@@ -215,8 +307,13 @@ fn parse_jobs_from_json(html: &str) -> Option<Vec<JobPosting>> {
     let locations: Vec<_> = location_pattern.captures_iter(html).map(|c| c.get(1).map(|m| m.as_str().to_string())).collect();
     let urns: Vec<_> = urn_pattern.captures_iter(html).map(|c| c.get(1).map(|m| m.as_str().to_string())).collect();
     
-    println!("   Found: {} titles, {} companies, {} locations, {} urns", 
-             titles.len(), companies.len(), locations.len(), urns.len());
+    debug!(
+        titles = titles.len(),
+        companies = companies.len(),
+        locations = locations.len(),
+        urns = urns.len(),
+        "found JSON job fields"
+    );
     
     // Match up the data - only create jobs when we have titles
     // Use the number of titles as the limit (we should have matching companies/locations)
@@ -292,60 +389,96 @@ fn parse_jobs_from_json(html: &str) -> Option<Vec<JobPosting>> {
     if jobs.is_empty() {
         None
     } else {
-        println!("✅ Successfully parsed {} jobs from JSON", jobs.len());
-        Some(jobs)
+        Some((jobs, max_len))
     }
 }
 
-/// Parse job postings from HTML
+/// Parse job postings from HTML using the built-in selector profile
 pub fn parse_jobs_from_html(html: &str) -> Result<Vec<JobPosting>> {
+    parse_jobs_from_html_with(html, &CompiledSelectors::builtin())
+}
+
+/// First element under `element` matched by the earliest selector that matches anything
+fn select_first<'a>(element: ElementRef<'a>, selectors: &[Selector]) -> Option<ElementRef<'a>> {
+    selectors
+        .iter()
+        .find_map(|selector| element.select(selector).next())
+}
+
+/// Parse job postings from HTML with a custom selector profile
+pub fn parse_jobs_from_html_with(html: &str, selectors: &CompiledSelectors) -> Result<Vec<JobPosting>> {
+    parse_jobs_with_diagnostics(html, selectors).map(|(jobs, _)| jobs)
+}
+
+/// Placeholder values the parsers fall back to when a field can't be extracted
+const UNKNOWN_PLACEHOLDERS: &[&str] = &["Unknown", "Unknown Company", "Location TBD"];
+
+fn count_unknown_fields(jobs: &[JobPosting]) -> usize {
+    jobs.iter()
+        .map(|job| {
+            [&job.title, &job.company, &job.location]
+                .iter()
+                .filter(|value| UNKNOWN_PLACEHOLDERS.contains(&value.as_str()))
+                .count()
+        })
+        .sum()
+}
+
+/// Parse job postings and report how the parse went (path used, cards seen,
+/// fields that fell back to placeholders)
+pub fn parse_jobs_with_diagnostics(
+    html: &str,
+    selectors: &CompiledSelectors,
+) -> Result<(Vec<JobPosting>, ParseDiagnostics)> {
     // Try JSON parsing first (for logged-in view)
-    if let Some(json_jobs) = parse_jobs_from_json(html) {
-        println!("📊 Parsed {} jobs from JSON data (logged-in view)", json_jobs.len());
-        return Ok(json_jobs);
-    }
-    
+    let (path, jobs, card_count) = match parse_jobs_from_json(html) {
+        Some((jobs, title_count)) => {
+            info!(jobs = jobs.len(), path = "json", "parsed jobs from logged-in view");
+            (ParsePath::Json, jobs, title_count)
+        }
+        None => {
+            let (jobs, card_count) = parse_job_cards(html, selectors);
+            (ParsePath::Html, jobs, card_count)
+        }
+    };
+
+    let diagnostics = ParseDiagnostics {
+        path,
+        html_bytes: html.len(),
+        card_count,
+        job_count: jobs.len(),
+        unknown_fields: count_unknown_fields(&jobs),
+    };
+
+    Ok((jobs, diagnostics))
+}
+
+/// Parse the guest view's job cards. Returns the jobs and the number of cards matched.
+fn parse_job_cards(html: &str, selectors: &CompiledSelectors) -> (Vec<JobPosting>, usize) {
     // Fall back to HTML parsing (for guest view)
     let document = Html::parse_document(html);
     let mut jobs = Vec::new();
     
-    // LinkedIn job card selector - this may need adjustment based on actual HTML structure
-    // Common selectors: .job-card-container, .base-card, [data-job-id]
-    let job_card_selector = Selector::parse(".base-card, .job-card-container, [data-job-id]")
-        .map_err(|e| anyhow::anyhow!("Failed to parse job card selector: {:?}", e))?;
-    
-    // DEBUG: Count how many job cards we find
-    let job_card_count = document.select(&job_card_selector).count();
-    println!("🔍 Found {} job card elements in HTML", job_card_count);
-    
-    let title_selector = Selector::parse("h3.base-search-card__title, .job-card-list__title, a.job-card-list__title")
-        .map_err(|e| anyhow::anyhow!("Failed to parse title selector: {:?}", e))?;
+    // Use the first card selector that matches anything on the page
+    let job_cards: Vec<ElementRef> = selectors
+        .card
+        .iter()
+        .map(|selector| document.select(selector).collect::<Vec<_>>())
+        .find(|cards| !cards.is_empty())
+        .unwrap_or_default();
     
-    let company_selector = Selector::parse("h4.base-search-card__subtitle, .job-card-container__company-name, a.job-card-container__company-name")
-        .map_err(|e| anyhow::anyhow!("Failed to parse company selector: {:?}", e))?;
+    let card_count = job_cards.len();
+    debug!(cards = card_count, "found job card elements");
     
-    let location_selector = Selector::parse(".job-search-card__location, .job-card-container__metadata-item")
-        .map_err(|e| anyhow::anyhow!("Failed to parse location selector: {:?}", e))?;
-    
-    let link_selector = Selector::parse("a.base-card__full-link, a.job-card-list__title")
-        .map_err(|e| anyhow::anyhow!("Failed to parse link selector: {:?}", e))?;
-    
-    let date_selector = Selector::parse("time[class*='job-search-card__listdate'], time.job-search-card__listdate, .job-search-card__listdate, .job-search-card__listdate--new")
-        .map_err(|e| anyhow::anyhow!("Failed to parse date selector: {:?}", e))?;
-    
-    for job_element in document.select(&job_card_selector) {
+    for job_element in job_cards {
         // Extract title
-        let title = job_element
-            .select(&title_selector)
-            .next()
+        let title = select_first(job_element, &selectors.title)
             .and_then(|e| e.text().next())
             .map(|s| s.trim().to_string())
             .unwrap_or_else(|| "Unknown".to_string());
         
         // Extract company (handle nested <a> tags)
-        let company = job_element
-            .select(&company_selector)
-            .next()
+        let company = select_first(job_element, &selectors.company)
             .map(|e| {
                 // Get all text from the element (including nested elements)
                 e.text().collect::<Vec<_>>().join(" ").trim().to_string()
@@ -353,17 +486,13 @@ pub fn parse_jobs_from_html(html: &str) -> Result<Vec<JobPosting>> {
             .unwrap_or_else(|| "Unknown".to_string());
         
         // Extract location
-        let location = job_element
-            .select(&location_selector)
-            .next()
+        let location = select_first(job_element, &selectors.location)
             .and_then(|e| e.text().next())
             .map(|s| s.trim().to_string())
             .unwrap_or_else(|| "Unknown".to_string());
         
         // Extract URL
-        let url = job_element
-            .select(&link_selector)
-            .next()
+        let url = select_first(job_element, &selectors.link)
             .and_then(|e| e.value().attr("href"))
             .map(|href| {
                 if href.starts_with("http") {
@@ -372,16 +501,14 @@ pub fn parse_jobs_from_html(html: &str) -> Result<Vec<JobPosting>> {
                     format!("https://www.linkedin.com{}", href)
                 }
             })
-            .unwrap_or_else(|| "".to_string());
+            .unwrap_or_default();
         
         if url.is_empty() {
             continue; // Skip jobs without URLs
         }
         
         // Extract posted date
-        let posted_date = job_element
-            .select(&date_selector)
-            .next()
+        let posted_date = select_first(job_element, &selectors.date)
             .and_then(|e| {
                 // Try datetime attribute first
                 e.value().attr("datetime")
@@ -394,9 +521,7 @@ pub fn parse_jobs_from_html(html: &str) -> Result<Vec<JobPosting>> {
                     .or_else(|| parse_relative_date(date_str))
             });
         
-        // DEBUG: Print what we extracted
-        println!("   📝 Extracted: '{}' at '{}' in '{}' (date: {:?})", 
-                 title, company, location, posted_date);
+        debug!(%title, %company, %location, ?posted_date, "extracted job card");
         
         let id = generate_job_id(&url);
         
@@ -411,37 +536,87 @@ pub fn parse_jobs_from_html(html: &str) -> Result<Vec<JobPosting>> {
         ));
     }
     
-    // DEBUG: Show parsing results
-    println!("✅ Successfully parsed {} jobs", jobs.len());
-    if !jobs.is_empty() {
-        println!("   First job: {} at {}", jobs[0].title, jobs[0].company);
-    }
+    info!(jobs = jobs.len(), cards = card_count, path = "html", "parsed job cards");
     
-    Ok(jobs)
+    (jobs, card_count)
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::facets::{ExperienceLevel, JobType, Workplace};
+
+    fn search(keywords: &str, location: &str) -> SearchConfig {
+        SearchConfig {
+            keywords: keywords.to_string(),
+            location: location.to_string(),
+            ..SearchConfig::default()
+        }
+    }
 
     #[test]
     fn test_build_search_url() {
-        let url = build_search_url("rust developer", "San Francisco", false, None);
-        assert!(url.contains("keywords=rust+developer"));
-        assert!(url.contains("location=San+Francisco"));
-        assert!(url.contains("f_TPR=r86400"));
+        let url = build_search_url(&search("rust developer", "San Francisco")).unwrap();
+        assert!(url.ends_with("?keywords=rust+developer&location=San+Francisco&f_TPR=r86400"));
     }
 
     #[test]
-    fn test_build_search_url_with_remote() {
-        let url = build_search_url("rust developer", "San Francisco", true, None);
-        assert!(url.contains("f_WT=2"));
+    fn test_build_search_url_with_workplace() {
+        let mut search = search("rust developer", "San Francisco");
+        search.workplace = vec![Workplace::Hybrid, Workplace::Remote];
+        let url = build_search_url(&search).unwrap();
+        assert!(url.contains("f_WT=2%2C3"));
+    }
+
+    #[test]
+    fn test_build_search_url_with_facets() {
+        let mut search = search("rust", "Berlin");
+        search.geo_id = Some(103035651);
+        search.distance = Some(25);
+        search.posted_within_hours = 168;
+        search.experience = vec![ExperienceLevel::MidSenior, ExperienceLevel::Entry];
+        search.job_types = vec![JobType::FullTime];
+        search.easy_apply = true;
+        search.company_ids = vec![1035, 1441];
+        search.industry_ids = vec![4];
+        search.sort_by = SortBy::Date;
+
+        let url = build_search_url(&search).unwrap();
+        let query = url.split_once('?').unwrap().1;
+        assert_eq!(
+            query,
+            "keywords=rust&location=Berlin&geoId=103035651&distance=25&f_TPR=r604800\
+             &f_E=2%2C4&f_JT=F&f_AL=true&f_C=1035%2C1441&f_I=4&sortBy=DD"
+        );
+
+        search.posted_within_hours = 0;
+        assert!(!build_search_url(&search).unwrap().contains("f_TPR"));
     }
 
     #[test]
     fn test_build_search_url_with_salary() {
-        let url = build_search_url("rust developer", "San Francisco", false, Some(100000));
+        let mut search = search("rust developer", "San Francisco");
+        search.salary_min = Some(100000);
+        let url = build_search_url(&search).unwrap();
         assert!(url.contains("f_SB2=100000"));
+
+        // Values LinkedIn doesn't offer are left out, as validate rejects them
+        search.salary_min = Some(50000);
+        assert!(!build_search_url(&search).unwrap().contains("f_SB2"));
+    }
+
+    #[test]
+    fn test_build_search_url_with_base() {
+        let url = build_search_url_with_base("http://127.0.0.1:8080", &search("rust", "SF")).unwrap();
+        assert!(url.starts_with("http://127.0.0.1:8080/jobs/search?"));
+        assert!(build_search_url_with_base("127.0.0.1:8080", &search("rust", "SF")).is_err());
+    }
+
+    #[test]
+    fn test_page_url() {
+        let url = build_search_url(&search("rust", "SF")).unwrap();
+        assert_eq!(page_url(&url, 0), url);
+        assert!(page_url(&url, 2).ends_with("&start=50"));
     }
 
     #[test]
@@ -486,6 +661,49 @@ mod tests {
         assert_eq!(jobs[0].company, "Tech Corp");
     }
 
+    #[test]
+    fn test_parse_jobs_with_fallback_selectors() {
+        let html = r#"
+            <li class="job-result">
+                <h2 class="role">Rust Engineer</h2>
+                <span class="job-search-card__location">Remote</span>
+                <a class="apply" href="/jobs/view/456">View</a>
+            </li>
+        "#;
+
+        let profile = crate::selectors::SelectorProfile::with_overrides(crate::selectors::SelectorOverrides {
+            card: Some(vec![".base-card".to_string(), "li.job-result".to_string()]),
+            title: Some(vec!["h3.base-search-card__title".to_string(), "h2.role".to_string()]),
+            link: Some(vec!["a.apply".to_string()]),
+            ..Default::default()
+        });
+
+        let jobs = parse_jobs_from_html_with(html, &profile.compile().unwrap()).unwrap();
+        assert_eq!(jobs.len(), 1);
+        assert_eq!(jobs[0].title, "Rust Engineer");
+        assert_eq!(jobs[0].location, "Remote");
+        assert_eq!(jobs[0].url, "https://www.linkedin.com/jobs/view/456");
+    }
+
+    #[test]
+    fn test_parse_diagnostics_counts_unknown_fields() {
+        let html = r#"
+            <div class="base-card">
+                <a class="base-card__full-link" href="/jobs/view/1">
+                    <h3 class="base-search-card__title">Engineer</h3>
+                </a>
+            </div>
+            <div class="base-card"><h3 class="base-search-card__title">No link</h3></div>
+        "#;
+
+        let (jobs, diagnostics) = parse_jobs_with_diagnostics(html, &CompiledSelectors::builtin()).unwrap();
+        assert_eq!(jobs.len(), 1);
+        assert_eq!(diagnostics.path, ParsePath::Html);
+        assert_eq!(diagnostics.card_count, 2);
+        assert_eq!(diagnostics.job_count, 1);
+        assert_eq!(diagnostics.unknown_fields, 2);
+    }
+
     #[test]
     fn test_parse_empty_html() {
         let html = "<html><body></body></html>";
@@ -0,0 +1,333 @@
+use anyhow::{Context, Result};
+use base64::{engine::general_purpose::STANDARD as BASE64, Engine};
+use chacha20poly1305::aead::{Aead, AeadCore, KeyInit, OsRng, Payload};
+use chacha20poly1305::{Key, XChaCha20Poly1305, XNonce};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::fmt;
+use std::path::{Path, PathBuf};
+use std::sync::{Mutex, MutexGuard};
+
+/// Base64-encoded 32-byte key (`openssl rand -base64 32`)
+pub const KEY_ENV: &str = "FRESH_POST_SECRET_KEY";
+
+/// Path to a file holding the base64-encoded key (e.g. a Docker/Kubernetes secret mount)
+pub const KEY_FILE_ENV: &str = "FRESH_POST_SECRET_KEY_FILE";
+
+/// Secret name the LinkedIn cookie file is stored under
+pub const COOKIES_SECRET: &str = "linkedin_cookies";
+
+/// Prefix for secret references in config values
+pub const SECRET_REF_PREFIX: &str = "secret:";
+
+pub const REDACTED: &str = "[REDACTED]";
+
+const NONCE_LEN: usize = 24;
+
+/// Key used to encrypt secrets at rest
+pub struct SecretKey(Key);
+
+impl SecretKey {
+    /// Read the key from `FRESH_POST_SECRET_KEY`, then `FRESH_POST_SECRET_KEY_FILE`.
+    /// Returns `None` when neither is set (secrets stay unencrypted).
+    pub fn from_env() -> Result<Option<Self>> {
+        if let Ok(encoded) = std::env::var(KEY_ENV) {
+            return Self::from_base64(&encoded)
+                .with_context(|| format!("Invalid {}", KEY_ENV))
+                .map(Some);
+        }
+
+        if let Ok(key_file) = std::env::var(KEY_FILE_ENV) {
+            let encoded = std::fs::read_to_string(&key_file)
+                .with_context(|| format!("Failed to read secret key file: {}", key_file))?;
+            return Self::from_base64(&encoded)
+                .with_context(|| format!("Invalid secret key in {}", key_file))
+                .map(Some);
+        }
+
+        Ok(None)
+    }
+
+    pub fn from_base64(encoded: &str) -> Result<Self> {
+        let bytes = BASE64
+            .decode(encoded.trim())
+            .context("Secret key is not valid base64")?;
+        if bytes.len() != 32 {
+            anyhow::bail!("Secret key must be 32 bytes, got {}", bytes.len());
+        }
+        Ok(Self(*Key::from_slice(&bytes)))
+    }
+
+    pub fn generate() -> Self {
+        Self(XChaCha20Poly1305::generate_key(&mut OsRng))
+    }
+
+    pub fn to_base64(&self) -> String {
+        BASE64.encode(self.0.as_slice())
+    }
+}
+
+impl fmt::Debug for SecretKey {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "SecretKey({})", REDACTED)
+    }
+}
+
+/// A decrypted secret. Prints as `[REDACTED]` so it can't leak through logs.
+#[derive(Clone, PartialEq, Eq)]
+pub struct SecretValue(String);
+
+impl SecretValue {
+    pub fn new(value: impl Into<String>) -> Self {
+        Self(value.into())
+    }
+
+    pub fn expose(&self) -> &str {
+        &self.0
+    }
+}
+
+impl fmt::Debug for SecretValue {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(REDACTED)
+    }
+}
+
+impl fmt::Display for SecretValue {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(REDACTED)
+    }
+}
+
+/// Reference to a stored secret in config, written as `"secret:<name>"`.
+/// Config only ever holds the name; the value is resolved from the store.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(try_from = "String", into = "String")]
+pub struct SecretRef {
+    name: String,
+}
+
+impl SecretRef {
+    pub fn new(name: impl Into<String>) -> Self {
+        Self { name: name.into() }
+    }
+
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+}
+
+impl TryFrom<String> for SecretRef {
+    type Error = String;
+
+    fn try_from(value: String) -> Result<Self, Self::Error> {
+        match value.strip_prefix(SECRET_REF_PREFIX) {
+            Some(name) if !name.trim().is_empty() => Ok(Self::new(name.trim())),
+            _ => Err(format!(
+                "expected a secret reference like \"{}name\", not a literal value",
+                SECRET_REF_PREFIX
+            )),
+        }
+    }
+}
+
+impl From<SecretRef> for String {
+    fn from(secret: SecretRef) -> Self {
+        format!("{}{}", SECRET_REF_PREFIX, secret.name)
+    }
+}
+
+#[derive(Debug, Default, Serialize, Deserialize)]
+struct SecretFile {
+    /// name -> base64(nonce || ciphertext)
+    secrets: BTreeMap<String, String>,
+}
+
+/// Encrypted name/value store persisted as JSON in the data directory
+#[derive(Debug)]
+pub struct SecretStore {
+    path: PathBuf,
+    key: SecretKey,
+}
+
+impl SecretStore {
+    pub fn new(path: PathBuf, key: SecretKey) -> Self {
+        Self { path, key }
+    }
+
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    fn cipher(&self) -> XChaCha20Poly1305 {
+        XChaCha20Poly1305::new(&self.key.0)
+    }
+
+    fn load(&self) -> Result<SecretFile> {
+        if !self.path.exists() {
+            return Ok(SecretFile::default());
+        }
+
+        let content = std::fs::read_to_string(&self.path)
+            .with_context(|| format!("Failed to read secrets file: {:?}", self.path))?;
+
+        serde_json::from_str(&content).context("Failed to parse secrets file")
+    }
+
+    fn save(&self, file: &SecretFile) -> Result<()> {
+        let content = serde_json::to_string_pretty(file)
+            .context("Failed to serialize secrets")?;
+
+        crate::storage::write_atomic(&self.path, content.as_bytes())
+    }
+
+    pub fn get(&self, name: &str) -> Result<Option<SecretValue>> {
+        let file = self.load()?;
+        let Some(encoded) = file.secrets.get(name) else {
+            return Ok(None);
+        };
+
+        let bytes = BASE64
+            .decode(encoded)
+            .with_context(|| format!("Secret '{}' is not valid base64", name))?;
+        if bytes.len() < NONCE_LEN {
+            anyhow::bail!("Secret '{}' is truncated", name);
+        }
+
+        let (nonce, ciphertext) = bytes.split_at(NONCE_LEN);
+        let plaintext = self
+            .cipher()
+            .decrypt(XNonce::from_slice(nonce), Payload { msg: ciphertext, aad: name.as_bytes() })
+            .map_err(|_| anyhow::anyhow!("Failed to decrypt secret '{}' (wrong key?)", name))?;
+
+        let value = String::from_utf8(plaintext)
+            .with_context(|| format!("Secret '{}' is not valid UTF-8", name))?;
+
+        Ok(Some(SecretValue(value)))
+    }
+
+    /// Held across each load-modify-save, so concurrent writers don't drop each other's secrets
+    fn write_lock() -> MutexGuard<'static, ()> {
+        static LOCK: Mutex<()> = Mutex::new(());
+        LOCK.lock().unwrap_or_else(|poisoned| poisoned.into_inner())
+    }
+
+    pub fn set(&self, name: &str, value: &str) -> Result<()> {
+        let nonce = XChaCha20Poly1305::generate_nonce(&mut OsRng);
+        let ciphertext = self
+            .cipher()
+            .encrypt(&nonce, Payload { msg: value.as_bytes(), aad: name.as_bytes() })
+            .map_err(|_| anyhow::anyhow!("Failed to encrypt secret '{}'", name))?;
+
+        let mut bytes = nonce.to_vec();
+        bytes.extend_from_slice(&ciphertext);
+
+        let _lock = Self::write_lock();
+        let mut file = self.load()?;
+        file.secrets.insert(name.to_string(), BASE64.encode(bytes));
+        self.save(&file)
+    }
+
+    pub fn remove(&self, name: &str) -> Result<bool> {
+        let _lock = Self::write_lock();
+        let mut file = self.load()?;
+        let removed = file.secrets.remove(name).is_some();
+        if removed {
+            self.save(&file)?;
+        }
+        Ok(removed)
+    }
+
+    pub fn names(&self) -> Result<Vec<String>> {
+        Ok(self.load()?.secrets.into_keys().collect())
+    }
+
+    pub fn resolve(&self, secret: &SecretRef) -> Result<SecretValue> {
+        self.get(secret.name())?
+            .with_context(|| format!("Secret '{}' is not set", secret.name()))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    fn store(dir: &TempDir) -> SecretStore {
+        SecretStore::new(dir.path().join("secrets.json"), SecretKey::generate())
+    }
+
+    #[test]
+    fn test_set_and_get_roundtrip() {
+        let dir = TempDir::new().unwrap();
+        let store = store(&dir);
+
+        store.set("bot_token", "hunter2").unwrap();
+        assert_eq!(store.get("bot_token").unwrap().unwrap().expose(), "hunter2");
+        assert!(store.get("missing").unwrap().is_none());
+        assert_eq!(store.names().unwrap(), vec!["bot_token".to_string()]);
+
+        let on_disk = std::fs::read_to_string(store.path()).unwrap();
+        assert!(!on_disk.contains("hunter2"));
+    }
+
+    #[test]
+    fn test_concurrent_writers_keep_every_secret() {
+        let dir = TempDir::new().unwrap();
+        let key = SecretKey::generate();
+        let encoded = key.to_base64();
+        let path = dir.path().join("secrets.json");
+        let writers: Vec<_> = (0..8)
+            .map(|index| {
+                let store = SecretStore::new(path.clone(), SecretKey::from_base64(&encoded).unwrap());
+                std::thread::spawn(move || store.set(&format!("secret_{}", index), "value").unwrap())
+            })
+            .collect();
+        for writer in writers {
+            writer.join().unwrap();
+        }
+        assert_eq!(SecretStore::new(path, key).names().unwrap().len(), 8);
+    }
+
+    #[test]
+    fn test_wrong_key_fails() {
+        let dir = TempDir::new().unwrap();
+        store(&dir).set("token", "value").unwrap();
+
+        let other = store(&dir);
+        assert!(other.get("token").is_err());
+    }
+
+    #[test]
+    fn test_remove() {
+        let dir = TempDir::new().unwrap();
+        let store = store(&dir);
+        store.set("token", "value").unwrap();
+        assert!(store.remove("token").unwrap());
+        assert!(!store.remove("token").unwrap());
+        assert!(store.get("token").unwrap().is_none());
+    }
+
+    #[test]
+    fn test_key_base64_roundtrip() {
+        let key = SecretKey::generate();
+        let decoded = SecretKey::from_base64(&key.to_base64()).unwrap();
+        assert_eq!(key.0, decoded.0);
+        assert!(SecretKey::from_base64("c2hvcnQ=").is_err());
+    }
+
+    #[test]
+    fn test_secret_value_is_redacted() {
+        let value = SecretValue::new("hunter2");
+        assert_eq!(format!("{}", value), REDACTED);
+        assert_eq!(format!("{:?}", value), REDACTED);
+    }
+
+    #[test]
+    fn test_secret_ref_parsing() {
+        let secret: SecretRef = serde_json::from_str(r#""secret:smtp_password""#).unwrap();
+        assert_eq!(secret.name(), "smtp_password");
+        assert_eq!(serde_json::to_string(&secret).unwrap(), r#""secret:smtp_password""#);
+        assert!(serde_json::from_str::<SecretRef>(r#""plaintext""#).is_err());
+    }
+}
@@ -0,0 +1,203 @@
+use anyhow::{Context, Result};
+use scraper::Selector;
+use serde::{Deserialize, Serialize};
+use std::path::Path;
+
+/// CSS selectors used to pull job cards out of the guest (HTML) search page.
+/// Each field is a list of fallbacks: the first selector that matches wins.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SelectorProfile {
+    pub card: Vec<String>,
+    pub title: Vec<String>,
+    pub company: Vec<String>,
+    pub location: Vec<String>,
+    pub link: Vec<String>,
+    pub date: Vec<String>,
+}
+
+/// Partial profile read from `selectors.toml`; fields left out keep the built-in selectors
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct SelectorOverrides {
+    pub card: Option<Vec<String>>,
+    pub title: Option<Vec<String>>,
+    pub company: Option<Vec<String>>,
+    pub location: Option<Vec<String>>,
+    pub link: Option<Vec<String>>,
+    pub date: Option<Vec<String>>,
+}
+
+fn strings(selectors: &[&str]) -> Vec<String> {
+    selectors.iter().map(|s| s.to_string()).collect()
+}
+
+impl Default for SelectorProfile {
+    fn default() -> Self {
+        Self::builtin()
+    }
+}
+
+impl SelectorProfile {
+    /// Selectors for LinkedIn's current guest and logged-in job card markup
+    pub fn builtin() -> Self {
+        Self {
+            card: strings(&[".base-card", ".job-card-container", "[data-job-id]"]),
+            title: strings(&["h3.base-search-card__title", ".job-card-list__title", "a.job-card-list__title"]),
+            company: strings(&[
+                "h4.base-search-card__subtitle",
+                ".job-card-container__company-name",
+                "a.job-card-container__company-name",
+            ]),
+            location: strings(&[".job-search-card__location", ".job-card-container__metadata-item"]),
+            link: strings(&["a.base-card__full-link", "a.job-card-list__title"]),
+            date: strings(&[
+                "time[class*='job-search-card__listdate']",
+                "time.job-search-card__listdate",
+                ".job-search-card__listdate",
+                ".job-search-card__listdate--new",
+            ]),
+        }
+    }
+
+    /// Built-in profile with the fields from `overrides` replacing the defaults
+    pub fn with_overrides(overrides: SelectorOverrides) -> Self {
+        let builtin = Self::builtin();
+        Self {
+            card: overrides.card.unwrap_or(builtin.card),
+            title: overrides.title.unwrap_or(builtin.title),
+            company: overrides.company.unwrap_or(builtin.company),
+            location: overrides.location.unwrap_or(builtin.location),
+            link: overrides.link.unwrap_or(builtin.link),
+            date: overrides.date.unwrap_or(builtin.date),
+        }
+    }
+
+    /// Load the built-in profile, applying overrides from `path` if it exists
+    pub fn load_or_default<P: AsRef<Path>>(path: P) -> Result<Self> {
+        if !path.as_ref().exists() {
+            return Ok(Self::builtin());
+        }
+
+        let content = std::fs::read_to_string(path.as_ref())
+            .with_context(|| format!("Failed to read selector file: {:?}", path.as_ref()))?;
+
+        let overrides: SelectorOverrides = toml::from_str(&content)
+            .with_context(|| format!("Failed to parse selector file: {:?}", path.as_ref()))?;
+
+        Ok(Self::with_overrides(overrides))
+    }
+
+    fn fields(&self) -> [(&'static str, &Vec<String>); 6] {
+        [
+            ("card", &self.card),
+            ("title", &self.title),
+            ("company", &self.company),
+            ("location", &self.location),
+            ("link", &self.link),
+            ("date", &self.date),
+        ]
+    }
+
+    /// Parse every selector, reporting all invalid or empty fields at once
+    pub fn compile(&self) -> Result<CompiledSelectors> {
+        let mut errors = Vec::new();
+        let mut compiled: Vec<Vec<Selector>> = Vec::new();
+
+        for (field, selectors) in self.fields() {
+            if selectors.is_empty() {
+                errors.push(format!("{}: at least one selector is required", field));
+            }
+
+            let mut parsed = Vec::new();
+            for (index, selector) in selectors.iter().enumerate() {
+                match Selector::parse(selector) {
+                    Ok(selector) => parsed.push(selector),
+                    Err(err) => errors.push(format!("{}[{}] '{}': {:?}", field, index, selector, err)),
+                }
+            }
+            compiled.push(parsed);
+        }
+
+        if !errors.is_empty() {
+            anyhow::bail!("Invalid selector profile:\n  {}", errors.join("\n  "));
+        }
+
+        let mut compiled = compiled.into_iter();
+        let mut next = || compiled.next().unwrap_or_default();
+        Ok(CompiledSelectors {
+            card: next(),
+            title: next(),
+            company: next(),
+            location: next(),
+            link: next(),
+            date: next(),
+        })
+    }
+}
+
+/// A validated selector profile, ready for the parser
+#[derive(Debug, Clone)]
+pub struct CompiledSelectors {
+    pub card: Vec<Selector>,
+    pub title: Vec<Selector>,
+    pub company: Vec<Selector>,
+    pub location: Vec<Selector>,
+    pub link: Vec<Selector>,
+    pub date: Vec<Selector>,
+}
+
+impl CompiledSelectors {
+    pub fn builtin() -> Self {
+        SelectorProfile::builtin()
+            .compile()
+            .expect("built-in selector profile is valid")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_builtin_profile_compiles() {
+        let compiled = SelectorProfile::builtin().compile().unwrap();
+        assert_eq!(compiled.card.len(), 3);
+    }
+
+    #[test]
+    fn test_overrides_replace_only_given_fields() {
+        let temp_dir = TempDir::new().unwrap();
+        let path = temp_dir.path().join("selectors.toml");
+        std::fs::write(&path, "title = [\"h2.new-title\", \"h3.base-search-card__title\"]\n").unwrap();
+
+        let profile = SelectorProfile::load_or_default(&path).unwrap();
+        assert_eq!(profile.title, vec!["h2.new-title", "h3.base-search-card__title"]);
+        assert_eq!(profile.card, SelectorProfile::builtin().card);
+    }
+
+    #[test]
+    fn test_missing_file_uses_builtin() {
+        let temp_dir = TempDir::new().unwrap();
+        let profile = SelectorProfile::load_or_default(temp_dir.path().join("missing.toml")).unwrap();
+        assert_eq!(profile, SelectorProfile::builtin());
+    }
+
+    #[test]
+    fn test_invalid_selectors_reported_together() {
+        let overrides = SelectorOverrides {
+            title: Some(vec!["h3[".to_string()]),
+            link: Some(Vec::new()),
+            ..Default::default()
+        };
+
+        let err = SelectorProfile::with_overrides(overrides).compile().unwrap_err().to_string();
+        assert!(err.contains("title[0] 'h3['"));
+        assert!(err.contains("link: at least one selector is required"));
+    }
+
+    #[test]
+    fn test_unknown_field_rejected() {
+        assert!(toml::from_str::<SelectorOverrides>("titel = [\"h3\"]").is_err());
+    }
+}
@@ -0,0 +1,381 @@
+use crate::config::ServerConfig;
+use anyhow::{Context, Result};
+use axum::extract::Request;
+use axum::middleware::{self, Next};
+use axum::response::{IntoResponse, Redirect};
+use axum::Router;
+use hyper::server::conn::http1;
+use hyper_util::rt::TokioIo;
+use hyper_util::service::TowerToHyperService;
+use std::fmt;
+use std::net::{IpAddr, Ipv4Addr, Ipv6Addr, SocketAddr};
+use std::os::unix::fs::FileTypeExt;
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+use tokio::io::{AsyncRead, AsyncReadExt, AsyncWrite, AsyncWriteExt};
+use tokio::net::{TcpListener, TcpStream, UnixListener, UnixStream};
+use tokio_native_tls::{native_tls, TlsAcceptor, TlsConnector};
+use tower::Layer;
+use tracing::{debug, info, warn};
+
+/// Prefix marking a Unix socket in `server.listen`
+pub const UNIX_PREFIX: &str = "unix:";
+
+/// One entry of `server.listen`
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ListenAddr {
+    Tcp(SocketAddr),
+    Unix(PathBuf),
+}
+
+impl ListenAddr {
+    pub fn parse(value: &str) -> Result<Self> {
+        if let Some(path) = value.strip_prefix(UNIX_PREFIX) {
+            if path.is_empty() {
+                anyhow::bail!("Unix socket address needs a path, e.g. {}/run/fresh-post.sock", UNIX_PREFIX);
+            }
+            return Ok(ListenAddr::Unix(PathBuf::from(path)));
+        }
+        value
+            .parse()
+            .map(ListenAddr::Tcp)
+            .with_context(|| format!("Invalid listen address {:?}; expected ip:port or {}/path/to.sock", value, UNIX_PREFIX))
+    }
+}
+
+impl fmt::Display for ListenAddr {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ListenAddr::Tcp(addr) => write!(f, "{}", addr),
+            ListenAddr::Unix(path) => write!(f, "{}{}", UNIX_PREFIX, path.display()),
+        }
+    }
+}
+
+/// `"fresh-post/"` → `"/fresh-post"`; empty when served at the root
+pub fn normalize_base_path(value: &str) -> Result<String> {
+    let trimmed = value.trim().trim_matches('/');
+    if trimmed.is_empty() {
+        return Ok(String::new());
+    }
+    if trimmed.contains(['?', '#', ' ']) || trimmed.split('/').any(|segment| segment.is_empty() || segment == "..") {
+        anyhow::bail!("Invalid server.base_path {:?}; expected a path like /fresh-post", value);
+    }
+    Ok(format!("/{}", trimmed))
+}
+
+/// Whether TCP listeners serve HTTPS
+pub fn is_tls(config: &ServerConfig) -> bool {
+    config.tls_cert.is_some() && config.tls_key.is_some()
+}
+
+fn tls_acceptor(config: &ServerConfig) -> Result<Option<TlsAcceptor>> {
+    let (cert_path, key_path) = match (&config.tls_cert, &config.tls_key) {
+        (Some(cert), Some(key)) => (cert, key),
+        (None, None) => return Ok(None),
+        _ => anyhow::bail!("TLS needs both server.tls_cert and server.tls_key"),
+    };
+
+    let cert = std::fs::read(cert_path).with_context(|| format!("Failed to read TLS certificate: {:?}", cert_path))?;
+    let key = std::fs::read(key_path).with_context(|| format!("Failed to read TLS key: {:?}", key_path))?;
+    let identity = native_tls::Identity::from_pkcs8(&cert, &key)
+        .context("Invalid TLS certificate or key (expected PEM, with the key in PKCS#8)")?;
+    let acceptor = native_tls::TlsAcceptor::new(identity).context("Failed to set up TLS")?;
+    Ok(Some(TlsAcceptor::from(acceptor)))
+}
+
+/// Check listen addresses, base path and TLS files without binding anything
+pub fn validate(config: &ServerConfig) -> Result<()> {
+    if config.listen.is_empty() {
+        anyhow::bail!("server.listen is empty; nothing to serve on");
+    }
+    for addr in &config.listen {
+        ListenAddr::parse(addr)?;
+    }
+    normalize_base_path(&config.base_path)?;
+    tls_acceptor(config)?;
+    Ok(())
+}
+
+/// Mount `app` under `base_path` (already normalized). The bare prefix redirects
+/// to the trailing-slash form so the UI's relative links resolve inside it.
+pub fn with_base_path(app: Router, base_path: &str) -> Router {
+    if base_path.is_empty() {
+        return app;
+    }
+    let prefix = base_path.to_string();
+    // Wrapped around the router rather than added with `Router::layer`, which
+    // would run after routing and so too late to rewrite the path
+    let rewrite = middleware::from_fn(move |mut request: Request, next: Next| {
+        let prefix = prefix.clone();
+        async move {
+            let path = request.uri().path();
+            if path == prefix {
+                return Redirect::permanent(&format!("{}/", prefix)).into_response();
+            }
+            // The nested `/` route only matches the bare prefix
+            if path.strip_prefix(prefix.as_str()) == Some("/") {
+                let query = request.uri().query().map(|query| format!("?{}", query)).unwrap_or_default();
+                if let Ok(uri) = format!("{}{}", prefix, query).parse() {
+                    *request.uri_mut() = uri;
+                }
+            }
+            next.run(request).await
+        }
+    });
+    Router::new().fallback_service(rewrite.layer(Router::new().nest(base_path, app)))
+}
+
+/// Serve `app` on every configured address until one of the listeners fails
+pub async fn serve(app: Router, config: &ServerConfig) -> Result<()> {
+    let addrs = config
+        .listen
+        .iter()
+        .map(|addr| ListenAddr::parse(addr))
+        .collect::<Result<Vec<_>>>()?;
+    let tls = tls_acceptor(config)?;
+
+    let mut listeners = tokio::task::JoinSet::new();
+    for addr in addrs {
+        match &addr {
+            ListenAddr::Tcp(socket) => {
+                let listener = TcpListener::bind(socket)
+                    .await
+                    .with_context(|| format!("Failed to listen on {}", addr))?;
+                info!(%addr, tls = tls.is_some(), "server listening");
+                listeners.spawn(accept_tcp(listener, app.clone(), tls.clone()));
+            }
+            ListenAddr::Unix(path) => {
+                let listener = bind_unix(path).with_context(|| format!("Failed to listen on {}", addr))?;
+                info!(%addr, "server listening");
+                listeners.spawn(accept_unix(listener, app.clone()));
+            }
+        }
+    }
+
+    while let Some(result) = listeners.join_next().await {
+        result.context("Listener task panicked")??;
+    }
+    Ok(())
+}
+
+/// Bind a Unix socket, replacing a stale socket file left by a previous run
+fn bind_unix(path: &Path) -> Result<UnixListener> {
+    if let Ok(metadata) = std::fs::symlink_metadata(path) {
+        if !metadata.file_type().is_socket() {
+            anyhow::bail!("{:?} exists and is not a socket", path);
+        }
+        std::fs::remove_file(path).with_context(|| format!("Failed to remove stale socket: {:?}", path))?;
+    }
+    Ok(UnixListener::bind(path)?)
+}
+
+/// Pause after a failed accept (e.g. out of file descriptors) instead of spinning
+const ACCEPT_ERROR_DELAY: Duration = Duration::from_secs(1);
+
+async fn accept_tcp(listener: TcpListener, app: Router, tls: Option<TlsAcceptor>) -> Result<()> {
+    loop {
+        let stream = match listener.accept().await {
+            Ok((stream, _)) => stream,
+            Err(err) => {
+                warn!(%err, "failed to accept connection");
+                tokio::time::sleep(ACCEPT_ERROR_DELAY).await;
+                continue;
+            }
+        };
+
+        let app = app.clone();
+        let tls = tls.clone();
+        tokio::spawn(async move {
+            match tls {
+                Some(tls) => match tls.accept(stream).await {
+                    Ok(stream) => serve_connection(stream, app).await,
+                    Err(err) => debug!(%err, "TLS handshake failed"),
+                },
+                None => serve_connection(stream, app).await,
+            }
+        });
+    }
+}
+
+async fn accept_unix(listener: UnixListener, app: Router) -> Result<()> {
+    loop {
+        let stream = match listener.accept().await {
+            Ok((stream, _)) => stream,
+            Err(err) => {
+                warn!(%err, "failed to accept connection");
+                tokio::time::sleep(ACCEPT_ERROR_DELAY).await;
+                continue;
+            }
+        };
+        tokio::spawn(serve_connection(stream, app.clone()));
+    }
+}
+
+/// How long `check_health` waits for the server
+const HEALTH_TIMEOUT: Duration = Duration::from_secs(5);
+
+/// Ask the server configured in `config` for `/healthz` on its first listen
+/// address, the way a container healthcheck would
+pub async fn check_health(config: &ServerConfig) -> Result<()> {
+    let listen = config.listen.first().context("server.listen is empty; nothing to check")?;
+    let addr = ListenAddr::parse(listen)?;
+    let path = format!("{}/healthz", normalize_base_path(&config.base_path)?);
+
+    let status_line = tokio::time::timeout(HEALTH_TIMEOUT, async {
+        match &addr {
+            ListenAddr::Unix(socket) => {
+                let stream = UnixStream::connect(socket).await?;
+                get_status_line(stream, &path).await
+            }
+            ListenAddr::Tcp(socket) => {
+                // A wildcard listener is reached over loopback
+                let mut target = *socket;
+                if target.ip().is_unspecified() {
+                    target.set_ip(match target.ip() {
+                        IpAddr::V4(_) => Ipv4Addr::LOCALHOST.into(),
+                        IpAddr::V6(_) => Ipv6Addr::LOCALHOST.into(),
+                    });
+                }
+                let stream = TcpStream::connect(target).await?;
+                if !is_tls(config) {
+                    return get_status_line(stream, &path).await;
+                }
+                // The certificate is for the public name, not loopback
+                let connector = native_tls::TlsConnector::builder()
+                    .danger_accept_invalid_certs(true)
+                    .danger_accept_invalid_hostnames(true)
+                    .build()?;
+                let stream = TlsConnector::from(connector).connect("localhost", stream).await?;
+                get_status_line(stream, &path).await
+            }
+        }
+    })
+    .await
+    .with_context(|| format!("{} did not answer within {:?}", addr, HEALTH_TIMEOUT))?
+    .with_context(|| format!("Failed to reach {}", addr))?;
+
+    if status_line.split_whitespace().nth(1) != Some("200") {
+        anyhow::bail!("{}{} answered {:?}", addr, path, status_line);
+    }
+    Ok(())
+}
+
+async fn get_status_line<S>(mut stream: S, path: &str) -> Result<String>
+where
+    S: AsyncRead + AsyncWrite + Unpin,
+{
+    let request = format!("GET {} HTTP/1.1\r\nHost: localhost\r\nConnection: close\r\n\r\n", path);
+    stream.write_all(request.as_bytes()).await?;
+    let mut response = Vec::new();
+    stream.read_to_end(&mut response).await?;
+    let response = String::from_utf8_lossy(&response);
+    Ok(response.lines().next().unwrap_or_default().to_string())
+}
+
+async fn serve_connection<S>(stream: S, app: Router)
+where
+    S: AsyncRead + AsyncWrite + Unpin + Send + 'static,
+{
+    let service = TowerToHyperService::new(app);
+    if let Err(err) = http1::Builder::new()
+        .serve_connection(TokioIo::new(stream), service)
+        .with_upgrades()
+        .await
+    {
+        debug!(%err, "connection closed with error");
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use axum::routing::get;
+
+    #[test]
+    fn test_parse_listen_addr() {
+        assert_eq!(
+            ListenAddr::parse("127.0.0.1:8080").unwrap(),
+            ListenAddr::Tcp("127.0.0.1:8080".parse().unwrap())
+        );
+        assert_eq!(ListenAddr::parse("[::]:443").unwrap().to_string(), "[::]:443");
+        assert_eq!(
+            ListenAddr::parse("unix:/run/fresh-post.sock").unwrap(),
+            ListenAddr::Unix(PathBuf::from("/run/fresh-post.sock"))
+        );
+        assert!(ListenAddr::parse("unix:").is_err());
+        assert!(ListenAddr::parse("localhost").is_err());
+    }
+
+    #[test]
+    fn test_normalize_base_path() {
+        assert_eq!(normalize_base_path("").unwrap(), "");
+        assert_eq!(normalize_base_path("/").unwrap(), "");
+        assert_eq!(normalize_base_path("/fresh-post/").unwrap(), "/fresh-post");
+        assert_eq!(normalize_base_path("apps/fresh-post").unwrap(), "/apps/fresh-post");
+        assert!(normalize_base_path("/a//b").is_err());
+        assert!(normalize_base_path("/a?b").is_err());
+    }
+
+    #[test]
+    fn test_validate_tls_needs_both_files() {
+        let config = ServerConfig {
+            tls_cert: Some(PathBuf::from("cert.pem")),
+            ..ServerConfig::default()
+        };
+        assert!(validate(&config).is_err());
+        assert!(validate(&ServerConfig::default()).is_ok());
+    }
+
+    async fn request(socket: &Path, path: &str) -> String {
+        let mut stream = UnixStream::connect(socket).await.unwrap();
+        let request = format!("GET {} HTTP/1.1\r\nHost: localhost\r\nConnection: close\r\n\r\n", path);
+        stream.write_all(request.as_bytes()).await.unwrap();
+        let mut response = String::new();
+        stream.read_to_string(&mut response).await.unwrap();
+        response
+    }
+
+    #[tokio::test]
+    async fn test_serves_unix_socket_under_base_path() {
+        let dir = tempfile::tempdir().unwrap();
+        let socket = dir.path().join("fresh-post.sock");
+        let config = ServerConfig {
+            listen: vec![format!("{}{}", UNIX_PREFIX, socket.display())],
+            base_path: "/fresh-post".to_string(),
+            ..ServerConfig::default()
+        };
+
+        let app = Router::new()
+            .route("/", get(|| async { "index" }))
+            .route("/healthz", get(|| async { "ok" }));
+        let app = with_base_path(app, &normalize_base_path(&config.base_path).unwrap());
+        let served = config.clone();
+        tokio::spawn(async move { serve(app, &served).await });
+        for _ in 0..50 {
+            if socket.exists() {
+                break;
+            }
+            tokio::time::sleep(Duration::from_millis(10)).await;
+        }
+
+        let response = request(&socket, "/fresh-post/healthz").await;
+        assert!(response.starts_with("HTTP/1.1 200"), "{}", response);
+        assert!(response.ends_with("ok"));
+
+        assert!(request(&socket, "/fresh-post/").await.ends_with("index"));
+
+        let response = request(&socket, "/fresh-post").await;
+        assert!(response.starts_with("HTTP/1.1 308"), "{}", response);
+        assert!(response.contains("location: /fresh-post/"));
+
+        assert!(request(&socket, "/healthz").await.starts_with("HTTP/1.1 404"));
+
+        check_health(&config).await.unwrap();
+        let without_prefix = ServerConfig {
+            base_path: String::new(),
+            ..config
+        };
+        assert!(check_health(&without_prefix).await.is_err());
+    }
+}
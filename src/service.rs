@@ -1,80 +1,353 @@
-use crate::{filters, scraper, storage};
+use crate::config::{Config, ProxyConfig};
+use crate::diagnostics::{self, ParseAnomaly, ParseDiagnostics};
+use crate::fingerprint::{self, BrowserProfile};
+use crate::metrics::{Metrics, RunOutcome, SearchCounts};
+use crate::models::JobPosting;
+use crate::proxy::{self, ProxyFailure, ProxyPool};
+use crate::ratelimit::RateLimiter;
+use crate::replay::Recording;
+use crate::scraper::{FetchedPage, ProxySettings};
+use crate::session::{self, PageSession, SessionStatus};
+use crate::{archive, cookies, filters, scraper, storage};
 use anyhow::{Context, Result};
 use chrono::{DateTime, Local};
+use reqwest::cookie::Jar;
+use std::collections::{HashMap, HashSet};
+use std::path::PathBuf;
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+use tracing::{info, info_span, warn, Instrument};
 
 #[derive(Debug, Clone, serde::Serialize, serde::Deserialize)]
 pub struct ScrapeSummary {
+    pub run_id: String,
     pub total_jobs: usize,
     pub today_jobs: usize,
     pub new_jobs: usize,
+    pub duplicate_jobs: usize,
+    pub pages_fetched: usize,
+    pub session_status: SessionStatus,
+    /// Browser header profile the run presented
+    pub browser_profile: String,
+    pub parse_anomalies: Vec<ParseAnomaly>,
     pub updated_at: DateTime<Local>,
     pub search_url: String,
 }
 
+/// Timestamp-prefixed id for a scrape run, so ids sort chronologically
+pub fn new_run_id() -> String {
+    Local::now().format("%Y%m%d-%H%M%S-%3f").to_string()
+}
+
+/// Where and how a scrape fetches pages. The default goes to linkedin.com;
+/// tests point `base_url` at a local replay server.
+#[derive(Debug, Clone)]
+pub struct ScrapeEnv {
+    pub base_url: String,
+    /// Client to use instead of one built from the stored cookies
+    pub client: Option<reqwest::Client>,
+    /// Limiter every request waits for, configured from `[rate_limit]` at the start
+    /// of each run; `None` sends requests unthrottled
+    pub rate_limiter: Option<Arc<RateLimiter>>,
+    /// Record every fetched page into this directory for later replay
+    pub record_dir: Option<PathBuf>,
+    /// Proxies to rotate through, configured from `[proxy]` at the start of each run
+    pub proxies: Arc<ProxyPool>,
+    /// Config to scrape with instead of reading `config.toml`; the server passes the live one
+    pub config: Option<Arc<Config>>,
+}
+
+impl Default for ScrapeEnv {
+    fn default() -> Self {
+        Self {
+            base_url: scraper::LINKEDIN_BASE_URL.to_string(),
+            client: None,
+            rate_limiter: Some(RateLimiter::shared()),
+            record_dir: None,
+            proxies: ProxyPool::shared(),
+            config: None,
+        }
+    }
+}
+
 pub async fn run_scrape_once(paths: &storage::Paths) -> Result<ScrapeSummary> {
-    let config = storage::load_config_or_default(paths)
-        .context("Failed to load config")?;
-
-    let search_url = scraper::build_search_url(
-        &config.search.keywords,
-        &config.search.location,
-        config.search.remote,
-        config.search.salary_min,
-    );
+    run_scrape_with(paths, &ScrapeEnv::default()).await
+}
+
+/// Scrape with `config` rather than what is in `config.toml`
+pub async fn run_scrape_with_config(paths: &storage::Paths, config: Arc<Config>) -> Result<ScrapeSummary> {
+    let env = ScrapeEnv {
+        config: Some(config),
+        ..ScrapeEnv::default()
+    };
+    run_scrape_with(paths, &env).await
+}
 
-    let client = scraper::create_client()?;
+pub async fn run_scrape_with(paths: &storage::Paths, env: &ScrapeEnv) -> Result<ScrapeSummary> {
+    let run_id = new_run_id();
+    let span = info_span!("run", run_id = %run_id);
+    let started = Instant::now();
+    let result = scrape(paths, env, run_id).instrument(span).await;
 
-    let cookies = if storage::file_exists(&paths.cookies) {
-        Some(scraper::load_cookies_from_file(
-            paths.cookies.to_string_lossy().as_ref(),
-        )?)
-    } else {
-        None
+    let outcome = if result.is_ok() { RunOutcome::Success } else { RunOutcome::Failure };
+    Metrics::global().record_run(outcome, started.elapsed());
+    result
+}
+
+async fn scrape(paths: &storage::Paths, env: &ScrapeEnv, run_id: String) -> Result<ScrapeSummary> {
+    let config = match &env.config {
+        Some(config) => config.clone(),
+        None => Arc::new(storage::load_config(paths).context("Failed to load config")?),
+    };
+
+    let search_url = scraper::build_search_url_with_base(&env.base_url, &config.search)?;
+
+    let cookies = match storage::load_cookie_content(paths).context("Failed to load cookies")? {
+        Some(content) => {
+            let stored = cookies::parse_cookies(&content)
+                .context("Failed to parse cookies")?;
+            info!(count = stored.len(), "loaded cookies");
+            Some(stored)
+        }
+        None => None,
     };
 
-    let html = if let Some(cookie_header) = &cookies {
-        scraper::fetch_jobs_page_with_cookies(&client, &search_url, Some(cookie_header))
-            .await
-            .context("Failed to fetch jobs page")?
-    } else {
-        scraper::fetch_jobs_page(&client, &search_url)
-            .await
-            .context("Failed to fetch jobs page")?
+    env.proxies
+        .configure(&config.proxy.urls)
+        .context("Invalid proxy configuration")?;
+    if let Some(limiter) = &env.rate_limiter {
+        limiter.configure(&config.rate_limit);
+    }
+    let session_key = cookies.as_deref().and_then(fingerprint::session_key);
+    let pin = storage::load_profile_pin(paths).context("Failed to load browser profile pin")?;
+    let browser = fingerprint::choose_profile(&config.browser, session_key.as_deref(), pin.as_ref())
+        .context("Invalid browser configuration")?;
+    if let Some(new_pin) = &browser.new_pin {
+        storage::save_profile_pin(paths, new_pin).context("Failed to save browser profile pin")?;
+    }
+
+    let mut clients = Clients {
+        jar: cookies::build_cookie_jar(cookies.as_deref().unwrap_or_default()),
+        profile: browser.profile,
+        fixed: env.client.clone(),
+        by_proxy: HashMap::new(),
+        proxy_config: &config.proxy,
     };
 
-    std::fs::write(&paths.debug_html, &html)
-        .with_context(|| format!("Failed to save debug HTML: {:?}", paths.debug_html))?;
+    let selectors = storage::load_selector_profile(paths)
+        .context("Failed to load selector profile")?
+        .compile()?;
+
+    let mut pages = Vec::new();
+    let mut all_jobs: Vec<JobPosting> = Vec::new();
+    let mut parse_diagnostics: Option<ParseDiagnostics> = None;
+    let mut page_session = None;
+
+    let search_span = info_span!(
+        "search",
+        keywords = %config.search.keywords,
+        location = %config.search.location,
+        profile = browser.profile.name
+    );
+    async {
+        for page_index in 0..config.search.max_pages.max(1) {
+            let url = scraper::page_url(&search_url, page_index);
+            let page = fetch_page(&mut clients, &env.proxies, &url, env.rate_limiter.as_deref())
+                .await
+                .with_context(|| format!("Failed to fetch jobs page {}", page_index + 1))?;
+
+            if let Some(dir) = &env.record_dir {
+                Recording::record_response(dir, &url, &page.final_url, page.status, &page.html)
+                    .context("Failed to record response")?;
+            }
+
+            let (jobs, page_diagnostics) = scraper::parse_jobs_with_diagnostics(&page.html, &selectors)
+                .context("Failed to parse jobs from HTML")?;
+            Metrics::global().record_parse_path(page_diagnostics.path.as_str());
+            match &mut parse_diagnostics {
+                Some(diagnostics) => diagnostics.merge(&page_diagnostics),
+                None => parse_diagnostics = Some(page_diagnostics),
+            }
+
+            let first_page_session = *page_session
+                .get_or_insert_with(|| session::detect_page_session(&page.final_url, &page.html));
+            let last_page = jobs.is_empty() || first_page_session == PageSession::LoginWall;
 
-    let all_jobs = scraper::parse_jobs_from_html(&html)
-        .context("Failed to parse jobs from HTML")?;
+            all_jobs.extend(jobs);
+            pages.push(page);
+            if last_page {
+                break;
+            }
+        }
+        Ok::<_, anyhow::Error>(())
+    }
+    .instrument(search_span)
+    .await?;
 
+    // The same posting can show up on two pages while results shift
+    let mut seen_ids = HashSet::new();
+    all_jobs.retain(|job| seen_ids.insert(job.id.clone()));
+
+    let html = join_pages(&pages);
+
+    archive::save_run_html(&paths.archive, &run_id, &html, &config.archive)
+        .context("Failed to archive run HTML")?;
+
+    let session_report = session::evaluate(cookies.as_deref(), page_session, chrono::Utc::now());
+    storage::save_session_report(paths, &session_report)
+        .context("Failed to save session report")?;
+
+    let parse_diagnostics = parse_diagnostics.context("No pages fetched")?;
+
+    let parse_history = storage::load_parse_history(paths)
+        .context("Failed to load parse history")?;
+    let mut parse_health = diagnostics::assess(parse_diagnostics, &parse_history);
+    if !parse_health.is_healthy() {
+        let saved = storage::save_anomaly_html(paths, &html, parse_health.checked_at)
+            .context("Failed to save anomalous HTML")?;
+        parse_health.saved_html = Some(saved);
+    }
+    storage::append_parse_history(paths, &parse_health)
+        .context("Failed to save parse history")?;
+
+    let parsed_count = all_jobs.len();
     let today_jobs = filters::filter_today_only(all_jobs);
 
     let mut app_state = storage::load_state(paths)
         .context("Failed to load state")?;
 
-    let new_jobs = app_state.filter_new_jobs(&today_jobs);
+    let classification = app_state.classify_jobs(&today_jobs);
+    let new_jobs = classification.new_jobs;
+    let duplicates = classification.duplicates;
 
     app_state.mark_jobs_seen(&new_jobs);
+    app_state.mark_duplicates_seen(&duplicates);
     storage::save_state(paths, &app_state)
         .context("Failed to save state")?;
 
+    let metrics = Metrics::global();
+    metrics.record_search(
+        &format!("{} @ {}", config.search.keywords, config.search.location),
+        SearchCounts {
+            parsed: parsed_count,
+            today: today_jobs.len(),
+            new: new_jobs.len(),
+        },
+    );
+    metrics.set_seen_jobs(app_state.seen_count());
+
     let updated_at = Local::now();
 
     let snapshot = storage::JobsSnapshot {
         updated_at: Some(updated_at),
         jobs: today_jobs.clone(),
         new_jobs: new_jobs.clone(),
+        duplicates,
     };
 
     storage::save_latest_jobs(paths, &snapshot)
         .context("Failed to save latest jobs")?;
 
     Ok(ScrapeSummary {
+        run_id,
         total_jobs: snapshot.jobs.len(),
         today_jobs: snapshot.jobs.len(),
         new_jobs: snapshot.new_jobs.len(),
+        duplicate_jobs: snapshot.duplicates.len(),
+        pages_fetched: pages.len(),
+        session_status: session_report.status,
+        browser_profile: browser.profile.name.to_string(),
+        parse_anomalies: parse_health.anomalies,
         updated_at,
         search_url,
     })
 }
+
+/// HTTP clients of a run: one per proxy, all sharing the run's cookie jar
+struct Clients<'a> {
+    jar: Arc<Jar>,
+    profile: &'static BrowserProfile,
+    /// Injected client that bypasses proxies
+    fixed: Option<reqwest::Client>,
+    by_proxy: HashMap<Option<String>, reqwest::Client>,
+    proxy_config: &'a ProxyConfig,
+}
+
+impl Clients<'_> {
+    fn get(&mut self, proxy: Option<&str>) -> Result<reqwest::Client> {
+        if let Some(client) = &self.fixed {
+            return Ok(client.clone());
+        }
+
+        let key = proxy.map(str::to_string);
+        if let Some(client) = self.by_proxy.get(&key) {
+            return Ok(client.clone());
+        }
+
+        let settings = proxy.map(|url| ProxySettings {
+            url: url.to_string(),
+            timeout: Duration::from_secs(self.proxy_config.timeout_secs),
+        });
+        let client = scraper::create_client_with_jar(self.jar.clone(), self.profile, settings.as_ref())?;
+        self.by_proxy.insert(key, client.clone());
+        Ok(client)
+    }
+}
+
+/// Fetch `url`, rotating to the next proxy when one is blocked or times out.
+/// Each configured proxy is tried at most once per page.
+async fn fetch_page(
+    clients: &mut Clients<'_>,
+    proxies: &ProxyPool,
+    url: &str,
+    limiter: Option<&RateLimiter>,
+) -> Result<FetchedPage> {
+    let attempts = if clients.fixed.is_some() { 1 } else { proxies.len().max(1) };
+    let cooldown = Duration::from_secs(clients.proxy_config.cooldown_minutes * 60);
+
+    let mut attempt = 1;
+    loop {
+        let proxy = if clients.fixed.is_some() { None } else { proxies.next_proxy() };
+        let client = clients.get(proxy.as_deref())?;
+
+        let err = match scraper::fetch_jobs_page_with(&client, url, limiter).await {
+            Ok(page) => {
+                if let Some(proxy) = &proxy {
+                    proxies.report_success(proxy);
+                }
+                return Ok(page);
+            }
+            Err(err) => err,
+        };
+
+        let (Some(proxy), Some(failure)) = (&proxy, ProxyFailure::classify(&err)) else {
+            return Err(err);
+        };
+        proxies.report_failure(proxy, failure, cooldown);
+        warn!(
+            proxy = %proxy::redact_proxy_url(proxy),
+            ?failure,
+            cooldown_minutes = clients.proxy_config.cooldown_minutes,
+            "proxy failed, taking it out of rotation"
+        );
+
+        if attempt >= attempts {
+            return Err(err);
+        }
+        attempt += 1;
+    }
+}
+
+/// All fetched pages of a run as one document, for the debug file and archive
+fn join_pages(pages: &[FetchedPage]) -> String {
+    if let [page] = pages {
+        return page.html.clone();
+    }
+
+    pages
+        .iter()
+        .enumerate()
+        .map(|(index, page)| format!("<!-- fresh-post page {}: {} -->\n{}", index + 1, page.final_url, page.html))
+        .collect::<Vec<_>>()
+        .join("\n")
+}
@@ -0,0 +1,199 @@
+use crate::cookies::{self, StoredCookie};
+use crate::storage;
+use anyhow::{Context, Result};
+use chrono::{DateTime, Local, Utc};
+use serde::{Deserialize, Serialize};
+
+/// LinkedIn's authenticated session cookie
+pub const SESSION_COOKIE: &str = "li_at";
+
+/// Start warning this many days before the session cookie expires
+pub const EXPIRY_WARNING_DAYS: i64 = 7;
+
+/// URL paths LinkedIn redirects to when the session is missing or rejected
+const LOGIN_PATHS: &[&str] = &["/authwall", "/login", "/uas/login", "/checkpoint", "/signup"];
+
+/// Markers that only appear on the logged-in (voyager) job search page
+const LOGGED_IN_MARKERS: &[&str] = &["jobPostingTitle", "JobPostingCard", "voyager", "global-nav__me"];
+
+/// Markers of the login wall served in place of the requested page
+const LOGIN_WALL_MARKERS: &[&str] = &["authwall", "join-form", "login__form", "sign-in-form__submit-btn"];
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum SessionStatus {
+    Valid,
+    Expiring,
+    Expired,
+    Absent,
+}
+
+/// What kind of page LinkedIn actually served
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum PageSession {
+    LoggedIn,
+    Guest,
+    LoginWall,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SessionReport {
+    pub status: SessionStatus,
+    /// Expiry of the `li_at` cookie, when the cookie file records one
+    pub expires_at: Option<DateTime<Utc>>,
+    /// Page type seen on the last scrape
+    pub page: Option<PageSession>,
+    pub checked_at: DateTime<Local>,
+    pub message: String,
+}
+
+/// Classify a fetched page by its final URL (after redirects) and content
+pub fn detect_page_session(final_url: &str, html: &str) -> PageSession {
+    let redirected_to_login = url::Url::parse(final_url)
+        .map(|url| LOGIN_PATHS.iter().any(|path| url.path().starts_with(path)))
+        .unwrap_or(false);
+
+    if redirected_to_login {
+        return PageSession::LoginWall;
+    }
+
+    if LOGGED_IN_MARKERS.iter().any(|marker| html.contains(marker)) {
+        return PageSession::LoggedIn;
+    }
+
+    if LOGIN_WALL_MARKERS.iter().any(|marker| html.contains(marker)) && !html.contains("base-card") {
+        return PageSession::LoginWall;
+    }
+
+    PageSession::Guest
+}
+
+/// Work out the session status from the loaded cookies and, if a page was
+/// fetched with them, what LinkedIn served
+pub fn evaluate(cookies: Option<&[StoredCookie]>, page: Option<PageSession>, now: DateTime<Utc>) -> SessionReport {
+    let session_cookie = cookies
+        .unwrap_or_default()
+        .iter()
+        .find(|cookie| cookie.name == SESSION_COOKIE && !cookie.value.is_empty());
+
+    let expires_at = session_cookie.and_then(|cookie| cookie.expires);
+
+    let (status, message) = match session_cookie {
+        None => (
+            SessionStatus::Absent,
+            format!("No {} cookie found; scraping the guest view", SESSION_COOKIE),
+        ),
+        Some(cookie) if cookie.is_expired(now) => (
+            SessionStatus::Expired,
+            format!("The {} cookie expired; export fresh cookies", SESSION_COOKIE),
+        ),
+        Some(_) if page == Some(PageSession::LoginWall) => (
+            SessionStatus::Expired,
+            "LinkedIn served a login wall; the session was rejected".to_string(),
+        ),
+        Some(_) if page == Some(PageSession::Guest) => (
+            SessionStatus::Expired,
+            "LinkedIn served the guest view despite session cookies".to_string(),
+        ),
+        Some(_) => match expires_at {
+            Some(expires) if expires - now <= chrono::Duration::days(EXPIRY_WARNING_DAYS) => {
+                let days = (expires - now).num_days();
+                (
+                    SessionStatus::Expiring,
+                    format!("The {} cookie expires in {} day(s)", SESSION_COOKIE, days),
+                )
+            }
+            _ => (SessionStatus::Valid, "Session looks valid".to_string()),
+        },
+    };
+
+    SessionReport {
+        status,
+        expires_at,
+        page,
+        checked_at: Local::now(),
+        message,
+    }
+}
+
+/// Current session status without fetching anything: cookie expiry plus the
+/// page seen on the last scrape, unless the cookie file changed since then
+pub fn check_session(paths: &storage::Paths) -> Result<SessionReport> {
+    let cookies = storage::load_cookie_content(paths)?
+        .map(|content| cookies::parse_cookies(&content))
+        .transpose()?;
+
+    let last_report = storage::load_session_report(paths)
+        .context("Failed to load session report")?;
+
+    let cookies_modified = storage::cookies_modified(paths);
+
+    let page = last_report.and_then(|report| match cookies_modified {
+        Some(modified) if modified > report.checked_at => None,
+        _ => report.page,
+    });
+
+    Ok(evaluate(cookies.as_deref(), page, Utc::now()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn li_at(expires: Option<DateTime<Utc>>) -> StoredCookie {
+        StoredCookie {
+            name: SESSION_COOKIE.to_string(),
+            value: "token".to_string(),
+            domain: ".linkedin.com".to_string(),
+            path: "/".to_string(),
+            secure: true,
+            http_only: true,
+            expires,
+        }
+    }
+
+    #[test]
+    fn test_detect_login_redirect() {
+        let page = detect_page_session("https://www.linkedin.com/authwall?trk=abc", "<html></html>");
+        assert_eq!(page, PageSession::LoginWall);
+    }
+
+    #[test]
+    fn test_detect_logged_in_and_guest() {
+        let url = "https://www.linkedin.com/jobs/search?keywords=rust";
+        assert_eq!(detect_page_session(url, r#"{"jobPostingTitle":"Engineer"}"#), PageSession::LoggedIn);
+        assert_eq!(detect_page_session(url, r#"<div class="base-card"></div>"#), PageSession::Guest);
+        assert_eq!(detect_page_session(url, r#"<form class="login__form"></form>"#), PageSession::LoginWall);
+    }
+
+    #[test]
+    fn test_evaluate_absent() {
+        let report = evaluate(None, None, Utc::now());
+        assert_eq!(report.status, SessionStatus::Absent);
+    }
+
+    #[test]
+    fn test_evaluate_expiry() {
+        let now = Utc::now();
+
+        let valid = [li_at(Some(now + chrono::Duration::days(90)))];
+        assert_eq!(evaluate(Some(&valid), None, now).status, SessionStatus::Valid);
+
+        let expiring = [li_at(Some(now + chrono::Duration::days(3)))];
+        assert_eq!(evaluate(Some(&expiring), None, now).status, SessionStatus::Expiring);
+
+        let expired = [li_at(Some(now - chrono::Duration::days(1)))];
+        assert_eq!(evaluate(Some(&expired), None, now).status, SessionStatus::Expired);
+    }
+
+    #[test]
+    fn test_evaluate_rejected_session() {
+        let cookies = [li_at(None)];
+        let report = evaluate(Some(&cookies), Some(PageSession::LoginWall), Utc::now());
+        assert_eq!(report.status, SessionStatus::Expired);
+
+        let report = evaluate(Some(&cookies), Some(PageSession::LoggedIn), Utc::now());
+        assert_eq!(report.status, SessionStatus::Valid);
+    }
+}
@@ -1,18 +1,45 @@
+use crate::dedup::{DuplicateJob, Fingerprint};
 use crate::models::JobPosting;
 use anyhow::{Context, Result};
+use chrono::{DateTime, Local};
 use serde::{Deserialize, Serialize};
-use std::collections::HashSet;
+use std::collections::{HashMap, HashSet};
 use std::path::Path;
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct State {
     seen_job_ids: HashSet<String>,
+    /// Content fingerprints of seen jobs, keyed by job id
+    #[serde(default)]
+    fingerprints: HashMap<String, Fingerprint>,
+    /// Reposts and cross-posts, mapped to the id of the original job
+    #[serde(default)]
+    duplicate_of: HashMap<String, String>,
+    /// When each id was first seen, for pruning
+    #[serde(default)]
+    seen_at: HashMap<String, DateTime<Local>>,
+}
+
+/// Result of splitting a batch of jobs against the seen state
+#[derive(Debug, Clone, Default)]
+pub struct Classification {
+    pub new_jobs: Vec<JobPosting>,
+    pub duplicates: Vec<DuplicateJob>,
+}
+
+impl Default for State {
+    fn default() -> Self {
+        Self::new()
+    }
 }
 
 impl State {
     pub fn new() -> Self {
         Self {
             seen_job_ids: HashSet::new(),
+            fingerprints: HashMap::new(),
+            duplicate_of: HashMap::new(),
+            seen_at: HashMap::new(),
         }
     }
 
@@ -41,22 +68,127 @@ impl State {
     }
 
     pub fn mark_jobs_seen(&mut self, jobs: &[JobPosting]) {
+        let now = Local::now();
         for job in jobs {
             self.seen_job_ids.insert(job.id.clone());
+            self.seen_at.entry(job.id.clone()).or_insert(now);
+            self.fingerprints.insert(job.id.clone(), Fingerprint::from_job(job));
+        }
+    }
+
+    /// Record duplicates as seen, grouped under their original job
+    pub fn mark_duplicates_seen(&mut self, duplicates: &[DuplicateJob]) {
+        let now = Local::now();
+        for duplicate in duplicates {
+            self.seen_job_ids.insert(duplicate.job.id.clone());
+            self.seen_at.entry(duplicate.job.id.clone()).or_insert(now);
+            self.duplicate_of
+                .insert(duplicate.job.id.clone(), duplicate.original_id.clone());
         }
     }
 
     pub fn filter_new_jobs(&self, jobs: &[JobPosting]) -> Vec<JobPosting> {
-        jobs
+        self.classify_jobs(jobs).new_jobs
+    }
+
+    /// Split jobs with unseen ids into genuinely new jobs and reposts of a job
+    /// already seen (or seen earlier in the same batch)
+    pub fn classify_jobs(&self, jobs: &[JobPosting]) -> Classification {
+        let mut classification = Classification::default();
+        let mut batch: Vec<(String, Fingerprint)> = Vec::new();
+        let originals = self.originals_by_employer();
+
+        for job in jobs {
+            if self.seen_job_ids.contains(&job.id) {
+                continue;
+            }
+
+            let fingerprint = Fingerprint::from_job(job);
+            let original_id = find_original(&originals, &fingerprint)
+                .or_else(|| {
+                    batch
+                        .iter()
+                        .find(|(_, seen)| seen.matches(&fingerprint))
+                        .map(|(id, _)| id.clone())
+                });
+
+            match original_id {
+                Some(original_id) => classification.duplicates.push(DuplicateJob {
+                    job: job.clone(),
+                    original_id,
+                }),
+                None => {
+                    batch.push((job.id.clone(), fingerprint));
+                    classification.new_jobs.push(job.clone());
+                }
+            }
+        }
+
+        classification
+    }
+
+    /// Seen fingerprints grouped by company and location (only those can match),
+    /// earliest seen first so the first match is the original
+    fn originals_by_employer(&self) -> HashMap<(&str, &str), Vec<(&str, &Fingerprint)>> {
+        let mut groups: HashMap<(&str, &str), Vec<(&str, &Fingerprint)>> = HashMap::new();
+        for (id, fingerprint) in &self.fingerprints {
+            groups
+                .entry((fingerprint.company.as_str(), fingerprint.location.as_str()))
+                .or_default()
+                .push((id.as_str(), fingerprint));
+        }
+        for group in groups.values_mut() {
+            group.sort_by_key(|(id, _)| (self.seen_at.get(*id), *id));
+        }
+        groups
+    }
+
+    pub fn original_of(&self, job_id: &str) -> Option<&str> {
+        self.duplicate_of.get(job_id).map(String::as_str)
+    }
+
+    /// Forget jobs first seen before `cutoff` and return how many were removed.
+    /// Ids from before seen times were tracked count as seen now.
+    pub fn prune_seen_before(&mut self, cutoff: DateTime<Local>) -> usize {
+        let now = Local::now();
+        for id in &self.seen_job_ids {
+            self.seen_at.entry(id.clone()).or_insert(now);
+        }
+
+        let expired: Vec<String> = self
+            .seen_at
             .iter()
-            .filter(|job| !self.seen_job_ids.contains(&job.id))
-            .cloned()
-            .collect()
+            .filter(|(_, seen_at)| **seen_at < cutoff)
+            .map(|(id, _)| id.clone())
+            .collect();
+
+        for id in &expired {
+            self.seen_job_ids.remove(id);
+            self.seen_at.remove(id);
+            self.fingerprints.remove(id);
+            self.duplicate_of.remove(id);
+        }
+        expired.len()
     }
 
     pub fn seen_count(&self) -> usize {
         self.seen_job_ids.len()
     }
+
+    pub fn duplicate_count(&self) -> usize {
+        self.duplicate_of.len()
+    }
+}
+
+fn find_original(
+    originals: &HashMap<(&str, &str), Vec<(&str, &Fingerprint)>>,
+    fingerprint: &Fingerprint,
+) -> Option<String> {
+    originals
+        .get(&(fingerprint.company.as_str(), fingerprint.location.as_str()))?
+        .iter()
+        .find(|(_, seen)| seen.matches(fingerprint))
+        .map(|(id, _)| id.to_string())
 }
 
 #[cfg(test)]
@@ -121,7 +253,7 @@ mod tests {
             None,
         );
 
-        state.mark_jobs_seen(&[job1.clone()]);
+        state.mark_jobs_seen(std::slice::from_ref(&job1));
         
         let all_jobs = vec![job1, job2.clone()];
         let new_jobs = state.filter_new_jobs(&all_jobs);
@@ -130,6 +262,102 @@ mod tests {
         assert_eq!(new_jobs[0].id, job2.id);
     }
 
+    #[test]
+    fn test_classify_repost_as_duplicate() {
+        let mut state = State::new();
+        let original = JobPosting::new(
+            "1".to_string(),
+            "Senior Rust Engineer".to_string(),
+            "Tech Corp".to_string(),
+            "San Francisco, CA".to_string(),
+            "https://example.com/1".to_string(),
+            None,
+            None,
+        );
+        let repost = JobPosting::new(
+            "2".to_string(),
+            "Senior Rust Engineer (Remote)".to_string(),
+            "Tech Corp, Inc.".to_string(),
+            "San Francisco, CA".to_string(),
+            "https://example.com/2".to_string(),
+            None,
+            None,
+        );
+        state.mark_jobs_seen(&[original]);
+
+        let classification = state.classify_jobs(&[repost]);
+        assert!(classification.new_jobs.is_empty());
+        assert_eq!(classification.duplicates.len(), 1);
+        assert_eq!(classification.duplicates[0].original_id, "1");
+
+        state.mark_duplicates_seen(&classification.duplicates);
+        assert_eq!(state.original_of("2"), Some("1"));
+        assert_eq!(state.duplicate_count(), 1);
+    }
+
+    #[test]
+    fn test_classify_duplicates_within_batch() {
+        let state = State::new();
+        let jobs = vec![
+            JobPosting::new(
+                "1".to_string(),
+                "Rust Developer".to_string(),
+                "Company".to_string(),
+                "Location".to_string(),
+                "https://example.com/1".to_string(),
+                None,
+                None,
+            ),
+            JobPosting::new(
+                "2".to_string(),
+                "Rust Developer".to_string(),
+                "Company".to_string(),
+                "Location".to_string(),
+                "https://example.com/2".to_string(),
+                None,
+                None,
+            ),
+        ];
+
+        let classification = state.classify_jobs(&jobs);
+        assert_eq!(classification.new_jobs.len(), 1);
+        assert_eq!(classification.duplicates.len(), 1);
+        assert_eq!(classification.duplicates[0].original_id, "1");
+    }
+
+    #[test]
+    fn test_original_is_earliest_seen_match() {
+        let job = |id: &str| {
+            JobPosting::new(
+                id.to_string(),
+                "Rust Developer".to_string(),
+                "Company".to_string(),
+                "Location".to_string(),
+                format!("https://example.com/{}", id),
+                None,
+                None,
+            )
+        };
+        let mut state = State::new();
+        state.mark_jobs_seen(&[job("a"), job("b"), job("c")]);
+        let now = Local::now();
+        state.seen_at.insert("a".to_string(), now);
+        state.seen_at.insert("b".to_string(), now - chrono::Duration::days(2));
+        state.seen_at.insert("c".to_string(), now - chrono::Duration::days(1));
+
+        for _ in 0..5 {
+            let classification = state.classify_jobs(&[job("d")]);
+            assert_eq!(classification.duplicates[0].original_id, "b");
+        }
+    }
+
+    #[test]
+    fn test_load_state_without_fingerprints() {
+        let state: State = serde_json::from_str(r#"{"seen_job_ids": ["1", "2"]}"#).unwrap();
+        assert_eq!(state.seen_count(), 2);
+        assert_eq!(state.duplicate_count(), 0);
+    }
+
     #[test]
     fn test_save_and_load_state() {
         let temp_dir = TempDir::new().unwrap();
@@ -162,4 +390,27 @@ mod tests {
         let state = State::load_from_file(&state_path).unwrap();
         assert_eq!(state.seen_count(), 0);
     }
+
+    #[test]
+    fn test_prune_seen_before() {
+        let mut state = State::new();
+        let job = |id: &str| {
+            JobPosting::new(
+                id.to_string(),
+                format!("Job {}", id),
+                "Company".to_string(),
+                "Location".to_string(),
+                format!("https://example.com/{}", id),
+                None,
+                None,
+            )
+        };
+        state.mark_jobs_seen(&[job("old"), job("recent")]);
+        state.seen_at.insert("old".to_string(), Local::now() - chrono::Duration::days(40));
+
+        let removed = state.prune_seen_before(Local::now() - chrono::Duration::days(30));
+        assert_eq!(removed, 1);
+        assert_eq!(state.seen_count(), 1);
+        assert_eq!(state.filter_new_jobs(&[job("old")]).len(), 1);
+    }
 }
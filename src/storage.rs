@@ -1,11 +1,24 @@
-use crate::config::Config;
+use crate::archive;
+use crate::audit::AuditEntry;
+use crate::auth::Credentials;
+use crate::config::{AuthConfig, Config};
+use crate::dedup::DuplicateJob;
+use crate::diagnostics::{self, ParseHealth};
 use crate::models::JobPosting;
+use crate::migrate;
+use crate::overrides;
+use crate::selectors::SelectorProfile;
+use crate::secrets::{self, SecretKey, SecretStore};
+use crate::fingerprint::ProfilePin;
+use crate::session::SessionReport;
 use crate::state::State;
 use anyhow::{Context, Result};
 use chrono::{DateTime, Local};
 use serde::{Deserialize, Serialize};
 use std::fs;
+use std::io::Write;
 use std::path::{Path, PathBuf};
+use tracing::{info, warn};
 
 #[derive(Debug, Clone)]
 pub struct Paths {
@@ -14,13 +27,25 @@ pub struct Paths {
     pub cookies: PathBuf,
     pub state: PathBuf,
     pub latest_jobs: PathBuf,
-    pub debug_html: PathBuf,
+    pub session: PathBuf,
+    pub secrets: PathBuf,
+    pub selectors: PathBuf,
+    pub parse_history: PathBuf,
+    pub parse_anomalies: PathBuf,
+    pub archive: PathBuf,
+    pub browser_profile: PathBuf,
+    pub config_audit: PathBuf,
 }
 
 impl Paths {
     pub fn new() -> Result<Self> {
         let base_dir = std::env::var("DATA_DIR").unwrap_or_else(|_| ".".to_string());
-        let base_dir = PathBuf::from(base_dir);
+        Self::in_dir(base_dir)
+    }
+
+    /// Paths under an explicit data directory, ignoring `DATA_DIR`
+    pub fn in_dir<P: Into<PathBuf>>(base_dir: P) -> Result<Self> {
+        let base_dir = base_dir.into();
         fs::create_dir_all(&base_dir)
             .with_context(|| format!("Failed to create data dir: {:?}", base_dir))?;
 
@@ -30,7 +55,14 @@ impl Paths {
             cookies: base_dir.join("linkedin_cookies.txt"),
             state: base_dir.join(".notifier_state.json"),
             latest_jobs: base_dir.join("latest_jobs.json"),
-            debug_html: base_dir.join("debug_linkedin.html"),
+            session: base_dir.join("session_status.json"),
+            secrets: base_dir.join("secrets.json"),
+            selectors: base_dir.join("selectors.toml"),
+            parse_history: base_dir.join("parse_history.json"),
+            parse_anomalies: base_dir.join("parse_anomalies"),
+            archive: base_dir.join("runs"),
+            browser_profile: base_dir.join("browser_profile.json"),
+            config_audit: base_dir.join("config_audit.jsonl"),
         })
     }
 }
@@ -40,6 +72,9 @@ pub struct JobsSnapshot {
     pub updated_at: Option<DateTime<Local>>,
     pub jobs: Vec<JobPosting>,
     pub new_jobs: Vec<JobPosting>,
+    /// Reposts and cross-posts found in this run, grouped with their original
+    #[serde(default)]
+    pub duplicates: Vec<DuplicateJob>,
 }
 
 impl JobsSnapshot {
@@ -48,19 +83,134 @@ impl JobsSnapshot {
             updated_at: None,
             jobs: Vec::new(),
             new_jobs: Vec::new(),
+            duplicates: Vec::new(),
         }
     }
 }
 
+/// Defaults and `config.toml` only, without env overrides; for editing the file
 pub fn load_config_or_default(paths: &Paths) -> Result<Config> {
     Config::load_or_default(&paths.config)
 }
 
+/// An older `config.toml` rewritten as the current version
+#[derive(Debug, Clone)]
+pub struct ConfigUpgrade {
+    pub from_version: u32,
+    pub applied: Vec<&'static str>,
+    /// Copy of the file as it was
+    pub backup: PathBuf,
+}
+
+/// Upgrade an older `config.toml` in place, first copying it to
+/// `config.toml.v<version>-<timestamp>.bak`. `None` when it is current or absent.
+pub fn upgrade_config_file(paths: &Paths) -> Result<Option<ConfigUpgrade>> {
+    if !paths.config.exists() {
+        return Ok(None);
+    }
+    let content = fs::read_to_string(&paths.config)
+        .with_context(|| format!("Failed to read config file: {:?}", paths.config))?;
+    // A file that doesn't parse is reported by whatever loads it next
+    let Ok(migrated) = migrate::upgrade(&content) else {
+        return Ok(None);
+    };
+    if !migrated.is_upgrade() {
+        return Ok(None);
+    }
+
+    let rewritten = migrate::rewrite(&content, &migrated)?;
+    let backup = paths.config.with_file_name(format!(
+        "config.toml.v{}-{}.bak",
+        migrated.from_version,
+        Local::now().format("%Y%m%d-%H%M%S")
+    ));
+    fs::copy(&paths.config, &backup).with_context(|| format!("Failed to back up config: {:?}", backup))?;
+    fs::write(&paths.config, rewritten)
+        .with_context(|| format!("Failed to write config: {:?}", paths.config))?;
+    Ok(Some(ConfigUpgrade {
+        from_version: migrated.from_version,
+        applied: migrated.applied,
+        backup,
+    }))
+}
+
+/// The config in effect: defaults, then `config.toml`, then `FRESH_POST__*` env vars
+pub fn load_config(paths: &Paths) -> Result<Config> {
+    let content = if paths.config.exists() {
+        Some(fs::read_to_string(&paths.config).with_context(|| format!("Failed to read config file: {:?}", paths.config))?)
+    } else {
+        None
+    };
+    Ok(overrides::layer(content.as_deref(), &overrides::from_env())?.config)
+}
+
 pub fn save_config(paths: &Paths, config: &Config) -> Result<()> {
     let content = toml::to_string_pretty(config).context("Failed to serialize config")?;
-    fs::write(&paths.config, content)
-        .with_context(|| format!("Failed to write config: {:?}", paths.config))?;
-    Ok(())
+    // The reload poller must never see a half-written file
+    write_atomic(&paths.config, content.as_bytes())
+}
+
+/// Entries kept in the config audit log; older ones are dropped as new ones come in
+pub const MAX_CONFIG_AUDIT_ENTRIES: usize = 1000;
+
+/// Length at which the audit log is cut back to `MAX_CONFIG_AUDIT_ENTRIES`, so it
+/// is rewritten once per hundred changes rather than on every one
+pub const CONFIG_AUDIT_TRIM_AT: usize = MAX_CONFIG_AUDIT_ENTRIES + MAX_CONFIG_AUDIT_ENTRIES / 10;
+
+/// Append a config change to the audit log (one JSON object per line)
+pub fn append_config_audit(paths: &Paths, entry: &AuditEntry) -> Result<()> {
+    let mut line = serde_json::to_string(entry).context("Failed to serialize audit entry")?;
+    line.push('\n');
+    let mut file = fs::OpenOptions::new()
+        .create(true)
+        .append(true)
+        .open(&paths.config_audit)
+        .with_context(|| format!("Failed to open config audit log: {:?}", paths.config_audit))?;
+    file.write_all(line.as_bytes())
+        .with_context(|| format!("Failed to write config audit log: {:?}", paths.config_audit))?;
+    drop(file);
+
+    let lines = fs::read(&paths.config_audit)
+        .with_context(|| format!("Failed to read config audit log: {:?}", paths.config_audit))?
+        .iter()
+        .filter(|byte| **byte == b'\n')
+        .count();
+    if lines <= CONFIG_AUDIT_TRIM_AT {
+        return Ok(());
+    }
+    let entries = load_config_audit(paths)?;
+    let mut kept = String::new();
+    for entry in &entries[entries.len().saturating_sub(MAX_CONFIG_AUDIT_ENTRIES)..] {
+        kept.push_str(&serde_json::to_string(entry).context("Failed to serialize audit entry")?);
+        kept.push('\n');
+    }
+    write_atomic(&paths.config_audit, kept.as_bytes())
+}
+
+/// Config audit log, oldest first. Lines that don't parse (e.g. cut short by a
+/// crash mid-append) are logged and skipped.
+pub fn load_config_audit(paths: &Paths) -> Result<Vec<AuditEntry>> {
+    if !paths.config_audit.exists() {
+        return Ok(Vec::new());
+    }
+    let content = fs::read_to_string(&paths.config_audit)
+        .with_context(|| format!("Failed to read config audit log: {:?}", paths.config_audit))?;
+    Ok(content
+        .lines()
+        .enumerate()
+        .filter(|(_, line)| !line.trim().is_empty())
+        .filter_map(|(index, line)| match serde_json::from_str(line) {
+            Ok(entry) => Some(entry),
+            Err(err) => {
+                warn!(line = index + 1, %err, "skipping unreadable config audit entry");
+                None
+            }
+        })
+        .collect())
+}
+
+pub fn load_selector_profile(paths: &Paths) -> Result<SelectorProfile> {
+    SelectorProfile::load_or_default(&paths.selectors)
 }
 
 pub fn load_state(paths: &Paths) -> Result<State> {
@@ -95,6 +245,219 @@ pub fn save_latest_jobs(paths: &Paths, snapshot: &JobsSnapshot) -> Result<()> {
     Ok(())
 }
 
+/// Number of anomalous pages kept in `parse_anomalies/`
+pub const MAX_ANOMALY_PAGES: usize = 10;
+
+pub fn load_parse_history(paths: &Paths) -> Result<Vec<ParseHealth>> {
+    if !paths.parse_history.exists() {
+        return Ok(Vec::new());
+    }
+
+    let content = fs::read_to_string(&paths.parse_history)
+        .with_context(|| format!("Failed to read parse history: {:?}", paths.parse_history))?;
+
+    let history: Vec<ParseHealth> = serde_json::from_str(&content)
+        .context("Failed to parse parse history JSON")?;
+
+    Ok(history)
+}
+
+/// Append a run to the parse history, keeping the most recent runs only
+pub fn append_parse_history(paths: &Paths, health: &ParseHealth) -> Result<()> {
+    let mut history = load_parse_history(paths)?;
+    history.push(health.clone());
+    if history.len() > diagnostics::HISTORY_LEN {
+        history.drain(..history.len() - diagnostics::HISTORY_LEN);
+    }
+
+    let content = serde_json::to_string_pretty(&history)
+        .context("Failed to serialize parse history")?;
+
+    fs::write(&paths.parse_history, content)
+        .with_context(|| format!("Failed to write parse history: {:?}", paths.parse_history))?;
+
+    Ok(())
+}
+
+/// Keep the HTML of an anomalous run for analysis, with tokens and CSRF values
+/// scrubbed as in the run archive. Returns the saved file name.
+pub fn save_anomaly_html(paths: &Paths, html: &str, at: DateTime<Local>) -> Result<String> {
+    fs::create_dir_all(&paths.parse_anomalies)
+        .with_context(|| format!("Failed to create dir: {:?}", paths.parse_anomalies))?;
+
+    let file_name = format!("{}.html", at.format("%Y%m%d-%H%M%S"));
+    let path = paths.parse_anomalies.join(&file_name);
+    fs::write(&path, archive::redact_html(html))
+        .with_context(|| format!("Failed to save anomalous HTML: {:?}", path))?;
+
+    let mut saved: Vec<PathBuf> = fs::read_dir(&paths.parse_anomalies)
+        .with_context(|| format!("Failed to list dir: {:?}", paths.parse_anomalies))?
+        .filter_map(|entry| entry.ok().map(|entry| entry.path()))
+        .filter(|path| path.extension().map(|ext| ext == "html").unwrap_or(false))
+        .collect();
+    saved.sort();
+    if saved.len() > MAX_ANOMALY_PAGES {
+        for old in &saved[..saved.len() - MAX_ANOMALY_PAGES] {
+            let _ = fs::remove_file(old);
+        }
+    }
+
+    Ok(file_name)
+}
+
+pub fn load_session_report(paths: &Paths) -> Result<Option<SessionReport>> {
+    if !paths.session.exists() {
+        return Ok(None);
+    }
+
+    let content = fs::read_to_string(&paths.session)
+        .with_context(|| format!("Failed to read session report: {:?}", paths.session))?;
+
+    let report: SessionReport = serde_json::from_str(&content)
+        .context("Failed to parse session report JSON")?;
+
+    Ok(Some(report))
+}
+
+pub fn save_session_report(paths: &Paths, report: &SessionReport) -> Result<()> {
+    let content = serde_json::to_string_pretty(report)
+        .context("Failed to serialize session report")?;
+
+    fs::write(&paths.session, content)
+        .with_context(|| format!("Failed to write session report: {:?}", paths.session))?;
+
+    Ok(())
+}
+
+/// Browser profile the current cookie session is pinned to
+pub fn load_profile_pin(paths: &Paths) -> Result<Option<ProfilePin>> {
+    if !paths.browser_profile.exists() {
+        return Ok(None);
+    }
+
+    let content = fs::read_to_string(&paths.browser_profile)
+        .with_context(|| format!("Failed to read browser profile pin: {:?}", paths.browser_profile))?;
+
+    let pin: ProfilePin = serde_json::from_str(&content)
+        .context("Failed to parse browser profile pin JSON")?;
+
+    Ok(Some(pin))
+}
+
+pub fn save_profile_pin(paths: &Paths, pin: &ProfilePin) -> Result<()> {
+    let content = serde_json::to_string_pretty(pin)
+        .context("Failed to serialize browser profile pin")?;
+
+    fs::write(&paths.browser_profile, content)
+        .with_context(|| format!("Failed to write browser profile pin: {:?}", paths.browser_profile))?;
+
+    Ok(())
+}
+
+/// Open the encrypted secret store, or `None` when no secret key is configured
+pub fn open_secret_store(paths: &Paths) -> Result<Option<SecretStore>> {
+    Ok(SecretKey::from_env()?.map(|key| SecretStore::new(paths.secrets.clone(), key)))
+}
+
+/// Credentials for the web UI and API, with secret references resolved
+pub fn load_credentials(paths: &Paths, config: &AuthConfig) -> Result<Credentials> {
+    let store = open_secret_store(paths)?;
+    Credentials::load(config, store.as_ref())
+}
+
+/// Read the raw cookie file content. With a secret key configured the cookies
+/// live encrypted in the secret store; a plaintext cookie file dropped into the
+/// data directory still wins until `migrate_plaintext_cookies` moves it there.
+pub fn load_cookie_content(paths: &Paths) -> Result<Option<String>> {
+    if paths.cookies.exists() {
+        let content = fs::read_to_string(&paths.cookies)
+            .with_context(|| format!("Failed to read cookie file: {:?}", paths.cookies))?;
+        return Ok(Some(content));
+    }
+    let Some(store) = open_secret_store(paths)? else {
+        return Ok(None);
+    };
+    Ok(store
+        .get(secrets::COOKIES_SECRET)?
+        .map(|value| value.expose().to_string()))
+}
+
+/// Move a plaintext cookie file into the secret store, when a secret key is
+/// configured. Runs once at startup; returns whether a file was moved.
+pub fn migrate_plaintext_cookies(paths: &Paths) -> Result<bool> {
+    if !paths.cookies.exists() {
+        return Ok(false);
+    }
+    let Some(store) = open_secret_store(paths)? else {
+        return Ok(false);
+    };
+    let content = fs::read_to_string(&paths.cookies)
+        .with_context(|| format!("Failed to read cookie file: {:?}", paths.cookies))?;
+    store.set(secrets::COOKIES_SECRET, &content)?;
+    fs::remove_file(&paths.cookies)
+        .with_context(|| format!("Failed to remove plaintext cookie file: {:?}", paths.cookies))?;
+    info!(path = ?paths.cookies, "moved cookies into the encrypted secret store");
+    Ok(true)
+}
+
+/// Replace the stored cookies with `content` (already validated by the caller).
+/// Returns whether they were stored encrypted.
+pub fn save_cookies(paths: &Paths, content: &str) -> Result<bool> {
+    match open_secret_store(paths)? {
+        Some(store) => {
+            store.set(secrets::COOKIES_SECRET, content)?;
+            if paths.cookies.exists() {
+                fs::remove_file(&paths.cookies)
+                    .with_context(|| format!("Failed to remove plaintext cookie file: {:?}", paths.cookies))?;
+            }
+            Ok(true)
+        }
+        None => {
+            write_atomic(&paths.cookies, content.as_bytes())?;
+            Ok(false)
+        }
+    }
+}
+
+/// When the stored cookies last changed (plaintext file, else the secret store)
+pub fn cookies_modified(paths: &Paths) -> Option<DateTime<Local>> {
+    [&paths.cookies, &paths.secrets]
+        .into_iter()
+        .find(|path| path.exists())
+        .and_then(|path| fs::metadata(path).and_then(|meta| meta.modified()).ok())
+        .map(DateTime::from)
+}
+
+/// Write to a temp file in the same directory, then rename over `path`, so
+/// readers never see a partially written file
+pub fn write_atomic(path: &Path, contents: &[u8]) -> Result<()> {
+    use std::io::Write;
+
+    let dir = path
+        .parent()
+        .filter(|dir| !dir.as_os_str().is_empty())
+        .unwrap_or_else(|| Path::new("."));
+
+    let mut temp = tempfile::NamedTempFile::new_in(dir)
+        .with_context(|| format!("Failed to create temp file in {:?}", dir))?;
+    temp.write_all(contents)
+        .with_context(|| format!("Failed to write temp file for {:?}", path))?;
+    temp.as_file()
+        .sync_all()
+        .with_context(|| format!("Failed to sync temp file for {:?}", path))?;
+    temp.persist(path)
+        .with_context(|| format!("Failed to replace {:?}", path))?;
+
+    Ok(())
+}
+
+/// Check the data directory accepts writes by creating and removing a temp file
+pub fn check_writable(paths: &Paths) -> Result<()> {
+    tempfile::NamedTempFile::new_in(&paths.base_dir)
+        .with_context(|| format!("Failed to write to data dir: {:?}", paths.base_dir))?;
+    Ok(())
+}
+
 pub fn file_exists(path: &Path) -> bool {
     path.exists()
 }
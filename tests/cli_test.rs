@@ -0,0 +1,187 @@
+use fresh_post::models::JobPosting;
+use fresh_post::storage::{self, JobsSnapshot, Paths};
+use std::process::{Command, Output};
+use tempfile::TempDir;
+
+fn fresh_post(data_dir: &TempDir, args: &[&str]) -> Output {
+    Command::new(env!("CARGO_BIN_EXE_fresh-post"))
+        .args(args)
+        .env("DATA_DIR", data_dir.path())
+        .output()
+        .unwrap()
+}
+
+fn stdout(output: &Output) -> String {
+    String::from_utf8_lossy(&output.stdout).to_string()
+}
+
+fn job(id: &str, title: &str) -> JobPosting {
+    JobPosting::new(
+        id.to_string(),
+        title.to_string(),
+        "Acme, Inc.".to_string(),
+        "Remote".to_string(),
+        format!("https://www.linkedin.com/jobs/view/{}", id),
+        None,
+        None,
+    )
+}
+
+fn with_snapshot() -> (TempDir, Paths) {
+    let dir = TempDir::new().unwrap();
+    let paths = Paths::in_dir(dir.path()).unwrap();
+    let snapshot = JobsSnapshot {
+        updated_at: Some(chrono::Local::now()),
+        jobs: vec![job("1", "Rust Engineer"), job("2", "Senior \"Rust\" Engineer")],
+        new_jobs: vec![job("2", "Senior \"Rust\" Engineer")],
+        duplicates: Vec::new(),
+    };
+    storage::save_latest_jobs(&paths, &snapshot).unwrap();
+    (dir, paths)
+}
+
+#[test]
+fn test_config_set_show_validate() {
+    let dir = TempDir::new().unwrap();
+
+    let output = fresh_post(&dir, &["config", "set", "search.keywords", "embedded rust"]);
+    assert!(output.status.success(), "{:?}", output);
+    assert!(fresh_post(&dir, &["config", "set", "schedule.interval_hours", "2"]).status.success());
+
+    let shown = stdout(&fresh_post(&dir, &["config", "show"]));
+    assert!(shown.contains("keywords = \"embedded rust\""));
+    assert!(shown.contains("interval_hours = 2"));
+
+    assert!(fresh_post(&dir, &["config", "validate"]).status.success());
+    assert!(!fresh_post(&dir, &["config", "set", "search.nope", "1"]).status.success());
+
+    let output = fresh_post(&dir, &["config", "set", "schedule.interval_hours", "0"]);
+    assert!(!output.status.success());
+    assert!(String::from_utf8_lossy(&output.stderr).contains("schedule.interval_hours: must be at least 1"));
+    assert!(stdout(&fresh_post(&dir, &["config", "show"])).contains("interval_hours = 2"));
+
+    std::fs::write(dir.path().join("config.toml"), "[search]\nkeywords = \"\"\nlocation = \"Berlin\"\n").unwrap();
+    let output = fresh_post(&dir, &["config", "validate"]);
+    assert!(String::from_utf8_lossy(&output.stderr).contains("search.keywords: must not be empty"));
+
+    std::fs::write(dir.path().join("config.toml"), "[search\nkeywords = ").unwrap();
+    assert!(!fresh_post(&dir, &["config", "validate"]).status.success());
+}
+
+#[test]
+fn test_config_env_overrides() {
+    let dir = TempDir::new().unwrap();
+    assert!(fresh_post(&dir, &["config", "set", "schedule.interval_hours", "2"]).status.success());
+
+    let run = |args: &[&str], interval: &str| {
+        Command::new(env!("CARGO_BIN_EXE_fresh-post"))
+            .args(args)
+            .env("DATA_DIR", dir.path())
+            .env("FRESH_POST__SCHEDULE__INTERVAL_HOURS", interval)
+            .output()
+            .unwrap()
+    };
+    let shown = stdout(&run(&["config", "show"], "6"));
+    assert!(shown.contains("interval_hours = 6"), "{}", shown);
+    assert!(shown.contains("schedule.interval_hours set by FRESH_POST__SCHEDULE__INTERVAL_HOURS"));
+
+    let output = run(&["config", "validate"], "often");
+    assert!(String::from_utf8_lossy(&output.stderr).contains("FRESH_POST__SCHEDULE__INTERVAL_HOURS"));
+
+    // Env values never end up in the file
+    assert!(stdout(&fresh_post(&dir, &["config", "show"])).contains("interval_hours = 2"));
+}
+
+#[test]
+fn test_config_upgrade_keeps_backup() {
+    let dir = TempDir::new().unwrap();
+    let v1 = "# mine\n[search]\nkeywords = \"rust\"\nlocation = \"Berlin\"\n\n[server]\nweb_dir = \"web\"\n";
+    std::fs::write(dir.path().join("config.toml"), v1).unwrap();
+
+    // validate only reports that an upgrade is due
+    let validated = fresh_post(&dir, &["config", "validate"]);
+    assert!(validated.status.success());
+    assert!(stdout(&validated).contains("needs upgrade"));
+    assert_eq!(std::fs::read_to_string(dir.path().join("config.toml")).unwrap(), v1);
+    assert!(fresh_post(&dir, &["jobs", "list"]).status.success());
+    assert_eq!(std::fs::read_to_string(dir.path().join("config.toml")).unwrap(), v1);
+
+    let upgraded = fresh_post(&dir, &["config", "upgrade"]);
+    assert!(upgraded.status.success());
+    assert!(stdout(&upgraded).contains("Upgraded config.toml from version 1"));
+    let upgraded = std::fs::read_to_string(dir.path().join("config.toml")).unwrap();
+    assert!(upgraded.contains(&format!("version = {}", fresh_post::migrate::CURRENT_VERSION)));
+    assert!(!upgraded.contains("web_dir"));
+
+    let backups: Vec<_> = std::fs::read_dir(dir.path())
+        .unwrap()
+        .map(|entry| entry.unwrap().file_name().to_string_lossy().to_string())
+        .filter(|name| name.starts_with("config.toml.v1-") && name.ends_with(".bak"))
+        .collect();
+    assert_eq!(backups.len(), 1);
+    assert_eq!(std::fs::read_to_string(dir.path().join(&backups[0])).unwrap(), v1);
+    assert!(!stdout(&fresh_post(&dir, &["config", "validate"])).contains("needs upgrade"));
+
+    let schema: serde_json::Value = serde_json::from_str(&stdout(&fresh_post(&dir, &["config", "schema"]))).unwrap();
+    assert!(schema["properties"]["search"]["properties"]["keywords"].is_object());
+}
+
+#[test]
+fn test_jobs_list_and_export() {
+    let (dir, _paths) = with_snapshot();
+
+    let listed = stdout(&fresh_post(&dir, &["jobs", "list", "--new"]));
+    assert!(listed.contains("Senior \"Rust\" Engineer"));
+    assert!(!listed.contains("\nRust Engineer"));
+
+    let json = stdout(&fresh_post(&dir, &["jobs", "list", "--json"]));
+    let jobs: Vec<JobPosting> = serde_json::from_str(&json).unwrap();
+    assert_eq!(jobs.len(), 2);
+
+    let csv = stdout(&fresh_post(&dir, &["export", "--format", "csv"]));
+    let lines: Vec<&str> = csv.lines().collect();
+    assert_eq!(lines[0], "id,title,company,location,url,posted_date");
+    assert_eq!(
+        lines[2],
+        "2,\"Senior \"\"Rust\"\" Engineer\",\"Acme, Inc.\",Remote,https://www.linkedin.com/jobs/view/2,"
+    );
+
+    let output_file = dir.path().join("export.json");
+    let output = fresh_post(&dir, &["export", "--new", "--output", output_file.to_str().unwrap()]);
+    assert!(output.status.success());
+    let exported: Vec<JobPosting> = serde_json::from_str(&std::fs::read_to_string(output_file).unwrap()).unwrap();
+    assert_eq!(exported.len(), 1);
+}
+
+#[test]
+fn test_state_reset_requires_confirmation() {
+    let (dir, paths) = with_snapshot();
+    let mut state = storage::load_state(&paths).unwrap();
+    state.mark_jobs_seen(&[job("1", "Rust Engineer")]);
+    storage::save_state(&paths, &state).unwrap();
+
+    assert!(!fresh_post(&dir, &["state", "reset"]).status.success());
+    assert_eq!(storage::load_state(&paths).unwrap().seen_count(), 1);
+
+    assert!(fresh_post(&dir, &["state", "prune"]).status.success());
+    assert_eq!(storage::load_state(&paths).unwrap().seen_count(), 1);
+
+    assert!(fresh_post(&dir, &["state", "reset", "--yes"]).status.success());
+    assert_eq!(storage::load_state(&paths).unwrap().seen_count(), 0);
+}
+
+#[test]
+fn test_cookies_import() {
+    let dir = TempDir::new().unwrap();
+    let file = dir.path().join("export.txt");
+    std::fs::write(&file, "li_at=token; JSESSIONID=ajax:1\n").unwrap();
+
+    let output = fresh_post(&dir, &["cookies", "import", file.to_str().unwrap()]);
+    assert!(output.status.success(), "{:?}", output);
+    assert!(stdout(&output).contains("Imported 2 cookies"));
+    assert!(dir.path().join("linkedin_cookies.txt").exists());
+
+    let empty = dir.path().join("empty.txt");
+    std::fs::write(&empty, "\n").unwrap();
+    assert!(!fresh_post(&dir, &["cookies", "import", empty.to_str().unwrap()]).status.success());
+}
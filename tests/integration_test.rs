@@ -1,3 +1,4 @@
+use fresh_post::config::SearchConfig;
 use fresh_post::{filters, scraper, state};
 use std::fs;
 use std::path::PathBuf;
@@ -22,7 +23,7 @@ async fn test_full_scrape_flow() {
     assert!(!first_job.url.is_empty());
     
     // Test date filtering (will depend on current date)
-    let today_jobs = filters::filter_today_only(jobs.clone());
+    let _today_jobs = filters::filter_today_only(jobs.clone());
     
     // Test state management
     let temp_state_path = PathBuf::from(".test_state.json");
@@ -42,7 +43,12 @@ async fn test_full_scrape_flow() {
 
 #[test]
 fn test_build_search_url() {
-    let url = scraper::build_search_url("rust", "SF", false, None);
+    let search = SearchConfig {
+        keywords: "rust".to_string(),
+        location: "SF".to_string(),
+        ..SearchConfig::default()
+    };
+    let url = scraper::build_search_url(&search).unwrap();
     assert!(url.starts_with("https://www.linkedin.com/jobs/search"));
     assert!(url.contains("keywords=rust"));
     assert!(url.contains("location=SF"));
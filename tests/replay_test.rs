@@ -0,0 +1,346 @@
+use fresh_post::fingerprint::BrowserProfile;
+use fresh_post::metrics::Metrics;
+use fresh_post::notify::{Notification, Notifier};
+use fresh_post::proxy::{ProxyFailure, ProxyPool};
+use fresh_post::ratelimit::RateLimiter;
+use fresh_post::replay::ReplayServer;
+use fresh_post::scheduler;
+use fresh_post::service::{self, ScrapeEnv};
+use fresh_post::session::SessionStatus;
+use fresh_post::storage::{self, Paths};
+use std::path::PathBuf;
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+use tempfile::TempDir;
+
+fn fixture_dir(scenario: &str) -> PathBuf {
+    PathBuf::from(env!("CARGO_MANIFEST_DIR"))
+        .join("tests/fixtures/replay")
+        .join(scenario)
+}
+
+fn data_dir(config: &str) -> (TempDir, Paths) {
+    let dir = TempDir::new().unwrap();
+    let paths = Paths::in_dir(dir.path()).unwrap();
+    std::fs::write(&paths.config, config).unwrap();
+    (dir, paths)
+}
+
+fn replay_env(server: &ReplayServer) -> ScrapeEnv {
+    ScrapeEnv {
+        base_url: server.base_url().to_string(),
+        rate_limiter: None,
+        ..Default::default()
+    }
+}
+
+const CONFIG: &str = r#"
+[search]
+keywords = "rust developer"
+location = "San Francisco Bay Area"
+max_pages = 5
+"#;
+
+#[tokio::test]
+async fn test_replay_paginated_scrape_cycle() {
+    let server = ReplayServer::start(fixture_dir("pagination")).await.unwrap();
+    let (_dir, paths) = data_dir(CONFIG);
+
+    let summary = service::run_scrape_with(&paths, &replay_env(&server)).await.unwrap();
+
+    // Third page is empty, so pagination stops there; job 1003 appears on two pages
+    assert_eq!(summary.pages_fetched, 3);
+    assert_eq!(summary.today_jobs, 5);
+    assert_eq!(summary.new_jobs, 5);
+    assert_eq!(summary.session_status, SessionStatus::Absent);
+
+    let starts: Vec<Option<String>> = server
+        .requests()
+        .iter()
+        .map(|request| request.query.get("start").cloned())
+        .collect();
+    assert_eq!(starts, vec![None, Some("25".to_string()), Some("50".to_string())]);
+
+    let snapshot = storage::load_latest_jobs(&paths).unwrap();
+    assert_eq!(snapshot.new_jobs.len(), 5);
+
+    // A second cycle sees the same jobs and announces nothing new
+    let summary = service::run_scrape_with(&paths, &replay_env(&server)).await.unwrap();
+    assert_eq!(summary.today_jobs, 5);
+    assert_eq!(summary.new_jobs, 0);
+}
+
+#[tokio::test]
+async fn test_replay_respects_max_pages() {
+    let server = ReplayServer::start(fixture_dir("pagination")).await.unwrap();
+    let (_dir, paths) = data_dir(&CONFIG.replace("max_pages = 5", "max_pages = 1"));
+
+    let summary = service::run_scrape_with(&paths, &replay_env(&server)).await.unwrap();
+    assert_eq!(summary.pages_fetched, 1);
+    assert_eq!(summary.new_jobs, 3);
+}
+
+#[tokio::test]
+async fn test_replay_blocked_request() {
+    let server = ReplayServer::start(fixture_dir("blocked")).await.unwrap();
+    let (_dir, paths) = data_dir(CONFIG);
+
+    let err = service::run_scrape_with(&paths, &replay_env(&server)).await.unwrap_err();
+    assert!(format!("{:#}", err).contains("status 999"));
+    assert!(!paths.latest_jobs.exists());
+
+    let metrics = Metrics::global().render();
+    assert!(metrics.contains("fresh_post_http_requests_total{status=\"999\"}"));
+    assert!(!metrics.contains("fresh_post_runs_total{outcome=\"failure\"} 0"));
+}
+
+#[tokio::test]
+async fn test_replay_login_wall_expires_session() {
+    let server = ReplayServer::start(fixture_dir("login_wall")).await.unwrap();
+    let (_dir, paths) = data_dir(CONFIG);
+    std::fs::write(&paths.cookies, "li_at=stale-token\n").unwrap();
+
+    let summary = service::run_scrape_with(&paths, &replay_env(&server)).await.unwrap();
+    assert_eq!(summary.session_status, SessionStatus::Expired);
+    assert_eq!(summary.today_jobs, 0);
+    assert_eq!(summary.pages_fetched, 1);
+
+    let paths_requested: Vec<String> = server.requests().into_iter().map(|request| request.path).collect();
+    assert_eq!(paths_requested, vec!["/jobs/search", "/authwall"]);
+}
+
+#[tokio::test]
+async fn test_replay_sends_cookies_and_parses_logged_in_view() {
+    let server = ReplayServer::start(fixture_dir("logged_in")).await.unwrap();
+    let (_dir, paths) = data_dir(CONFIG);
+    std::fs::write(
+        &paths.cookies,
+        "# Netscape HTTP Cookie File\n127.0.0.1\tFALSE\t/\tFALSE\t0\tli_at\tsession-token\n",
+    )
+    .unwrap();
+
+    let summary = service::run_scrape_with(&paths, &replay_env(&server)).await.unwrap();
+    assert_eq!(summary.session_status, SessionStatus::Valid);
+    assert_eq!(summary.new_jobs, 1);
+
+    let requests = server.requests();
+    assert_eq!(requests[0].cookie.as_deref(), Some("li_at=session-token"));
+
+    let snapshot = storage::load_latest_jobs(&paths).unwrap();
+    assert_eq!(snapshot.jobs[0].title, "Staff Rust Engineer");
+    assert_eq!(snapshot.jobs[0].company, "Oxide Systems");
+}
+
+#[tokio::test]
+async fn test_replay_expiring_session_notifies() {
+    let server = ReplayServer::start(fixture_dir("logged_in")).await.unwrap();
+    let (_dir, paths) = data_dir(CONFIG);
+    let expires = chrono::Utc::now() + chrono::Duration::days(2);
+    std::fs::write(
+        &paths.cookies,
+        format!(
+            "# Netscape HTTP Cookie File\n127.0.0.1\tFALSE\t/\tFALSE\t{}\tli_at\tsession-token\n",
+            expires.timestamp()
+        ),
+    )
+    .unwrap();
+    let mut notifications = Notifier::global().subscribe();
+
+    let summary = service::run_scrape_with(&paths, &replay_env(&server)).await.unwrap();
+    assert_eq!(summary.session_status, SessionStatus::Expiring);
+    assert_eq!(summary.new_jobs, 1);
+
+    // What the scheduler does after each run
+    scheduler::warn_on_session_problems(&paths);
+    let Notification::SessionWarning { session } = notifications.try_recv().unwrap();
+    assert_eq!(session.status, SessionStatus::Expiring);
+    assert!(session.message.contains("expires"));
+    assert!(Notifier::global().pending().is_some());
+}
+
+#[tokio::test]
+async fn test_record_then_replay() {
+    let live = ReplayServer::start(fixture_dir("pagination")).await.unwrap();
+    let (_dir, paths) = data_dir(CONFIG);
+    let recording_dir = TempDir::new().unwrap();
+
+    let env = ScrapeEnv {
+        record_dir: Some(recording_dir.path().to_path_buf()),
+        ..replay_env(&live)
+    };
+    let recorded = service::run_scrape_with(&paths, &env).await.unwrap();
+    drop(live);
+
+    let replay = ReplayServer::start(recording_dir.path()).await.unwrap();
+    let (_dir, paths) = data_dir(CONFIG);
+    let replayed = service::run_scrape_with(&paths, &replay_env(&replay)).await.unwrap();
+
+    assert_eq!(replayed.pages_fetched, recorded.pages_fetched);
+    assert_eq!(replayed.new_jobs, recorded.new_jobs);
+}
+
+#[tokio::test]
+async fn test_record_then_replay_login_wall() {
+    let live = ReplayServer::start(fixture_dir("login_wall")).await.unwrap();
+    let (_dir, paths) = data_dir(CONFIG);
+    std::fs::write(&paths.cookies, "li_at=stale-token\n").unwrap();
+    let recording_dir = TempDir::new().unwrap();
+
+    let env = ScrapeEnv {
+        record_dir: Some(recording_dir.path().to_path_buf()),
+        ..replay_env(&live)
+    };
+    let recorded = service::run_scrape_with(&paths, &env).await.unwrap();
+    assert_eq!(recorded.session_status, SessionStatus::Expired);
+    drop(live);
+
+    let replay = ReplayServer::start(recording_dir.path()).await.unwrap();
+    let (_dir, paths) = data_dir(CONFIG);
+    std::fs::write(&paths.cookies, "li_at=stale-token\n").unwrap();
+    let replayed = service::run_scrape_with(&paths, &replay_env(&replay)).await.unwrap();
+
+    // Still a redirect to the login wall, not a 200 with the wall's HTML at /jobs/search
+    assert_eq!(replayed.session_status, SessionStatus::Expired);
+    let paths_requested: Vec<String> = replay.requests().into_iter().map(|request| request.path).collect();
+    assert_eq!(paths_requested, vec!["/jobs/search", "/authwall"]);
+}
+
+/// Replay servers double as plain HTTP proxies: they see absolute-form request
+/// URIs and answer by path, so the target host is never resolved
+fn proxied_env(proxies: &[&ReplayServer]) -> (ScrapeEnv, Arc<ProxyPool>) {
+    let pool = Arc::new(ProxyPool::new());
+    let urls: Vec<String> = proxies.iter().map(|server| server.base_url().to_string()).collect();
+    pool.configure(&urls).unwrap();
+
+    let env = ScrapeEnv {
+        base_url: "http://jobs.invalid".to_string(),
+        rate_limiter: None,
+        proxies: pool.clone(),
+        ..Default::default()
+    };
+    (env, pool)
+}
+
+fn proxy_config(proxies: &[&ReplayServer]) -> String {
+    let urls: Vec<String> = proxies.iter().map(|server| format!("{:?}", server.base_url())).collect();
+    format!("{}\n[proxy]\nurls = [{}]\n", CONFIG, urls.join(", "))
+}
+
+#[tokio::test]
+async fn test_blocked_proxy_rotates_out() {
+    let blocked = ReplayServer::start(fixture_dir("blocked")).await.unwrap();
+    let healthy = ReplayServer::start(fixture_dir("pagination")).await.unwrap();
+    let (env, pool) = proxied_env(&[&blocked, &healthy]);
+    let (_dir, paths) = data_dir(&proxy_config(&[&blocked, &healthy]));
+
+    let summary = service::run_scrape_with(&paths, &env).await.unwrap();
+    assert_eq!(summary.pages_fetched, 3);
+    assert_eq!(summary.new_jobs, 5);
+
+    // The blocked proxy saw one request, then sat out the rest of the run
+    assert_eq!(blocked.requests().len(), 1);
+    assert_eq!(healthy.requests().len(), 3);
+
+    let health = pool.health();
+    assert_eq!(health[0].last_failure, Some(ProxyFailure::Blocked));
+    assert!(!health[0].available);
+    assert!(health[0].cooldown_until.is_some());
+    assert_eq!(health[1].successes, 3);
+    assert!(health[1].available);
+}
+
+#[tokio::test]
+async fn test_rotation_continues_across_runs() {
+    let first = ReplayServer::start(fixture_dir("pagination")).await.unwrap();
+    let second = ReplayServer::start(fixture_dir("pagination")).await.unwrap();
+    let (env, _pool) = proxied_env(&[&first, &second]);
+    let config = proxy_config(&[&first, &second]).replace("max_pages = 5", "max_pages = 1");
+    let (_dir, paths) = data_dir(&config);
+
+    service::run_scrape_with(&paths, &env).await.unwrap();
+    assert_eq!((first.requests().len(), second.requests().len()), (1, 0));
+
+    // Each run reconfigures the pool from [proxy]; the next one picks up where this left off
+    service::run_scrape_with(&paths, &env).await.unwrap();
+    assert_eq!((first.requests().len(), second.requests().len()), (1, 1));
+}
+
+#[tokio::test]
+async fn test_all_proxies_blocked() {
+    let first = ReplayServer::start(fixture_dir("blocked")).await.unwrap();
+    let second = ReplayServer::start(fixture_dir("blocked")).await.unwrap();
+    let (env, pool) = proxied_env(&[&first, &second]);
+    let (_dir, paths) = data_dir(&proxy_config(&[&first, &second]));
+
+    let err = service::run_scrape_with(&paths, &env).await.unwrap_err();
+    assert!(format!("{:#}", err).contains("status 999"));
+    assert!(pool.health().iter().all(|proxy| proxy.failures == 1));
+}
+
+#[tokio::test]
+async fn test_rate_limiter_paces_pages() {
+    let server = ReplayServer::start(fixture_dir("pagination")).await.unwrap();
+    let config = format!(
+        "{}\n[rate_limit]\nrequests_per_minute = 600\nburst = 1\nmin_delay_ms = 0\nmax_delay_ms = 0\n",
+        CONFIG
+    );
+    let (_dir, paths) = data_dir(&config);
+    let limiter = Arc::new(RateLimiter::default());
+    let env = ScrapeEnv {
+        rate_limiter: Some(limiter.clone()),
+        ..replay_env(&server)
+    };
+
+    let started = Instant::now();
+    let summary = service::run_scrape_with(&paths, &env).await.unwrap();
+    assert_eq!(summary.pages_fetched, 3);
+
+    // One request may go out at once, the next two wait 100ms each
+    assert!(started.elapsed() >= Duration::from_millis(200));
+
+    let status = limiter.status();
+    assert_eq!(status.hosts.len(), 1);
+    assert_eq!(status.hosts[0].total_requests, 3);
+    assert!(status.hosts[0].total_wait_ms > 0);
+}
+
+#[tokio::test]
+async fn test_browser_profile_pinned_to_session() {
+    let server = ReplayServer::start(fixture_dir("logged_in")).await.unwrap();
+    let config = format!("{}\n[browser]\nprofile = \"rotate\"\n", CONFIG);
+    let (_dir, paths) = data_dir(&config);
+    std::fs::write(
+        &paths.cookies,
+        "# Netscape HTTP Cookie File\n127.0.0.1\tFALSE\t/\tFALSE\t0\tli_at\tsession-token\n",
+    )
+    .unwrap();
+
+    let first = service::run_scrape_with(&paths, &replay_env(&server)).await.unwrap();
+    let pin = storage::load_profile_pin(&paths).unwrap().unwrap();
+    assert_eq!(pin.profile, first.browser_profile);
+
+    for _ in 0..3 {
+        let summary = service::run_scrape_with(&paths, &replay_env(&server)).await.unwrap();
+        assert_eq!(summary.browser_profile, first.browser_profile);
+    }
+
+    let profile = BrowserProfile::find(&first.browser_profile).unwrap();
+    assert!(server
+        .requests()
+        .iter()
+        .all(|request| request.user_agent.as_deref() == Some(profile.user_agent)));
+}
+
+#[tokio::test]
+async fn test_configured_browser_profile_headers() {
+    let server = ReplayServer::start(fixture_dir("pagination")).await.unwrap();
+    let config = format!("{}\n[browser]\nprofile = \"firefox-windows\"\n", CONFIG);
+    let (_dir, paths) = data_dir(&config);
+
+    let summary = service::run_scrape_with(&paths, &replay_env(&server)).await.unwrap();
+    assert_eq!(summary.browser_profile, "firefox-windows");
+    assert!(server.requests()[0].user_agent.as_deref().unwrap().contains("Firefox/"));
+
+    // Without a session cookie there is nothing to pin
+    assert!(storage::load_profile_pin(&paths).unwrap().is_none());
+}
@@ -1,12 +1,39 @@
-async function fetchJson(url, options = {}) {
+// URLs are relative so the UI also works under server.base_path
+function cookieValue(name) {
+  const match = document.cookie.split("; ").find((pair) => pair.startsWith(name + "="));
+  return match ? decodeURIComponent(match.slice(name.length + 1)) : "";
+}
+
+// Fetch and throw on errors; returns the response so callers can read headers
+async function fetchChecked(url, options = {}) {
   const res = await fetch(url, {
-    headers: { "Content-Type": "application/json" },
     ...options,
+    headers: {
+      "Content-Type": "application/json",
+      "X-CSRF-Token": cookieValue("fresh_post_csrf"),
+      ...(options.headers || {}),
+    },
   });
+  if (res.status === 401 && url !== "api/login") {
+    window.location.href = "login";
+    throw new Error("Login required");
+  }
+  if (res.status === 412) {
+    throw new Error("Config was changed elsewhere; reload the page and try again");
+  }
+  if (res.status === 422) {
+    const { errors } = await res.json();
+    throw new Error(errors.map((error) => `${error.field}: ${error.message}`).join("; "));
+  }
   if (!res.ok) {
     const text = await res.text();
     throw new Error(text || res.statusText);
   }
+  return res;
+}
+
+async function fetchJson(url, options = {}) {
+  const res = await fetchChecked(url, options);
   return res.json();
 }
 
@@ -16,30 +43,142 @@ function setStatus(el, message, isError = false) {
   el.classList.toggle("error", isError);
 }
 
+// Version of the config the form shows, so saving can't overwrite someone else's change
+let configEtag = null;
+
+// Config form inputs and the field each one edits
+const CONFIG_FIELDS = {
+  keywords: "search.keywords",
+  location: "search.location",
+  workplace_on_site: "search.workplace",
+  workplace_remote: "search.workplace",
+  workplace_hybrid: "search.workplace",
+  easy_apply: "search.easy_apply",
+  posted_within_hours: "search.posted_within_hours",
+  sort_by_date: "search.sort_by",
+  salary_min: "search.salary_min",
+  interval_hours: "schedule.interval_hours",
+};
+
+// Limits and hints for the form from the config schema
+async function applySchema() {
+  let schema;
+  try {
+    schema = await fetchJson("api/config/schema");
+  } catch (err) {
+    return;
+  }
+  for (const [id, field] of Object.entries(CONFIG_FIELDS)) {
+    const input = document.getElementById(id);
+    const [section, name] = field.split(".");
+    const spec = schema.properties[section].properties[name];
+    if (!input || !spec) continue;
+    // salary_min is 0 or a range, so only the range's upper bound and step apply
+    const range = (spec.anyOf || []).find((option) => option.type === "integer") || spec;
+    if (spec.minimum !== undefined) input.min = spec.minimum;
+    if (range.maximum !== undefined) input.max = range.maximum;
+    if (range.multipleOf !== undefined) input.step = range.multipleOf;
+    if (spec.minLength) input.required = true;
+    if (!input.title) input.title = spec.description || "";
+  }
+}
+
 async function loadConfig() {
-  const config = await fetchJson("/api/config");
+  const res = await fetchChecked("api/config");
+  configEtag = res.headers.get("ETag");
+  const config = await res.json();
   const keywords = document.getElementById("keywords");
   const location = document.getElementById("location");
-  const remote = document.getElementById("remote");
+  const easyApply = document.getElementById("easy_apply");
+  const postedWithin = document.getElementById("posted_within_hours");
+  const sortByDate = document.getElementById("sort_by_date");
   const salaryMin = document.getElementById("salary_min");
   const intervalHours = document.getElementById("interval_hours");
 
   if (keywords) keywords.value = config.search.keywords || "";
   if (location) location.value = config.search.location || "";
-  if (remote) remote.checked = !!config.search.remote;
+  for (const input of document.querySelectorAll('input[name="workplace"]')) {
+    input.checked = (config.search.workplace || []).includes(input.value);
+  }
+  if (easyApply) easyApply.checked = !!config.search.easy_apply;
+  if (postedWithin) postedWithin.value = config.search.posted_within_hours ?? 24;
+  if (sortByDate) sortByDate.checked = config.search.sort_by === "date";
   if (salaryMin) salaryMin.value = config.search.salary_min || 0;
   if (intervalHours) intervalHours.value = (config.schedule && config.schedule.interval_hours) || 4;
+
+  // Values set by environment variables can't be changed from here
+  const sources = config.sources || {};
+  for (const [id, field] of Object.entries(CONFIG_FIELDS)) {
+    const input = document.getElementById(id);
+    if (!input) continue;
+    const pinned = sources[field] === "env";
+    input.disabled = pinned;
+    if (pinned) input.title = "Set by an environment variable";
+  }
+}
+
+function showReloadError(error) {
+  setStatus(
+    document.getElementById("config-status"),
+    `config.toml was not applied, the previous config is still running: ${error.message}`,
+    true,
+  );
+}
+
+async function loadReloadStatus() {
+  try {
+    const status = await fetchJson("api/config/status");
+    if (status.error) showReloadError(status.error);
+  } catch (err) {
+    // Shown by the config form already if the API is down
+  }
+}
+
+// Follow config changes made elsewhere (config.toml edits, other users)
+function watchConfig() {
+  const events = new EventSource("api/events");
+  events.onmessage = (message) => {
+    const event = JSON.parse(message.data);
+    if (event.type === "config_invalid") {
+      showReloadError(event.error);
+    } else if (event.type === "config_changed" && event.etag !== configEtag) {
+      loadConfig();
+      setStatus(document.getElementById("config-status"), `Config updated from ${event.source}.`);
+    } else if (event.type === "session_warning") {
+      showSession(event.session);
+    }
+  };
+}
+
+function showSession(report) {
+  const isProblem = report.status === "expiring" || report.status === "expired";
+  setStatus(document.getElementById("session-status"), `LinkedIn session: ${report.status} - ${report.message}`, isProblem);
+}
+
+async function loadSession() {
+  const statusEl = document.getElementById("session-status");
+  if (!statusEl) return;
+
+  try {
+    showSession(await fetchJson("api/session"));
+  } catch (err) {
+    setStatus(statusEl, `Failed to check session: ${err.message}`, true);
+  }
 }
 
 async function saveConfig(event) {
   event.preventDefault();
   const statusEl = document.getElementById("config-status");
 
-  const payload = {
+  // Only the fields the form edits; the server merges them into the rest
+  const patch = {
     search: {
       keywords: document.getElementById("keywords").value.trim(),
       location: document.getElementById("location").value.trim(),
-      remote: document.getElementById("remote").checked,
+      workplace: [...document.querySelectorAll('input[name="workplace"]:checked')].map((input) => input.value),
+      easy_apply: document.getElementById("easy_apply").checked,
+      posted_within_hours: Number.parseInt(document.getElementById("posted_within_hours").value, 10) || 0,
+      sort_by: document.getElementById("sort_by_date").checked ? "date" : "relevance",
       salary_min: Number.parseInt(document.getElementById("salary_min").value, 10) || 0,
     },
     schedule: {
@@ -48,10 +187,12 @@ async function saveConfig(event) {
   };
 
   try {
-    await fetchJson("/api/config", {
-      method: "POST",
-      body: JSON.stringify(payload),
+    const res = await fetchChecked("api/config", {
+      method: "PATCH",
+      headers: configEtag ? { "If-Match": configEtag } : {},
+      body: JSON.stringify(patch),
     });
+    configEtag = res.headers.get("ETag");
     setStatus(statusEl, "Config saved.");
   } catch (err) {
     setStatus(statusEl, `Failed to save config: ${err.message}`, true);
@@ -62,10 +203,10 @@ async function runNow() {
   const statusEl = document.getElementById("config-status");
   setStatus(statusEl, "Running scrape...");
   try {
-    const summary = await fetchJson("/api/run", { method: "POST" });
+    const summary = await fetchJson("api/run", { method: "POST" });
     setStatus(
       statusEl,
-      `Done. New jobs: ${summary.new_jobs}, Today: ${summary.today_jobs}.`
+      `Done. New jobs: ${summary.new_jobs}, Reposts: ${summary.duplicate_jobs}, Today: ${summary.today_jobs}.`
     );
   } catch (err) {
     setStatus(statusEl, `Run failed: ${err.message}`, true);
@@ -78,11 +219,11 @@ async function loadJobs() {
   if (!listEl || !metaEl) return;
 
   try {
-    const snapshot = await fetchJson("/api/jobs");
+    const snapshot = await fetchJson("api/jobs");
     const updated = snapshot.updated_at
       ? new Date(snapshot.updated_at).toLocaleString()
       : "No runs yet";
-    metaEl.textContent = `Updated: ${updated} • Total today: ${snapshot.jobs.length} • New: ${snapshot.new_jobs.length}`;
+    metaEl.textContent = `Updated: ${updated} • Total today: ${snapshot.jobs.length} • New: ${snapshot.new_jobs.length} • Reposts: ${(snapshot.duplicates || []).length}`;
 
     listEl.innerHTML = "";
     if (snapshot.jobs.length === 0) {
@@ -120,11 +261,63 @@ async function loadJobs() {
   }
 }
 
+async function login(event) {
+  event.preventDefault();
+  const statusEl = document.getElementById("login-status");
+  setStatus(statusEl, "Logging in...");
+  try {
+    await fetchJson("api/login", {
+      method: "POST",
+      body: JSON.stringify({
+        username: document.getElementById("username").value,
+        password: document.getElementById("password").value,
+      }),
+    });
+    window.location.href = "./";
+  } catch (err) {
+    setStatus(statusEl, err.message, true);
+  }
+}
+
+async function logout(event) {
+  event.preventDefault();
+  await fetch("api/logout", {
+    method: "POST",
+    headers: { "X-CSRF-Token": cookieValue("fresh_post_csrf") },
+  });
+  window.location.href = "login";
+}
+
+async function showLogout() {
+  const nav = document.querySelector(".nav");
+  if (!nav) return;
+  const status = await fetchJson("api/auth");
+  if (!status.username) return;
+
+  const link = document.createElement("a");
+  link.href = "login";
+  link.className = "nav-link nav-logout";
+  link.textContent = `Log out ${status.username}`;
+  link.addEventListener("click", logout);
+  nav.appendChild(link);
+}
+
 function init() {
+  const loginForm = document.getElementById("login-form");
+  if (loginForm) {
+    loginForm.addEventListener("submit", login);
+    return;
+  }
+  showLogout();
+
   const form = document.getElementById("config-form");
   if (form) {
     form.addEventListener("submit", saveConfig);
     loadConfig();
+    applySchema();
+    loadReloadStatus();
+    watchConfig();
+    loadSession();
 
     const runButton = document.getElementById("run-now");
     if (runButton) {